@@ -6,6 +6,10 @@ use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use memmap::MmapOptions;
 use wd::components::home::get_visible_lines;
 use wd::dateparser;
+use wd::dateparser::datetime::{Family, Parse};
+use wd::drainrs::RecordParser;
+use wd::records::{Folds, Records};
+use wd::template_index::{default_threads, TemplateIndex};
 
 fn criterion_benchmark(c: &mut Criterion) {
     let file = File::open("./hugefile.txt").unwrap();
@@ -15,7 +19,38 @@ fn criterion_benchmark(c: &mut Criterion) {
     // Used this to determine 30us vs 57 us for get_visible_lines returning a copy rather than a Cow, on a 40kb file.
     // Not the bottleneck, go for it.
     c.bench_function("hugefile.txt getviz", |b| {
-        b.iter(|| get_visible_lines(black_box(mmap.as_bstr()), &vec![], 10000, 10000, 0))
+        b.iter(|| {
+            get_visible_lines(
+                black_box(mmap.as_bstr()),
+                &Records::default(),
+                &Folds::default(),
+                &[],
+                &mut RecordParser::default(),
+                None,
+                10000,
+                10000,
+                0,
+            )
+        })
+    });
+    c.bench_function("hugefile.txt template index", |b| {
+        b.iter(|| {
+            TemplateIndex::build(
+                black_box(&mmap),
+                &mut RecordParser::default(),
+                &Records::default(),
+            )
+        })
+    });
+    c.bench_function("hugefile.txt template index parallel", |b| {
+        b.iter(|| {
+            TemplateIndex::build_parallel(
+                black_box(&mmap),
+                &mut RecordParser::default(),
+                &Records::default(),
+                default_threads(),
+            )
+        })
     });
     // c.bench_function("hugefile.txt getviz copy", |b| b.iter(|| get_visible_lines_slow(black_box(mmap.as_bstr()), &vec!(), 10000, 10000)));
     let s = "04/04/1997 12:04:01";
@@ -23,6 +58,10 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("dateparser generic", |b| {
         b.iter(|| dateparser::parse_with_timezone(black_box(s), &Local, None))
     });
+    c.bench_function("dateparser hinted", |b| {
+        let parse = Parse::new(&Local, None, None);
+        b.iter(|| parse.parse_hinted(black_box(s), Some(Family::SlashMdy)))
+    });
     // 67ns to parse the epoch timestamp though, dang, must be nice.
     c.bench_function("dateparser generic epoch", |b| {
         b.iter(|| dateparser::parse_with_timezone(black_box("1687208330"), &Local, None))
@@ -1,12 +1,14 @@
 use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Local, Utc};
 // use crossterm::event::KeyEvent;
-use ratatui::crossterm::event::KeyEvent;
 use env_logger::filter::Filter;
 use futures::{FutureExt, StreamExt};
+use ratatui::crossterm::event::KeyEvent;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, trace};
 
+use crate::fields::FieldFilter;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FilterType {
     // It might be cool to have an ! button on filters,
@@ -24,9 +26,21 @@ impl FilterType {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterKind {
+    /// Line contains `needle`.
+    Substring,
+    /// Line is recognized by drain as this template id. `needle` holds the template for display.
+    Template(usize),
+    /// Record has a field that satisfies this, e.g. `level=error`. `needle` holds what was typed after the `.`,
+    /// which records without fields are searched for instead.
+    Field(FieldFilter),
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LineFilter {
     pub needle: String,
+    pub kind: FilterKind,
     pub filter_type: FilterType,
     pub enabled: bool,
 }
@@ -34,6 +48,31 @@ impl LineFilter {
     pub fn new(needle: String, filter_type: FilterType) -> Self {
         Self {
             needle,
+            kind: FilterKind::Substring,
+            filter_type,
+            enabled: true,
+        }
+    }
+
+    /// A field filter if `needle` is a `.` and then something like `level=error`, otherwise a substring filter,
+    /// so text that happens to have an `=` or `>` in it is still searched for as is.
+    pub fn from_input(needle: String, filter_type: FilterType) -> Self {
+        let field = needle.strip_prefix('.').and_then(FieldFilter::parse);
+        match field {
+            Some(field) => Self {
+                needle: needle[1..].to_string(),
+                kind: FilterKind::Field(field),
+                filter_type,
+                enabled: true,
+            },
+            None => Self::new(needle, filter_type),
+        }
+    }
+
+    pub fn new_template(template_id: usize, template: String, filter_type: FilterType) -> Self {
+        Self {
+            needle: template,
+            kind: FilterKind::Template(template_id),
             filter_type,
             enabled: true,
         }
@@ -52,6 +91,58 @@ pub enum FilterListAction {
     CloseNew,
     ConfirmNew,
     Toggle,
+
+    /// Filter by the drain template of the line at the top of the screen.
+    NewTemplateAtCursor(FilterType),
+    /// Filter by this drain template id.
+    AddTemplate(usize, FilterType),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateListAction {
+    OpenTemplateScreen,
+    NextItem,
+    PrevItem,
+    CloseList,
+    Filter(FilterType),
+    /// Show the parameters of the selected template.
+    ShowParams,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamsAction {
+    NextRow,
+    PrevRow,
+    NextColumn,
+    PrevColumn,
+    /// Jump to the occurrence in the selected row.
+    GoToRow,
+    Close,
+}
+
+/// Changes to the column view of records with fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnsAction {
+    SelectPrev,
+    SelectNext,
+    MoveLeft,
+    MoveRight,
+    Narrower,
+    Wider,
+    Hide,
+    ShowAll,
+    /// Keep the selected column on screen however far the others are scrolled.
+    TogglePin,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffListAction {
+    OpenDiffScreen,
+    NextItem,
+    PrevItem,
+    CloseList,
+    /// Jump to the first occurrence of the selected template in the bad log.
+    GoToFirst,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -60,10 +151,6 @@ pub enum Direction {
     Next,
 }
 
-pub struct TimeDelta {
-    num_seconds: i64,
-}
-
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CursorMove {
     /// 'j' or 'k' in less
@@ -72,13 +159,15 @@ pub enum CursorMove {
     End(Direction),
     /// Percentage through the file. '54%' in less => 0.54
     Percentage(f64),
-    // /// Absolute lineno, ':54' in less.
-    // LineNo(u64),
+    /// Absolute lineno, ':54' in less.
+    LineNo(usize),
     /// Put the cursor at the beginning of the first line with a timestamp >= this timestamp.
     Timestamp(DateTime<Utc>),
-    // /// E.g. "+1s, +5m". TODO
-    // TimeDelta(TimeDelta),
+    /// E.g. "+1s, +5m", relative to the timestamp of the record at the top of the screen.
+    TimeDelta(chrono::TimeDelta),
     Screenful(Direction),
+    /// Put the cursor at the beginning of the line containing this byte.
+    ByteOffset(usize),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -96,6 +185,10 @@ pub enum Action {
     RepeatSearch(Direction),
 
     FilterListAction(FilterListAction),
+    TemplateListAction(TemplateListAction),
+    DiffListAction(DiffListAction),
+    ParamsAction(ParamsAction),
+    ColumnsAction(ColumnsAction),
 
     OpenTextEntry,
     // TODO Move these and the .show to pub members of the sub-component?
@@ -105,6 +198,16 @@ pub enum Action {
 
     /// Use drainrs to autoskip what's on screen.
     AutoSkip,
+    /// Put the cursor on the next record whose template is rare across the whole file.
+    NextRare,
+    /// Show timestamps as written, in local time, in UTC, or relative to the record at the top of the screen.
+    CycleTimeDisplay,
+    /// Show records with fields as written, collapsed to key=value, pretty-printed, or as columns.
+    CycleFieldView,
+    /// Fold the long record at the top of the screen to its first line, or unfold it.
+    ToggleFold,
+    /// Fold every long record, or unfold every one.
+    ToggleFoldAll,
 
     Noop,
 }
@@ -115,6 +218,12 @@ pub struct ActionHandler {
     pub receiver: mpsc::UnboundedReceiver<Action>,
 }
 
+impl Default for ActionHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ActionHandler {
     pub fn new() -> Self {
         let (sender, receiver) = mpsc::unbounded_channel();
@@ -11,6 +11,7 @@ use memmap::{Mmap, MmapOptions};
 use crate::{
     action::{Action, ActionHandler},
     components::{home::Home, Component},
+    config::Config,
     event::EventHandler,
     tui::Tui,
 };
@@ -23,7 +24,7 @@ pub struct App {
 }
 
 impl App {
-    pub fn new(tick_rate: u64, filename: String, mmap: Mmap) -> Self {
+    pub fn new(tick_rate: u64, filename: String, mmap: Mmap, config: Config) -> Self {
         let arc_mmap = Arc::new(mmap);
         let tui = Arc::new(Mutex::new(
             Tui::new().context(anyhow!("Unable to create TUI")).unwrap(),
@@ -31,7 +32,7 @@ impl App {
         let events = EventHandler::new(tick_rate);
         let actions = ActionHandler::new();
 
-        let home = Arc::new(Mutex::new(Home::new(filename, arc_mmap)));
+        let home = Arc::new(Mutex::new(Home::new(filename, arc_mmap, config)));
 
         Self {
             tui,
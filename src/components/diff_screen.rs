@@ -0,0 +1,111 @@
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState},
+};
+
+use crate::{
+    action::{Action, CursorMove, DiffListAction},
+    template_diff::{DiffKind, TemplateDiff},
+};
+
+use super::{Component, Frame};
+
+/// Report from `wd diff`, shown over the bad log so each template can be looked at in context.
+#[derive(Default)]
+pub struct DiffScreen {
+    pub show: bool,
+    items: Vec<TemplateDiff>,
+    state: ListState,
+}
+
+impl DiffScreen {
+    pub fn set_items(&mut self, items: Vec<TemplateDiff>) {
+        self.items = items;
+        self.state.select((!self.items.is_empty()).then_some(0));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl Component for DiffScreen {
+    fn init(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_key_event(&self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => Action::DiffListAction(DiffListAction::CloseList),
+            KeyCode::Char('j') | KeyCode::Down => Action::DiffListAction(DiffListAction::NextItem),
+            KeyCode::Char('k') | KeyCode::Up => Action::DiffListAction(DiffListAction::PrevItem),
+            KeyCode::Enter => Action::DiffListAction(DiffListAction::GoToFirst),
+            _ => Action::Tick,
+        }
+    }
+
+    fn dispatch(&mut self, action: Action) -> Option<Action> {
+        if self.items.is_empty() {
+            return None;
+        }
+        let last = self.items.len() - 1;
+        let curr = self.state.selected();
+        if let Action::DiffListAction(da) = action {
+            match da {
+                DiffListAction::NextItem => {
+                    self.state
+                        .select(curr.map(|c| if c == last { 0 } else { c + 1 }));
+                }
+                DiffListAction::PrevItem => {
+                    self.state
+                        .select(curr.map(|c| if c == 0 { last } else { c - 1 }));
+                }
+                DiffListAction::GoToFirst => {
+                    let offset = self.items.get(curr?)?.bad_first_seen;
+                    if offset.is_some() {
+                        self.show = false;
+                    }
+                    return Some(Action::CursorMove(CursorMove::ByteOffset(offset?)));
+                }
+                DiffListAction::OpenDiffScreen => unimplemented!(),
+                DiffListAction::CloseList => unimplemented!(),
+            }
+        }
+        None
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, rect: Rect) {
+        let items: Vec<_> = self
+            .items
+            .iter()
+            .map(|diff| {
+                let (label, color) = match diff.kind {
+                    DiffKind::OnlyBad => ("only bad".to_string(), Color::Red),
+                    DiffKind::OnlyGood => ("only good".to_string(), Color::Green),
+                    DiffKind::Changed(ratio) => (format!("x{:.1}", ratio), Color::Yellow),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{:>9} ", label), Style::default().fg(color)),
+                    Span::raw(format!(
+                        "{:>7} -> {:<7} | {}",
+                        diff.good_count, diff.bad_count, diff.template
+                    )),
+                ]))
+            })
+            .collect();
+        let l = List::new(items)
+            .block(
+                Block::default()
+                    .title("Template diff, good -> bad: (enter) Go to first in bad log (q/escape) Close ")
+                    .borders(Borders::ALL.difference(Borders::BOTTOM)),
+            )
+            .style(Style::default().fg(Color::White))
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+            .highlight_symbol(">> ");
+        f.render_stateful_widget(l, rect, &mut self.state);
+    }
+}
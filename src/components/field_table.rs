@@ -0,0 +1,363 @@
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Cell, Row, Table},
+};
+
+use crate::{
+    action::{Action, ColumnsAction},
+    fields::{value_style, value_text, Fields},
+};
+
+use super::{home::highlight_spans, Component, Frame};
+
+/// Columns are no wider than this unless asked, so one long value doesn't push the rest off screen.
+const MAX_WIDTH: u16 = 40;
+/// How much wider or narrower one key press makes a column.
+const WIDTH_STEP: u16 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Column {
+    key: String,
+    /// Set by the user, otherwise the column is as wide as the widest value seen so far.
+    width: Option<u16>,
+    seen: u16,
+    hidden: bool,
+    /// Pinned columns are drawn first and never scroll off screen.
+    pinned: bool,
+}
+
+impl Column {
+    /// `key` or `key:width`.
+    fn parse(spec: &str) -> Self {
+        let (key, width) = match spec.rsplit_once(':') {
+            Some((key, width)) if width.parse::<u16>().is_ok() => (key, width.parse().ok()),
+            _ => (spec, None),
+        };
+        Self {
+            key: key.to_string(),
+            width,
+            seen: text_width(key),
+            hidden: false,
+            pinned: false,
+        }
+    }
+
+    fn width(&self) -> u16 {
+        self.width.unwrap_or(self.seen)
+    }
+}
+
+fn text_width(s: &str) -> u16 {
+    s.chars().count().min(MAX_WIDTH.into()) as u16
+}
+
+/// A record on screen: its fields, or the line itself if it has none.
+pub struct FieldRow {
+    /// Drawn in a narrow first column, e.g. the rare marker.
+    pub gutter: Option<Span<'static>>,
+    pub fields: Option<Fields>,
+    pub line: Line<'static>,
+}
+
+/// Records with fields as a table, one column per field, whose columns can be hidden, reordered, pinned and
+/// resized.
+#[derive(Default)]
+pub struct FieldTable {
+    columns: Vec<Column>,
+    /// Index into `columns`.
+    selected: usize,
+    /// How many unpinned columns are scrolled off to the left.
+    scroll: usize,
+    rows: Vec<FieldRow>,
+    needle: String,
+}
+
+impl FieldTable {
+    /// `specs` are like [`Column::parse`] takes, `pinned` is typically the timestamp.
+    pub fn new(specs: &[String], hidden: &[String], pinned: Option<&str>) -> Self {
+        let columns = specs
+            .iter()
+            .map(|spec| {
+                let mut column = Column::parse(spec);
+                column.hidden = hidden.contains(&column.key);
+                column.pinned = Some(column.key.as_str()) == pinned;
+                column
+            })
+            .collect();
+        let mut table = Self {
+            columns,
+            ..Self::default()
+        };
+        table.selected = table.order().first().copied().unwrap_or(0);
+        table
+    }
+
+    /// The records to draw next, and the search to highlight in them.
+    pub fn set_rows(&mut self, rows: Vec<FieldRow>, needle: &str) {
+        for column in &mut self.columns {
+            for fields in rows.iter().filter_map(|row| row.fields.as_ref()) {
+                if let Some(text) = fields.text(&column.key) {
+                    column.seen = column.seen.max(text_width(&text));
+                }
+            }
+        }
+        self.rows = rows;
+        self.needle = needle.to_string();
+    }
+
+    /// Indices of the columns that aren't hidden, pinned ones first, in the order they're drawn.
+    fn order(&self) -> Vec<usize> {
+        let visible = |pinned| {
+            self.columns
+                .iter()
+                .enumerate()
+                .filter(move |(_, c)| !c.hidden && c.pinned == pinned)
+                .map(|(i, _)| i)
+        };
+        visible(true).chain(visible(false)).collect()
+    }
+
+    /// Indices of the columns that fit in `width`, after scrolling the selected one into view.
+    fn layout(&mut self, width: u16) -> Vec<usize> {
+        let (pinned, unpinned): (Vec<_>, Vec<_>) = self
+            .order()
+            .into_iter()
+            .partition(|&i| self.columns[i].pinned);
+        // Each column is followed by a space.
+        let span = |i: &usize| self.columns[*i].width() + 1;
+        let room = width.saturating_sub(pinned.iter().map(span).sum());
+        self.scroll = self.scroll.min(unpinned.len().saturating_sub(1));
+        // Don't leave room on the right that columns scrolled off the left would fit in.
+        while self.scroll > 0 && unpinned[self.scroll - 1..].iter().map(span).sum::<u16>() <= room {
+            self.scroll -= 1;
+        }
+        if let Some(at) = unpinned.iter().position(|&i| i == self.selected) {
+            self.scroll = self.scroll.min(at);
+            while self.scroll < at
+                && unpinned[self.scroll..=at].iter().map(span).sum::<u16>() > room
+            {
+                self.scroll += 1;
+            }
+        }
+        let mut used = 0;
+        let shown = unpinned
+            .iter()
+            .skip(self.scroll)
+            .enumerate()
+            .take_while(|(n, i)| {
+                used += span(i);
+                *n == 0 || used <= room
+            });
+        pinned
+            .iter()
+            .copied()
+            .chain(shown.map(|(_, i)| *i))
+            .collect()
+    }
+
+    fn select(&mut self, forward: bool) {
+        let order = self.order();
+        let Some(at) = order.iter().position(|&i| i == self.selected) else {
+            self.selected = order.first().copied().unwrap_or(0);
+            return;
+        };
+        self.selected = match forward {
+            true => order[(at + 1) % order.len()],
+            false => order[(at + order.len() - 1) % order.len()],
+        };
+    }
+
+    /// Swaps the selected column with the next one drawn on that side, if it's pinned the same way.
+    fn move_selected(&mut self, forward: bool) {
+        let order = self.order();
+        let Some(at) = order.iter().position(|&i| i == self.selected) else {
+            return;
+        };
+        let other = match forward {
+            true => order.get(at + 1),
+            false => at.checked_sub(1).and_then(|at| order.get(at)),
+        };
+        if let Some(&other) = other {
+            if self.columns[other].pinned == self.columns[self.selected].pinned {
+                self.columns.swap(self.selected, other);
+                self.selected = other;
+            }
+        }
+    }
+
+    fn cells(&self, row: &FieldRow, shown: &[usize]) -> Vec<Line<'static>> {
+        let mut cells: Vec<Line> = match &row.fields {
+            Some(fields) => shown
+                .iter()
+                .map(|&i| {
+                    let key = &self.columns[i].key;
+                    match fields.get(key) {
+                        Some(value) => Line::styled(
+                            value_text(value).replace('\n', " "),
+                            value_style(key, value),
+                        ),
+                        None => Line::default(),
+                    }
+                })
+                .collect(),
+            // A stray line of text goes in the last column, which has the most room.
+            None => {
+                let mut cells = vec![Line::default(); shown.len()];
+                if let Some(last) = cells.last_mut() {
+                    *last = row.line.clone();
+                }
+                cells
+            }
+        };
+        if !self.needle.is_empty() {
+            for cell in &mut cells {
+                highlight_spans(cell, &self.needle);
+            }
+        }
+        cells
+    }
+}
+
+impl Component for FieldTable {
+    fn init(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_key_event(&self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Left => Action::ColumnsAction(ColumnsAction::SelectPrev),
+            KeyCode::Right => Action::ColumnsAction(ColumnsAction::SelectNext),
+            KeyCode::Char('<') => Action::ColumnsAction(ColumnsAction::MoveLeft),
+            KeyCode::Char('>') => Action::ColumnsAction(ColumnsAction::MoveRight),
+            KeyCode::Char('-') => Action::ColumnsAction(ColumnsAction::Narrower),
+            KeyCode::Char('+') | KeyCode::Char('=') => Action::ColumnsAction(ColumnsAction::Wider),
+            KeyCode::Char('x') => Action::ColumnsAction(ColumnsAction::Hide),
+            KeyCode::Char('X') => Action::ColumnsAction(ColumnsAction::ShowAll),
+            KeyCode::Char('p') => Action::ColumnsAction(ColumnsAction::TogglePin),
+            _ => Action::Tick,
+        }
+    }
+
+    fn dispatch(&mut self, action: Action) -> Option<Action> {
+        let Action::ColumnsAction(ca) = action else {
+            return None;
+        };
+        if self.columns.is_empty() {
+            return None;
+        }
+        match ca {
+            ColumnsAction::SelectPrev => self.select(false),
+            ColumnsAction::SelectNext => self.select(true),
+            ColumnsAction::MoveLeft => self.move_selected(false),
+            ColumnsAction::MoveRight => self.move_selected(true),
+            ColumnsAction::Narrower => {
+                let column = &mut self.columns[self.selected];
+                column.width = Some(column.width().saturating_sub(WIDTH_STEP).max(1));
+            }
+            ColumnsAction::Wider => {
+                let column = &mut self.columns[self.selected];
+                column.width = Some(column.width() + WIDTH_STEP);
+            }
+            ColumnsAction::Hide => {
+                // Keep at least one column, or there'd be nothing to select.
+                if self.order().len() > 1 {
+                    let hidden = self.selected;
+                    self.select(true);
+                    self.columns[hidden].hidden = true;
+                }
+            }
+            ColumnsAction::ShowAll => self.columns.iter_mut().for_each(|c| c.hidden = false),
+            ColumnsAction::TogglePin => {
+                let column = &mut self.columns[self.selected];
+                column.pinned = !column.pinned;
+            }
+        }
+        None
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, rect: Rect) {
+        let gutter = self.rows.iter().any(|row| row.gutter.is_some());
+        let shown = self.layout(rect.width.saturating_sub(gutter.into()));
+        let gutter_cell = |span: Option<&Span<'static>>| match gutter {
+            true => Some(Cell::from(span.cloned().unwrap_or_default())),
+            false => None,
+        };
+
+        let header = Row::new(gutter_cell(None).into_iter().chain(shown.iter().map(|&i| {
+            let column = &self.columns[i];
+            let mut style = Style::default();
+            if column.pinned {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            if i == self.selected {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            Cell::from(Span::styled(column.key.clone(), style))
+        })))
+        .style(Style::default().add_modifier(Modifier::UNDERLINED));
+        let rows = self.rows.iter().map(|row| {
+            Row::new(
+                gutter_cell(row.gutter.as_ref())
+                    .into_iter()
+                    .chain(self.cells(row, &shown).into_iter().map(Cell::from)),
+            )
+        });
+        let last = shown.len().saturating_sub(1);
+        let widths = gutter.then_some(Constraint::Length(1)).into_iter().chain(
+            shown.iter().enumerate().map(|(n, &i)| match n == last {
+                true => Constraint::Fill(1),
+                false => Constraint::Length(self.columns[i].width()),
+            }),
+        );
+        f.render_widget(Table::new(rows, widths).header(header), rect);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shown(table: &mut FieldTable, width: u16) -> Vec<String> {
+        let shown = table.layout(width);
+        shown
+            .iter()
+            .map(|&i| table.columns[i].key.clone())
+            .collect()
+    }
+
+    #[test]
+    fn layout() {
+        let specs: Vec<_> = ["level:5", "ts:24", "host:10", "msg:20"]
+            .map(String::from)
+            .to_vec();
+        let mut table = FieldTable::new(&specs, &[], Some("ts"));
+        assert_eq!(table.columns[0].width, Some(5));
+        assert_eq!(table.columns[table.selected].key, "ts");
+        assert_eq!(shown(&mut table, 80), ["ts", "level", "host", "msg"]);
+
+        // Only room for the pinned timestamp and one more, which follows the selection.
+        assert_eq!(shown(&mut table, 40), ["ts", "level"]);
+        table.dispatch(Action::ColumnsAction(ColumnsAction::SelectPrev));
+        assert_eq!(table.columns[table.selected].key, "msg");
+        assert_eq!(shown(&mut table, 40), ["ts", "msg"]);
+
+        table.dispatch(Action::ColumnsAction(ColumnsAction::MoveLeft));
+        assert_eq!(shown(&mut table, 80), ["ts", "level", "msg", "host"]);
+        table.dispatch(Action::ColumnsAction(ColumnsAction::Hide));
+        assert_eq!(shown(&mut table, 80), ["ts", "level", "host"]);
+        table.dispatch(Action::ColumnsAction(ColumnsAction::ShowAll));
+        assert_eq!(shown(&mut table, 80), ["ts", "level", "msg", "host"]);
+
+        table.dispatch(Action::ColumnsAction(ColumnsAction::SelectPrev));
+        table.dispatch(Action::ColumnsAction(ColumnsAction::SelectPrev));
+        table.dispatch(Action::ColumnsAction(ColumnsAction::TogglePin));
+        assert_eq!(table.columns[table.selected].key, "level");
+        assert_eq!(shown(&mut table, 80), ["level", "ts", "msg", "host"]);
+        table.dispatch(Action::ColumnsAction(ColumnsAction::Narrower));
+        assert_eq!(table.columns[table.selected].width(), 3);
+    }
+}
@@ -1,4 +1,6 @@
-use crate::action::{Action, CursorMove, FilterListAction, FilterType, LineFilter};
+use crate::action::{Action, CursorMove, FilterKind, FilterListAction, FilterType, LineFilter};
+use crate::drainrs::RecordParser;
+use crate::fields::FieldSource;
 
 #[derive(PartialEq, Eq)]
 pub(crate) enum LineFilterResult {
@@ -7,11 +9,32 @@ pub(crate) enum LineFilterResult {
     Indifferent,
 }
 
-pub(crate) fn line_allowed(filters: &Vec<LineFilter>, line: &str) -> (bool, LineFilterResult) {
+pub(crate) fn line_allowed(
+    filters: &[LineFilter],
+    drain: &mut RecordParser,
+    fields: Option<&FieldSource>,
+    line: &str,
+) -> (bool, LineFilterResult) {
     let mut cur = LineFilterResult::Indifferent;
     let get_active_filters = || filters.iter().filter(|f| f.enabled);
+    // Only pay for drain or field parsing if a filter that needs it is actually on.
+    let mut template_id = None;
+    let mut line_fields = None;
     for filter in get_active_filters() {
-        match (line.contains(&filter.needle), filter.filter_type) {
+        let matched = match &filter.kind {
+            FilterKind::Substring => line.contains(&filter.needle),
+            FilterKind::Template(id) => {
+                let line_template = *template_id.get_or_insert_with(|| drain.template_id(line));
+                line_template == Some(*id)
+            }
+            FilterKind::Field(field) => {
+                match line_fields.get_or_insert_with(|| fields.and_then(|f| f.parse(line))) {
+                    Some(line_fields) => field.matches(line_fields),
+                    None => line.contains(&filter.needle),
+                }
+            }
+        };
+        match (matched, filter.filter_type) {
             (true, crate::action::FilterType::In) => cur = LineFilterResult::Include,
             (true, crate::action::FilterType::Out) => cur = LineFilterResult::Exclude,
             _ => continue,
@@ -12,7 +12,7 @@ use ratatui::{
 };
 
 use crate::action::LineFilter;
-use crate::action::{Action, FilterListAction, FilterType};
+use crate::action::{Action, FilterKind, FilterListAction, FilterType};
 
 use super::{text_entry::TextEntry, Component, Frame};
 
@@ -32,13 +32,17 @@ impl FilterScreen<'_> {
     }
 
     fn confirm_new_filter(&mut self) {
-        self.items.push(LineFilter::new(
+        self.add(LineFilter::from_input(
             self.new.textarea.lines()[0].clone(),
             self.new_filter_type.unwrap(),
         ));
-        self.state.select(Some(self.items.len() - 1));
         self.new_filter_type = None;
         self.new.textarea.delete_line_by_head();
+    }
+
+    pub fn add(&mut self, filter: LineFilter) {
+        self.items.push(filter);
+        self.state.select(Some(self.items.len() - 1));
         info!("Added new filter: {:?}", self.items[self.items.len() - 1]);
     }
 }
@@ -79,6 +83,12 @@ impl Component for FilterScreen<'_> {
             }
             KeyCode::Char('i') => Action::FilterListAction(FilterListAction::New(FilterType::In)),
             KeyCode::Char('o') => Action::FilterListAction(FilterListAction::New(FilterType::Out)),
+            KeyCode::Char('I') => {
+                Action::FilterListAction(FilterListAction::NewTemplateAtCursor(FilterType::In))
+            }
+            KeyCode::Char('O') => {
+                Action::FilterListAction(FilterListAction::NewTemplateAtCursor(FilterType::Out))
+            }
             // KeyCode::Char(' ') => Action::FilterListAction(FilterListAction::Toggle),
             KeyCode::Tab => Action::FilterListAction(FilterListAction::Toggle),
             KeyCode::Char(' ') => Action::FilterListAction(FilterListAction::Toggle),
@@ -99,6 +109,8 @@ impl Component for FilterScreen<'_> {
                     FilterListAction::New(which) => self.new_filter_type = Some(which),
                     FilterListAction::OpenFilterScreen => unimplemented!(),
                     FilterListAction::CloseList => unimplemented!(),
+                    FilterListAction::NewTemplateAtCursor(_) => unimplemented!(),
+                    FilterListAction::AddTemplate(_, _) => unimplemented!(),
                     FilterListAction::CloseNew => {
                         self.new_filter_type = None;
                         self.new.textarea.delete_line_by_head();
@@ -136,7 +148,10 @@ impl Component for FilterScreen<'_> {
                 // .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
                 .constraints([Constraint::Min(3), Constraint::Max(3)])
                 .split(rect);
-            let s = format!("Filter {:?}", self.new_filter_type.unwrap());
+            let s = format!(
+                "Filter {:?}: text, or .key=value for a field",
+                self.new_filter_type.unwrap()
+            );
             let block = Block::default().title(s).borders(Borders::all());
             self.new.textarea.set_block(block);
             self.new.render(f, chunks[1]);
@@ -155,7 +170,12 @@ impl Component for FilterScreen<'_> {
             .items
             .iter()
             .map(|i| {
-                ListItem::new(fmt_status(i.enabled).to_owned() + &i.needle.clone()).style(
+                let kind = match i.kind {
+                    FilterKind::Substring => "",
+                    FilterKind::Template(_) => "template: ",
+                    FilterKind::Field(_) => "field: ",
+                };
+                ListItem::new(fmt_status(i.enabled).to_owned() + kind + &i.needle).style(
                     Style::default().bg(if !i.enabled {
                         Color::DarkGray
                     } else if i.filter_type.include() {
@@ -176,7 +196,7 @@ impl Component for FilterScreen<'_> {
             .block(
                 // TODO Show how many lines a filter is filtering
                 Block::default()
-                    .title("Filters: (i) In (o) Out (tab) Toggle Filter (q/enter/escape) Close ")
+                    .title("Filters: (i) In (o) Out (I/O) In/Out top line's template (tab) Toggle Filter (q/enter/escape) Close ")
                     .borders(Borders::ALL.difference(Borders::BOTTOM)),
             )
             .style(Style::default().fg(Color::White))
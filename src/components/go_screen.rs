@@ -1,7 +1,8 @@
 use std::panic;
 
 use anyhow::Result;
-use chrono::{DateTime, Local, NaiveDate, Utc};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
 use crossterm::{
     cursor::MoveToPreviousLine,
     event::{KeyCode, KeyEvent},
@@ -28,24 +29,54 @@ pub struct GoScreen {
     txt: TextEntry<'static>,
     destination: Option<CursorMove>,
     today: Option<NaiveDate>,
+    /// Zone of times that don't name one, local time if None.
+    tz: Option<Tz>,
+    /// Last timestamp in the file, what `end-5m` counts back from.
+    end: Option<DateTime<Utc>>,
+    /// The last `+5m` style move, which an empty entry repeats.
+    last_delta: Option<TimeDelta>,
 }
 
 impl GoScreen {
     fn confirm(&mut self) {
         self.show = false;
         let dest = self.txt.pop();
-        self.destination = self.parse_dest(&dest);
+        self.destination = match dest.trim().is_empty() {
+            // Makes stepping through an incident a matter of g, enter.
+            true => self.last_delta.map(CursorMove::TimeDelta),
+            false => self.parse_dest(&dest),
+        };
+        if let Some(CursorMove::TimeDelta(delta)) = self.destination {
+            self.last_delta = Some(delta);
+        }
     }
 
     fn parse_dest(&self, dest: &str) -> Option<CursorMove> {
         // TODO Color red if not parseable.
+        let dest = dest.trim();
+        if let Some(line_no) = dest.strip_prefix(':') {
+            return Some(CursorMove::LineNo(line_no.trim().parse().ok()?));
+        }
         if dest.contains('%') {
             return Some(CursorMove::Percentage(dest[..dest.len() - 1].parse().ok()?));
         }
+        if let Some(delta) = parse_delta(dest) {
+            return Some(CursorMove::TimeDelta(delta));
+        }
+        if let Some(delta) = dest.strip_prefix("now").and_then(offset_from) {
+            return Some(CursorMove::Timestamp(Utc::now().checked_add_signed(delta)?));
+        }
+        if let Some(delta) = dest.strip_prefix("end").and_then(offset_from) {
+            return Some(CursorMove::Timestamp(self.end?.checked_add_signed(delta)?));
+        }
 
-        let res = crate::dateparser::parse_with_timezone(dest, &Local, self.today).ok();
-        if res.is_some() {
-            return Some(CursorMove::Timestamp(res.unwrap()));
+        let (dest, zone) = crate::dateparser::timezone::split_zone_name(dest);
+        let res = match zone.or(self.tz) {
+            Some(tz) => parse_day_and_time(dest, &tz, self.today),
+            None => parse_day_and_time(dest, &Local, self.today),
+        };
+        if let Some(ts) = res {
+            return Some(CursorMove::Timestamp(ts));
         }
 
         None
@@ -91,6 +122,80 @@ impl GoScreen {
     pub fn set_today(&mut self, today: Option<NaiveDate>) {
         self.today = today;
     }
+
+    pub fn set_tz(&mut self, tz: Option<Tz>) {
+        self.tz = tz;
+    }
+
+    pub fn set_end(&mut self, end: Option<DateTime<Utc>>) {
+        self.end = end;
+    }
+}
+
+/// `+5m`, `-30s`, `+1h15m`, `+250ms`: a sign, then any number of amounts each with a unit of ms, s, m, h or d.
+fn parse_delta(s: &str) -> Option<TimeDelta> {
+    let (negative, mut rest) = match s.as_bytes().first()? {
+        b'+' => (false, &s[1..]),
+        b'-' => (true, &s[1..]),
+        _ => return None,
+    };
+    rest = rest.trim_start();
+    if rest.is_empty() {
+        return None;
+    }
+    let mut total = TimeDelta::zero();
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let amount: i64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let delta = match &rest[..unit_len] {
+            "ms" => TimeDelta::try_milliseconds(amount),
+            "s" => TimeDelta::try_seconds(amount),
+            "m" => TimeDelta::try_minutes(amount),
+            "h" => TimeDelta::try_hours(amount),
+            "d" => TimeDelta::try_days(amount),
+            _ => None,
+        }?;
+        total = total.checked_add(&delta)?;
+        rest = rest[unit_len..].trim_start();
+    }
+    Some(if negative { -total } else { total })
+}
+
+/// What follows `now` or `end`: nothing, or a [`parse_delta`] offset.
+fn offset_from(rest: &str) -> Option<TimeDelta> {
+    match rest.trim() {
+        "" => Some(TimeDelta::zero()),
+        rest => parse_delta(rest),
+    }
+}
+
+/// A time, optionally after `today` or `yesterday`, which go by the wall clock rather than the log.
+/// A day on its own is its midnight.
+fn parse_day_and_time<Tz2: TimeZone>(
+    dest: &str,
+    tz: &Tz2,
+    today: Option<NaiveDate>,
+) -> Option<DateTime<Utc>> {
+    let wall_today = Utc::now().with_timezone(tz).date_naive();
+    let (day, time) = match dest.split_once(' ').unwrap_or((dest, "")) {
+        ("today", time) => (wall_today, time),
+        ("yesterday", time) => (wall_today.pred_opt()?, time),
+        _ => return crate::dateparser::parse_with_timezone(dest, tz, today).ok(),
+    };
+    match time.trim() {
+        "" => Some(
+            tz.from_local_datetime(&day.and_time(NaiveTime::MIN))
+                .earliest()?
+                .with_timezone(&Utc),
+        ),
+        time => crate::dateparser::parse_with_timezone(time, tz, Some(day)).ok(),
+    }
 }
 
 impl Component for GoScreen {
@@ -142,7 +247,7 @@ impl Component for GoScreen {
         .split(rect);*/
         // let s = format!("Filter {:?}", self.new_filter_type.unwrap());
         let block = Block::default()
-    .title("GoTo: (Enter) Confirm. Examples: \"09:44:21\" \"+5m\" (g) beginning (q/enter/escape) Close ")
+    .title("GoTo: (Enter) Confirm, or repeat the last +/- if empty. Examples: \"09:44:21\" \"09:44 Europe/Berlin\" \"yesterday 14:00\" \"+5m\" \"end-5m\" \":120\" (g) beginning (q/enter/escape) Close ")
     .borders(Borders::ALL.difference(Borders::BOTTOM))
     .style(Style::default().fg(if self.valid_entry() { Color::Green } else { Color::Red }));
         self.txt.textarea.set_block(block);
@@ -150,3 +255,43 @@ impl Component for GoScreen {
         // f.render_widget(self.txt, rect);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn destinations() {
+        assert_eq!(parse_delta("+5m"), TimeDelta::try_minutes(5));
+        assert_eq!(parse_delta("-30s"), TimeDelta::try_seconds(-30));
+        assert_eq!(parse_delta("+1h15m"), TimeDelta::try_minutes(75));
+        assert_eq!(parse_delta("+250ms"), TimeDelta::try_milliseconds(250));
+        assert_eq!(parse_delta("+5"), None);
+        assert_eq!(parse_delta("+5y"), None);
+        assert_eq!(parse_delta("5m"), None);
+
+        let end = DateTime::from_timestamp(1647935466, 0).unwrap();
+        let mut go = GoScreen::default();
+        go.set_tz(Some(chrono_tz::UTC));
+        assert_eq!(go.parse_dest(":120"), Some(CursorMove::LineNo(120)));
+        assert_eq!(go.parse_dest("end-5m"), None);
+        go.set_end(Some(end));
+        assert_eq!(
+            go.parse_dest("end-5m"),
+            Some(CursorMove::Timestamp(
+                end - TimeDelta::try_minutes(5).unwrap()
+            ))
+        );
+        assert!(matches!(
+            go.parse_dest("now-10m"),
+            Some(CursorMove::Timestamp(ts)) if ts < Utc::now() - TimeDelta::try_minutes(9).unwrap()
+        ));
+        let yesterday = Utc::now().date_naive().pred_opt().unwrap();
+        assert_eq!(
+            go.parse_dest("yesterday 14:00"),
+            Some(CursorMove::Timestamp(
+                yesterday.and_hms_opt(14, 0, 0).unwrap().and_utc()
+            ))
+        );
+    }
+}
@@ -2,13 +2,31 @@ use std::{
     borrow::Cow,
     cmp::{max, min, Ordering},
     collections::{HashMap, HashSet},
+    iter::zip,
+    ops::Range,
     str::pattern::{Pattern, Searcher},
-    sync::Arc,
+    sync::{
+        mpsc::{channel, Receiver, TryRecvError},
+        Arc,
+    },
 };
 
-use crate::{action::Direction, dateparser::datetime::Parse, drainrs::RecordParser};
+use crate::{
+    action::Direction,
+    config::Config,
+    dateparser::datetime::Parse,
+    drainrs::{RecordParsedResult, RecordParser},
+    fields::{value_style, FieldFormat, FieldSource, FieldView, Fields, Locator},
+    records::{self, Folds, Records},
+    template_diff::TemplateDiff,
+    template_index::{default_threads, TemplateIndex},
+    timestamps::{
+        find_line_starting_before, DayIndex, FileOffset, TimeDisplay, TimestampParser,
+        TsBinSearchError,
+    },
+};
 use bstr::{BStr, ByteSlice};
-use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use chrono::{DateTime, Days, Duration, Local, NaiveDate, TimeDelta, TimeZone, Utc};
 use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
 // use log::{debug, info, warn}; when unit testing, use these?
 use memmap::Mmap;
@@ -23,14 +41,21 @@ use tracing::{debug, error, info};
 use tui_textarea::TextArea;
 
 use super::{
+    diff_screen::DiffScreen,
+    field_table::{FieldRow, FieldTable},
     filter::{line_allowed, LineFilterResult},
     filter_screen::FilterScreen,
     go_screen::GoScreen,
     logger::Logger,
+    params_screen::{ParamRow, ParamsScreen},
+    template_screen::TemplateScreen,
     text_entry::TextEntry,
     Component, Frame,
 };
-use crate::action::{Action, CursorMove, FilterListAction, FilterType, LineFilter};
+use crate::action::{
+    Action, ColumnsAction, CursorMove, DiffListAction, FilterKind, FilterListAction, FilterType,
+    LineFilter, ParamsAction, TemplateListAction,
+};
 
 // TODO:
 // 11. Fix CTRL+C not working when the thing is really going
@@ -56,6 +81,7 @@ use crate::action::{Action, CursorMove, FilterListAction, FilterType, LineFilter
 // wd's internal model processes the log file in the following sequence:
 //   file mmap -- raw bytes of the underlying file, mmapped into memory
 //     |--> record parsing -- a log is a series of records, which are basically new-line delimited strings (with not-permanently-specified-exceptions)
+//       |    and which may have named fields, when they're JSON or logfmt. See crate::fields.
 //       |--> record filtering -- filter these records based on their contents and user-provided in/out requirements
 //         |--> display -- apply highlighting/custom formatting and then line wrapping for display to the screen.
 //
@@ -92,16 +118,32 @@ impl Screen {
         }
     }
 
+    /// Rows the view takes up, which can be more than its lines, e.g. in the pretty field view.
+    pub fn rows(&self) -> usize {
+        self.view.iter().map(DispLine::rows).sum()
+    }
+
     pub fn prepend_line(&mut self, line: DispLine) {
-        if !(self.view.len() + 1 < self.screen_size.height.into()) {
+        if !(self.rows() + line.rows() < self.screen_size.height.into()) {
             self.view.pop();
         }
         self.view.insert(0, line);
     }
 
+    /// Scrolls down with no line to add at the bottom, as long as what's left still fills the screen.
+    pub fn drop_first_line(&mut self) -> bool {
+        match self.view.first() {
+            Some(first) if self.rows() - first.rows() + 1 >= self.screen_size.height.into() => {
+                self.view.remove(0);
+                true
+            }
+            _ => false,
+        }
+    }
+
     // This line must already be highlighted. TODO use type system for that.
     pub fn push_line(&mut self, line: DispLine) {
-        if !(self.view.len() + 1 < self.screen_size.height.into()) {
+        if !(self.rows() + line.rows() < self.screen_size.height.into()) {
             self.view.remove(0); // Technically this causes a shift of the vector but I don't care at the moment :-)
         }
         self.view.push(line);
@@ -112,34 +154,65 @@ pub fn highlight_line(line: &mut DispLine, needle: &str) {
     if needle.is_empty() {
         return;
     }
-    let orig_line = line.line.clone();
-    let line_txt = orig_line.spans[0].content.to_owned();
-    let mut searcher = needle.into_searcher(&line_txt);
-    let mut spans = Vec::new();
-    let mut last_plain = 0;
-    while let Some((start, end)) = searcher.next_match() {
-        // let spans
-        spans.push(Span {
-            content: line_txt[last_plain..start].to_owned().into(),
-            style: Style::default(),
-        });
-        spans.push(Span {
-            content: line_txt[start..end].to_owned().into(),
-            style: Style::default().bg(Color::LightGreen),
-        });
-        last_plain = end;
-    }
-    if last_plain != line_txt.len() {
-        spans.push(Span {
-            content: line_txt[last_plain..].to_owned().into(),
-            style: Style::default(),
-        });
+    highlight_spans(&mut line.line, needle);
+    for more in &mut line.more {
+        highlight_spans(more, needle);
     }
-    line.line = Line {
-        spans,
-        alignment: None,
-        style: Style::default(),
+}
+
+/// Colors `range` of a line that's still one span the way the value of the field `key` is colored.
+fn color_range(line: &mut Line<'static>, range: Range<usize>, key: &str) {
+    let [span] = line.spans.as_slice() else {
+        return;
     };
+    let text = &span.content;
+    let Some(value) = text.get(range.clone()) else {
+        return;
+    };
+    let style = value_style(key, &value.into());
+    line.spans = vec![
+        Span::raw(text[..range.start].to_string()),
+        Span::styled(value.to_string(), style),
+        Span::raw(text[range.end..].to_string()),
+    ];
+}
+
+/// Ends a folded record's line with how many more it has, like `[+37 lines]`.
+fn mark_folded(line: &mut DispLine) {
+    if line.hidden_lines > 0 {
+        line.line.spans.push(Span::styled(
+            format!(" [+{} lines]", line.hidden_lines),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+}
+
+/// Splits each span of `line` around the matches of `needle`, keeping the span's own style.
+pub fn highlight_spans(line: &mut Line<'static>, needle: &str) {
+    let mut spans = Vec::new();
+    for span in std::mem::take(&mut line.spans) {
+        let text = span.content;
+        let mut searcher = needle.into_searcher(&text);
+        let mut last_plain = 0;
+        while let Some((start, end)) = searcher.next_match() {
+            spans.push(Span {
+                content: text[last_plain..start].to_owned().into(),
+                style: span.style,
+            });
+            spans.push(Span {
+                content: text[start..end].to_owned().into(),
+                style: span.style.bg(Color::LightGreen),
+            });
+            last_plain = end;
+        }
+        if last_plain != text.len() {
+            spans.push(Span {
+                content: text[last_plain..].to_owned().into(),
+                style: span.style,
+            });
+        }
+    }
+    line.spans = spans;
 }
 
 pub fn highlight_lines(lines: &mut [DispLine], needle: &str) {
@@ -151,212 +224,122 @@ pub fn highlight_lines(lines: &mut [DispLine], needle: &str) {
     }
 }
 
+/// Lines of the records in `source` from the line starting at `start` on, as many as fit in `rows` rows of
+/// `cols` columns. A record is filtered as a whole, so its lines are all shown or none are,
+/// even when it starts above `start`. A folded record is only its first line, wherever `start` is in it.
+#[allow(clippy::too_many_arguments)]
 pub fn get_visible_lines(
     source: &BStr,
-    filters: &Vec<LineFilter>,
+    records: &Records,
+    folds: &Folds,
+    filters: &[LineFilter],
+    drain: &mut RecordParser,
+    fields: Option<&FieldSource>,
     rows: u16,
     cols: u16,
-    offset_into_big: usize,
+    start: usize,
 ) -> Vec<DispLine> {
-    // info!("{}x{}", rows, cols);
-    // Dimensions don't  match what I'd expect, and we run out of text before filling screen.
-    // Let's just mult by a constant factor since this "assuming a line may be too big to process at once"
-    // is a huge pessimization anyway.
-    // assert_eq!(rows, 64);
+    // Assumes always linewrap, one byte == one visible width char.
     let mut displayed_rows = 0;
-    let mut rows_for_this_line = 0;
-    let mut used_cols = 0;
-    let mut line_start = 0;
     let mut lines = Vec::with_capacity(1000);
-    let mut in_bad_record = false;
-    let mut maybe_add_line = |lines: &mut Vec<DispLine>,
-                              ending_index: usize,
-                              displayed_rows: &mut u16,
-                              rows_for_this_line: &u16,
-                              line_start: usize| {
-        let line = source[line_start..ending_index].to_str_lossy().into_owned();
-        // TODO Do we need to allow IN filters which match part of a record to display the whole record?
-        // IMO no, you can add a new in filter for the line you're interested in, with higher priority.
-        let is_new_record = {
-            if line_start != ending_index {
-                let first_char = source[line_start];
-                first_char != b'\t' && first_char != b' '
-            } else {
-                false
-            }
-        };
-        if is_new_record {
-            in_bad_record = false;
+    let first = records.record_at(source, start).start;
+    for record in records.forward(source, first) {
+        if !line_allowed(
+            filters,
+            drain,
+            fields,
+            &source[record.clone()].to_str_lossy(),
+        )
+        .0
+        {
+            continue;
         }
-        let matches = line_allowed(filters, &line);
-        let should_print = if in_bad_record {
-            matches.1 == LineFilterResult::Include // Must have exactly matched an include line if it's a part of an otherwise filtered record.
-        } else {
-            matches.0 // Otherwise fallback to the general rules for filters.
+        let record_loc = FileLoc(record.start, record.end);
+        let hidden = folds.hidden_lines(source, &record);
+        let shown = match hidden {
+            0 => usize::MAX,
+            _ => 1,
         };
-        if should_print {
-            lines.push(DispLine {
-                file_loc: FileLoc(offset_into_big + line_start, offset_into_big + ending_index),
-                line: line.into(),
-            });
-            *displayed_rows += rows_for_this_line + 1;
-        } else {
-            in_bad_record = true;
-        }
-        // return false;
-    };
-    // We need some concept of 'records' rather than lines so that we can filter multi-line log msgs easily.
-    // A good generic (if not fast) approach seems to be a newline followed by a timestamp (since we currently assume that timestamps begin the line)
-    // Right now parsing a timestamp is kinda slow (50usec) but this can be vastly improved by remembering what format worked last time and using that first (or exclusively).
-    // Let's give it a shot and see how useable this approach is. Keep in mind that we only need to fill the screen.
-    // As another approximation, we could say that indented lines following a true-start are part of the previous record, as are lines that look like '\n}' or '\n]'.
-    // This approach works for me today without having to attempt parsing timestamps over and over :-) We would anyways have checked 'does this line start with a number'
-    // before continuing to attempt timestamp parsing.
-    // let b = source.as_bytes();
-    // Assumes always linewrap, one byte == one visible width char.
-    for (start, end, c) in source.char_indices() {
-        match c {
-            '\n' => {
-                maybe_add_line(
-                    &mut lines,
-                    start,
-                    &mut displayed_rows,
-                    &rows_for_this_line,
-                    line_start,
-                );
-                line_start = end;
-                rows_for_this_line = 0;
-                used_cols = 0;
-                if displayed_rows == rows {
-                    return lines;
-                }
-            }
-            _ => {
+        for line in records::lines(source, record)
+            .filter(|line| hidden > 0 || line.start >= start)
+            .take(shown)
+        {
+            let mut rows_for_this_line = 0;
+            let mut used_cols = 0;
+            for (_, end, _) in source[line.clone()].char_indices() {
                 used_cols += 1;
                 if used_cols == cols {
-                    // No need to complete out the line atm, TODO.
-                    // Assumption, user will be filtering on something that at least fits in the screen when scrolling by.
                     rows_for_this_line += 1;
                     used_cols = 0;
-                    // Note a line which spans multiple screens will not be fully excluded if the thing we're filtering
-                    // doesn't fit on-screen.
+                    // Assumption, user will be filtering on something that at least fits in the screen when scrolling by.
                     if displayed_rows + rows_for_this_line == rows {
-                        maybe_add_line(
-                            &mut lines,
-                            end,
-                            &mut displayed_rows,
-                            &rows_for_this_line,
-                            line_start,
-                        );
+                        let end = line.start + end;
+                        lines.push(DispLine::new(source, line.start..end, record_loc, hidden));
                         return lines;
                     }
                 }
             }
+            lines.push(DispLine::new(source, line, record_loc, hidden));
+            displayed_rows += rows_for_this_line + 1;
+            if displayed_rows == rows {
+                return lines;
+            }
         }
     }
-    // if used_cols > 0 {
-    // TODO Skip the newline if it terminates this line?
-    maybe_add_line(
-        &mut lines,
-        source.len(),
-        &mut displayed_rows,
-        &rows_for_this_line,
-        line_start,
-    );
-    // }
     lines
 }
 
-/// Find first character of line starting at or before byte_offset.
-fn find_line_starting_before(s: &[u8], byte_offset: usize) -> usize {
-    // mmap[0..byte_offset].iter().rev().find('\n').unwrap_or(0)
-    let bytes_before_offset_of_newline = s[..byte_offset] // or s[0..byte_offset]
-        .iter()
-        .rev()
-        .enumerate()
-        .find_map(|(index, val)| match val {
-            val if *val == b'\n' => Some(index),
-            _ => None,
-        })
-        .unwrap_or(byte_offset);
-    // 4096 -> 3486 should find byte 10. TODO Unit test.
-    let idx = byte_offset - bytes_before_offset_of_newline;
-    assert!(idx == 0 || s[idx - 1] == b'\n');
-    idx
-}
-
 fn find_start_line_pct(mmap: &Mmap, pct: f64) -> usize {
-    let pct = f64::min(f64::max(pct, 0.0), 100.0);
+    let pct = pct.clamp(0.0, 100.0);
     let going_to = (mmap.len() as f64 * (pct / 100.0)).floor() as usize;
     find_line_starting_before(mmap, going_to)
 }
 
-fn parse_date_starting_at(
-    s: &[u8],
-    start_offset: FileOffset,
-    default_date: NaiveDate,
-) -> Option<DateTime<Utc>> {
-    // TODO .. need to use min or is that implicit like Python?
-    let s = std::str::from_utf8(&s[start_offset..min(start_offset + 100, s.len())]).ok()?;
-    let second_space_idx = s
-        .char_indices()
-        .filter_map(|(index, char)| match char == ' ' {
-            true => Some(index),
-            false => None,
-        })
-        .nth(1)?;
-    let s = &s[0..second_space_idx];
-    debug!("Parsing: {}", s);
-    // match crate::dateparser::parse(std::str::from_utf8(&s).unwrap()) {
-    /*match crate::dateparser::parse(s) {
-        Ok(dt) => dt,
-        _ => panic!(),
-    }*/
-
-    // Parse::new(tz, Utc::now().time()).parse(input)
-    // TODO set context with this: https://github.com/waltzofpearls/dateparser/issues/39
-    crate::dateparser::parse_with_timezone(s, &Local, Some(default_date)).ok()
-}
-
-fn find_date_before(
-    s: &[u8],
-    mut byte_offset: FileOffset,
-    default_date: NaiveDate,
-) -> Option<(FileOffset, DateTime<Utc>)> {
-    let mut lines_try = 1000;
-    while lines_try > 0 {
-        let line_start = find_line_starting_before(s, byte_offset);
-        let maybe_ts = parse_date_starting_at(s, line_start, default_date);
-        match maybe_ts {
-            Some(ts) => {
-                return Some((line_start, ts));
-            }
-            None => {
-                byte_offset = line_start - 1;
-                lines_try -= 1;
-            }
-        }
+/// Start of the `line_no`th line, counting from 1 like less does. The last line if there aren't that many.
+fn find_line_number(mmap: &[u8], line_no: usize) -> usize {
+    match line_no.checked_sub(2) {
+        None => 0,
+        Some(newlines) => match mmap.find_iter("\n").nth(newlines) {
+            Some(newline) if newline + 1 < mmap.len() => newline + 1,
+            _ => find_line_starting_before(mmap, mmap.len().saturating_sub(1)),
+        },
     }
-    None
 }
 
 #[cfg(test)]
 mod tests {
-    use chrono::FixedOffset;
-
     use super::*;
 
     static LINES: &str = "03/22/2022 08:51:06 INFO   :...mylogline
 03/22/2022 08:51:08 INFO   :...mylogline";
 
+    #[test]
+    fn line_number() {
+        let lines = b"one\ntwo\nthree\n";
+        assert_eq!(find_line_number(lines, 0), 0);
+        assert_eq!(find_line_number(lines, 1), 0);
+        assert_eq!(find_line_number(lines, 3), 8);
+        assert_eq!(find_line_number(lines, 30), 8);
+    }
+
     #[test]
     fn test_visible() {
         let call = |rows, cols| -> String {
-            get_visible_lines("lol".into(), &vec![], rows, cols, 0)
-                .iter()
-                .map(|l| l.line.spans[0].content.clone().to_owned())
-                .intersperse("\n".to_string().into())
-                .collect()
+            get_visible_lines(
+                "lol".into(),
+                &Records::default(),
+                &Folds::default(),
+                &[],
+                &mut RecordParser::default(),
+                None,
+                rows,
+                cols,
+                0,
+            )
+            .iter()
+            .map(|l| l.line.spans[0].content.clone())
+            .intersperse("\n".to_string().into())
+            .collect()
         };
         assert_eq!(call(80, 80), "lol");
     }
@@ -365,11 +348,21 @@ mod tests {
     fn test_visible1() {
         let call = |rows, cols| -> String {
             // let s: Vec<_> = self.view.iter().map(|dl| dl.line.clone()).collect();
-            get_visible_lines(LINES.into(), &vec![], rows, cols, 0)
-                .iter()
-                .map(|l| l.line.spans[0].content.clone().to_owned())
-                .intersperse("\n".to_string().into())
-                .collect()
+            get_visible_lines(
+                LINES.into(),
+                &Records::default(),
+                &Folds::default(),
+                &[],
+                &mut RecordParser::default(),
+                None,
+                rows,
+                cols,
+                0,
+            )
+            .iter()
+            .map(|l| l.line.spans[0].content.clone())
+            .intersperse("\n".to_string().into())
+            .collect()
         };
         assert_eq!(call(80, 80), LINES);
     }
@@ -382,11 +375,21 @@ mod tests {
         // assert_eq!(res, comp);
         // assert_eq!(get_visible_lines(s, &vec!(), 1, 1), comp);
         let call = |rows, cols| -> String {
-            get_visible_lines(s, &vec![], rows, cols, 0)
-                .iter()
-                .map(|l| l.line.spans[0].content.clone().to_owned())
-                .intersperse("\n".to_string().into())
-                .collect()
+            get_visible_lines(
+                s,
+                &Records::default(),
+                &Folds::default(),
+                &[],
+                &mut RecordParser::default(),
+                None,
+                rows,
+                cols,
+                0,
+            )
+            .iter()
+            .map(|l| l.line.spans[0].content.clone())
+            .intersperse("\n".to_string().into())
+            .collect()
         };
         assert_eq!(call(80, 80), s);
         assert_eq!(call(1, 1), "");
@@ -396,39 +399,105 @@ mod tests {
         assert_eq!(call(3, 2), "\n\nhi");
     }
 
+    #[test]
+    fn test_visible_records() {
+        let s = "boom\n  at a\n  at b\nfine\nboom again\n  at c\n";
+        let call = |filter: &str, start| -> Vec<String> {
+            get_visible_lines(
+                s.into(),
+                &Records::default(),
+                &Folds::default(),
+                &[LineFilter::new(filter.to_string(), FilterType::Out)],
+                &mut RecordParser::default(),
+                None,
+                80,
+                80,
+                start,
+            )
+            .iter()
+            .map(|l| l.line.spans[0].content.to_string())
+            .collect()
+        };
+        // A filter matching any line of a record takes out all of it, even starting halfway through.
+        assert_eq!(call("at b", 0), ["fine", "boom again", "  at c", ""]);
+        assert_eq!(call("at b", 12), ["fine", "boom again", "  at c", ""]);
+        assert_eq!(call("fine", 12), ["  at b", "boom again", "  at c", ""]);
+    }
+
+    #[test]
+    fn test_visible_folded() {
+        let s = "boom\n  at a\n  at b\n  at c\nfine\n";
+        let records = Records::default();
+        let call = |folds: &Folds, start| -> Vec<String> {
+            let mut lines = get_visible_lines(
+                s.into(),
+                &records,
+                folds,
+                &[],
+                &mut RecordParser::default(),
+                None,
+                80,
+                80,
+                start,
+            );
+            lines.iter_mut().for_each(mark_folded);
+            lines.iter().map(|l| l.line.to_string()).collect()
+        };
+        let mut folds = Folds::default();
+        assert_eq!(call(&folds, 0), ["boom [+3 lines]", "fine", ""]);
+        // Wherever in a folded record the view starts, it's shown from its first line.
+        assert_eq!(call(&folds, 12), ["boom [+3 lines]", "fine", ""]);
+        assert!(folds.toggle(s.as_bytes(), &records.record_at(s.as_bytes(), 12)));
+        assert_eq!(call(&folds, 12), ["  at b", "  at c", "fine", ""]);
+        assert!(!folds.toggle(s.as_bytes(), &records.record_at(s.as_bytes(), 26)));
+        folds.toggle_all();
+        assert_eq!(call(&folds, 0).len(), 6);
+        folds.toggle_all();
+        assert_eq!(call(&folds, 0).len(), 3);
+    }
+
     #[test]
     fn test_allowed() {
-        assert!(line_allowed(&vec!(), "Lol").0);
+        let drain = &mut RecordParser::default();
+        assert!(line_allowed(&[], drain, None, "Lol").0);
         assert!(
             !line_allowed(
-                &vec!(LineFilter::new("Lol".to_string(), FilterType::Out)),
+                &[LineFilter::new("Lol".to_string(), FilterType::Out)],
+                drain,
+                None,
                 "Lol"
             )
             .0
         );
         assert!(
             line_allowed(
-                &vec!(LineFilter::new("Lol".to_string(), FilterType::In)),
+                &[LineFilter::new("Lol".to_string(), FilterType::In)],
+                drain,
+                None,
                 "Lol"
             )
             .0
         );
         assert!(
             !line_allowed(
-                &vec!(
+                &[
                     LineFilter::new("Lol".to_string(), FilterType::In),
                     LineFilter::new("Lol".to_string(), FilterType::Out),
-                ),
+                ],
+                drain,
+                None,
                 "Lol"
             )
             .0
         );
         assert!(
             line_allowed(
-                &vec!(
+                &[
                     LineFilter::new("Lol".to_string(), FilterType::Out),
                     LineFilter::new("Lol".to_string(), FilterType::In),
-                ),
+                ],
+                drain,
+                None,
                 "Lol"
             )
             .0
@@ -436,93 +505,85 @@ mod tests {
     }
 
     #[test]
-    fn test_first() {
-        assert_eq!(
-            bin_search(
-                LINES.as_bytes(),
-                &DateTime::<FixedOffset>::parse_from_rfc3339("2022-03-22T08:51:06Z")
-                    .unwrap()
-                    .with_timezone(&Utc)
+    fn test_allowed_template() {
+        let drain = &mut RecordParser::default();
+        let id = drain
+            .template_id("03/22/2022 08:51:06 INFO   :...connection 5 dropped")
+            .unwrap();
+        let out = vec![LineFilter::new_template(
+            id,
+            drain.templates[id].clone(),
+            FilterType::Out,
+        )];
+        assert!(
+            !line_allowed(
+                &out,
+                drain,
+                None,
+                "03/22/2022 08:51:09 INFO   :...connection 12 dropped"
             )
-            .unwrap(),
-            0
+            .0
         );
-    }
-
-    #[test]
-    fn test_dt() {
-        // let dt = crate::dateparser::parse_with_timezone("09:42:21.0090980809 WORD", &Local);
-        // TODO switch to qsv-dateparser to get support for fractions on the timestamps?
-        // Or just hardcode a filter out of them, because we don't need the tenths?
-        assert!(crate::dateparser::parse_with_timezone("09:42:21", &Local, None).is_ok());
-        assert!(crate::dateparser::parse_with_timezone("09:42:21.401", &Local, None).is_ok());
-        assert!(crate::dateparser::parse_with_timezone("09:42:21.99923", &Local, None).is_ok());
-    }
-
-    #[test]
-    fn test_second() {
-        assert_eq!(
-            bin_search(
-                LINES.as_bytes(),
-                &chrono::NaiveDateTime::parse_from_str("2022-03-22T08:51:08", "%Y-%m-%dT%H:%M:%S")
-                    .unwrap()
-                    .and_local_timezone(Local)
-                    .unwrap()
-                    .with_timezone(&Utc)
+        assert!(
+            line_allowed(
+                &out,
+                drain,
+                None,
+                "03/22/2022 08:51:09 WARN   :...disk is full"
             )
-            .unwrap(),
-            41
+            .0
         );
-    }
 
-    #[test]
-    fn test_between() {
-        assert_eq!(
-            bin_search(
-                LINES.as_bytes(),
-                &chrono::NaiveDateTime::parse_from_str("2022-03-22T08:51:07", "%Y-%m-%dT%H:%M:%S")
-                    .unwrap()
-                    .and_local_timezone(Local)
-                    .unwrap()
-                    .with_timezone(&Utc)
+        let only = vec![LineFilter::new_template(
+            id,
+            drain.templates[id].clone(),
+            FilterType::In,
+        )];
+        assert!(
+            line_allowed(
+                &only,
+                drain,
+                None,
+                "03/22/2022 08:52:00 INFO   :...connection 7 dropped"
             )
-            .unwrap(),
-            41
+            .0
         );
-    }
-
-    #[test]
-    fn test_after() {
-        // env_logger::init();
-        assert_eq!(
-            bin_search(
-                LINES.as_bytes(),
-                &chrono::NaiveDateTime::parse_from_str("2022-03-22T08:51:09", "%Y-%m-%dT%H:%M:%S")
-                    // &DateTime::<FixedOffset>::parse_from_rfc3339("2022-03-22T08:51:09Z")
-                    .unwrap()
-                    .and_local_timezone(Local)
-                    .unwrap()
-                    .with_timezone(&Utc)
+        assert!(
+            !line_allowed(
+                &only,
+                drain,
+                None,
+                "03/22/2022 08:52:00 WARN   :...disk is full"
             )
-            .unwrap(),
-            80
+            .0
         );
+        assert!(!line_allowed(&only, drain, None, "").0);
     }
 
     #[test]
-    fn test_before() {
-        assert_eq!(
-            bin_search(
-                LINES.as_bytes(),
-                &chrono::NaiveDateTime::parse_from_str("2022-03-22T08:51:00", "%Y-%m-%dT%H:%M:%S")
-                    .unwrap()
-                    .and_local_timezone(Local)
-                    .unwrap()
-                    .with_timezone(&Utc)
-            )
-            .unwrap(),
-            0
-        );
+    fn test_allowed_field() {
+        let drain = &mut RecordParser::default();
+        let json = Some(&FieldSource::Format(FieldFormat::Json));
+        let slow = [LineFilter::from_input(
+            ".latency_ms>500".to_string(),
+            FilterType::In,
+        )];
+        let record = |latency| format!(r#"{{"level":"info","latency_ms":{latency}}}"#);
+        assert!(line_allowed(&slow, drain, json, &record(900)).0);
+        assert!(!line_allowed(&slow, drain, json, &record(12)).0);
+        assert!(!line_allowed(&slow, drain, json, r#"{"level":"info"}"#).0);
+        // Lines without fields are matched on the text of the filter, like any other.
+        assert!(line_allowed(&slow, drain, json, "retrying latency_ms>500").0);
+        assert!(!line_allowed(&slow, drain, None, &record(900)).0);
+
+        // Without the `.` it's text to look for, even in records with fields.
+        let text = [LineFilter::from_input(
+            "status=500".to_string(),
+            FilterType::In,
+        )];
+        assert_eq!(text[0].kind, FilterKind::Substring);
+        assert!(line_allowed(&text, drain, json, r#"{"msg":"got status=500"}"#).0);
+        assert!(!line_allowed(&text, drain, json, r#"{"status":500}"#).0);
     }
 
     #[test]
@@ -534,51 +595,44 @@ mod tests {
     }
 }
 
-type FileOffset = usize;
-
-#[derive(Debug)]
-enum TsBinSearchError {
-    FailedParseTs,
-}
-
-// fn bin_search_file(s: &[u8], dt: &DateTime<Utc>) {
-fn bin_search(s: &[u8], dt: &DateTime<Utc>) -> Result<FileOffset, TsBinSearchError> {
-    let mut low: usize = 0;
-    let mut high: usize = s.len() - 1;
-    let mut middle = 0;
-
-    while low <= high {
-        middle = (high + low) / 2;
-        info!("Bin search: low: {}, mid: {}, high: {}", low, middle, high);
-        match find_date_before(s, middle, dt.date_naive()) {
-            Some((line_start, ts)) => {
-                info!("Comparing tses {} and {}", ts, dt);
-                match ts.cmp(dt) {
-                    Ordering::Less => {
-                        low = middle + 1;
-                    }
-                    Ordering::Equal => return Ok(line_start), // lucky guess!
-                    Ordering::Greater => {
-                        if middle == 0 {
-                            return Ok(0);
-                        }
-                        high = middle - 1;
-                    }
-                }
-            }
-            None => {
-                return Err(TsBinSearchError::FailedParseTs);
-            }
-        }
-    }
-    Ok(middle)
-}
+/// Past this, the params table shows only the first occurrences of a template.
+const MAX_PARAM_ROWS: usize = 10_000;
+/// Scans that run drain on the UI thread, like collecting params or looking for a rare record,
+/// give up after reading this much of the file.
+const MAX_SCAN_BYTES: usize = 64 << 20;
 
 #[derive(PartialEq, Eq, Clone)]
 pub struct DispLine {
     file_loc: FileLoc, // <-- [begin, end)
     // line: String,
     line: ratatui::text::Line<'static>,
+    /// Further rows the record is shown over, e.g. when its fields are pretty-printed.
+    more: Vec<Line<'static>>,
+    /// Set in the column view, which draws these rather than the line.
+    fields: Option<Fields>,
+    /// The whole record this line is part of.
+    record: FileLoc,
+    /// Lines of the record left out after this one because it's folded.
+    hidden_lines: usize,
+}
+
+impl DispLine {
+    fn new(source: &[u8], line: Range<usize>, record: FileLoc, hidden_lines: usize) -> Self {
+        Self {
+            file_loc: FileLoc(line.start, line.end),
+            // Always one span, even for an empty line, since the timestamp display works on spans[0].
+            line: Span::raw(source[line].to_str_lossy().into_owned()).into(),
+            more: Vec::new(),
+            fields: None,
+            record,
+            hidden_lines,
+        }
+    }
+
+    /// How many rows of the screen this takes, not counting wrapping.
+    fn rows(&self) -> usize {
+        1 + self.more.len()
+    }
 }
 
 // impl DispLine {
@@ -604,12 +658,25 @@ pub struct Home {
     mmap: Arc<Mmap>,
     byte_cursor: usize,
     today: Option<NaiveDate>,
+    timestamps: TimestampParser,
+    records: Records,
+    folds: Folds,
+    time_display: TimeDisplay,
+    field_view: FieldView,
+    field_source: Option<FieldSource>,
+    /// Finds the level of a record as written, so it can be colored.
+    level_locator: Option<Locator>,
+    field_table: FieldTable,
 
     go_screen: GoScreen,
 
     show_filter_screen: bool,
     filter_screen: FilterScreen<'static>,
 
+    template_screen: TemplateScreen,
+    diff_screen: DiffScreen,
+    params_screen: ParamsScreen,
+
     show_search: bool,
     search_screen: TextEntry<'static>,
     last_search: String,
@@ -625,10 +692,26 @@ pub struct Home {
     // screen_size: Rect,
     drain_parsed: HashMap<FileLoc, i32>,
     drain_parser: RecordParser,
+
+    config: Config,
+    /// Counts of every template in the file, once the background thread started for `r` or `t` is done.
+    template_index: Option<TemplateIndex>,
+    template_index_rx: Option<Receiver<(TemplateIndex, RecordParser)>>,
+    /// Last line with a timestamp, until the midnights in the log have been looked for.
+    day_index_from: Option<FileOffset>,
+    /// Midnights in a log of time-only timestamps, once the background thread started for going to a time is done.
+    day_index_rx: Option<Receiver<DayIndex>>,
+    /// A move to a time waiting on the midnights.
+    pending_move: Option<CursorMove>,
+    /// Shown at the bottom of the screen until the next key press, for things that went wrong.
+    notice: Option<String>,
 }
 
 impl Home {
-    pub fn new(filename: String, mmap: Arc<Mmap>) -> Self {
+    pub fn new(filename: String, mmap: Arc<Mmap>, mut config: Config) -> Self {
+        config.detect_fields(&mmap);
+        let field_table =
+            FieldTable::new(&config.columns, &config.hide_keys, config.ts_key.as_deref());
         Self {
             is_running: false,
             show_logger: false,
@@ -638,8 +721,22 @@ impl Home {
             mmap,
             byte_cursor: 0,
             today: None,
+            timestamps: config.timestamp_parser(),
+            records: config.records(),
+            folds: Folds::default(),
+            time_display: TimeDisplay::default(),
+            field_view: FieldView::default(),
+            field_source: config.field_source(),
+            level_locator: config
+                .field_source()
+                .zip(config.level_key.as_deref())
+                .and_then(|(source, key)| source.key_locator(key)),
+            field_table,
             show_filter_screen: false,
             filter_screen: FilterScreen::default(),
+            template_screen: TemplateScreen::default(),
+            diff_screen: DiffScreen::default(),
+            params_screen: ParamsScreen::default(),
             go_screen: GoScreen::default(),
             show_search: false,
             search_screen: TextEntry::default(),
@@ -652,7 +749,14 @@ impl Home {
                 height: 1000,
             }),
             drain_parsed: HashMap::new(),
-            drain_parser: RecordParser::default(),
+            drain_parser: config.record_parser(),
+            config,
+            template_index: None,
+            template_index_rx: None,
+            day_index_from: None,
+            day_index_rx: None,
+            pending_move: None,
+            notice: None,
         }
     }
 
@@ -660,70 +764,58 @@ impl Home {
         // debug!("Tick");
     }
 
-    // This is a copy paste of next_line but in the opposite direction;
-    // it did not seem necessarily simpler to use one impl that constantly switched on direction.
+    /// Scrolls up to the line above the top one: the line before it in the same record, or else the last
+    /// line of the nearest record above that the filters let through.
     pub fn prev_line(&mut self) {
-        let first_line = self.screen.view.first();
-        let first_line = match first_line {
+        let first_line = match self.screen.view.first() {
             Some(first_line) => first_line,
             None => {
                 info!("Tried to go past beginning of empty file.");
                 return;
             }
         };
-        // For some reason when scrolling up past the beginning of a file, we end up in a scenario where
-        // byte cursor is 1 while file loc is 0. I'm going to disable that ability, but making this assert
-        // a little more friendly just in case.
-        if first_line.file_loc.0 == 0 {
-            // Trying to scroll before beginning of file. I think scrolling before the beginning of lines that are filtered out
-            // but not displaying will still scroll depsite this.
+        let FileLoc(top, _) = first_line.file_loc;
+        // The top line's record has already been through the filters, so the rest of it can be shown as is.
+        let prev_record = match top > first_line.record.0 {
+            true => Some(first_line.record.0..top - 1),
+            false => self
+                .records
+                .backward(&self.mmap, top)
+                .find(|record| {
+                    line_allowed(
+                        &self.filter_screen.items,
+                        &mut self.drain_parser,
+                        self.field_source.as_ref(),
+                        &self.mmap[record.clone()].to_str_lossy(),
+                    )
+                    .0
+                })
+                .map(|record| record.start..record.end),
+        };
+        let Some(prev_record) = prev_record else {
             return;
-        }
-        // assert!(self.byte_cursor == first_line.file_loc.0 || self.byte_cursor - 1 == first_line.file_loc.0);
-
-        // A naive copy paste of next_line to here results in prev_line moving back one file-line and not one visible line.
-        // We got lucky with next_line.
-        // We have a conundrum here, which is that seeking backwards doesn't have a parallel/inverse with forwards in our lowest function calls
-        // For example, get_visible_lines seeks forward rather than backwards, but expects to be started on the start of a line.
-        // some of this is just reality; for example our filters must be applied to a 'line' (or a 'record'!), not just an arbitrary byte stream.
-        //
-        // There are two obvious approaches in front of us.
-        //   1. Scroll back file-line by file-line until a line is visible.
-        //   2. Write a get_visible_lines_backwards.
-        // get_visible_lines is kind of a big gnarly function that I don't want to go near right now,
-        // while (1) seems straightforward.
-        // One problem lurking is that approach (1) done wrong will approach n^2 runtime with high-percentage filtering.
-        // We can avoid this by only asking it to review the freshly under-consideration file-line.
-        // We thus treat get_visible_lines as a sort of single-line filter. Perhaps it should be refactored to lift a single-line filter out of it.
-        // Also note by taking approach 1, we are totally ignoring our prior concept of 'records', which results in the following bug:
-        // When scrolling forward, the lines 'filtered-out-string\n  continued\n  continued' would all be filtered, but when scrolling backwards, not so.
-        // The fix for this is to implement something like a RecordIterator, and break get_visible_lines into more distinguishable pieces.
-        // For now, this gets the job mostly done, at least it makes scrolling up responsive in the usual simple cases.
-        let mut end_search = first_line.file_loc.0;
-        loop {
-            if self.byte_cursor == 0 {
-                return;
-            }
-            self.byte_cursor =
-                find_line_starting_before(&self.mmap, self.byte_cursor.saturating_sub(1));
-            let prev_line_starts_at = self.byte_cursor;
-            // line_allowed(&self.filter_screen.items, line) 0-thought went into not using this
-            let binding = get_visible_lines(
-                self.mmap[prev_line_starts_at..end_search].as_bstr(),
-                &self.filter_screen.items,
-                1,
-                600,
-                prev_line_starts_at,
-            );
-            assert!(binding.len() == 1);
-            let prev_line = binding.into_iter().next();
-            if let Some(mut prev_line) = prev_line {
-                // Found a previous line that is actually visible.
-                highlight_line(&mut prev_line, &self.last_search);
-                self.screen.prepend_line(prev_line);
-                return;
-            }
-            end_search = prev_line_starts_at;
+        };
+        // A folded record shows as its first line, otherwise it's the last line that comes into view.
+        self.byte_cursor = match self.folds.hidden_lines(&self.mmap, &prev_record) {
+            0 => find_line_starting_before(&self.mmap, prev_record.end),
+            _ => prev_record.start,
+        };
+        let prev_line = get_visible_lines(
+            self.mmap.as_bstr(),
+            &self.records,
+            &self.folds,
+            &self.filter_screen.items,
+            &mut self.drain_parser,
+            self.field_source.as_ref(),
+            1,
+            600,
+            self.byte_cursor,
+        )
+        .into_iter()
+        .next();
+        if let Some(mut prev_line) = prev_line {
+            self.decorate_line(&mut prev_line);
+            self.screen.prepend_line(prev_line);
         }
     }
 
@@ -739,34 +831,48 @@ impl Home {
                 return false;
             }
         };
-        self.byte_cursor = self.screen.view[0].file_loc.1 + 1;
+        let top = &self.screen.view[0];
+        self.byte_cursor = match top.hidden_lines {
+            0 => top.file_loc.1 + 1,
+            _ => top.record.1 + 1,
+        };
         if self.byte_cursor >= self.mmap.len() {
             self.byte_cursor = self.mmap.len() - 1;
             info!("Tried to go past end of file!");
             return false;
         }
 
-        // TODO document some invariants on these values. Do they point at the newline? One before? etc.
-        // Intent is for [start, end), i.e. end index points one past the last valid index.
-        // Ropey makes some guarantees on this that we can probably use.
-        let next_line_starts_at = last_line.file_loc.1; // + 1;
+        // Lines end at their newline, or earlier if they didn't fit on screen.
+        // The rest of a folded record is skipped over.
+        let last_end = match last_line.hidden_lines {
+            0 => last_line.file_loc.1,
+            _ => last_line.record.1,
+        };
+        let next_line_starts_at = match self.mmap[last_end..].find_byte(b'\n') {
+            Some(idx) => last_end + idx + 1,
+            None => return self.screen.drop_first_line(),
+        };
         let next_lines = get_visible_lines(
-            self.mmap[next_line_starts_at..].as_bstr(),
+            self.mmap.as_bstr(),
+            &self.records,
+            &self.folds,
             &self.filter_screen.items,
+            &mut self.drain_parser,
+            self.field_source.as_ref(),
             1,
             600,
             next_line_starts_at,
         );
         let next_lines_len = next_lines.len();
         let first = next_lines.into_iter().next();
-        if first == None {
-            return false;
-        }
+        let mut first = match first {
+            Some(first) => first,
+            None => return self.screen.drop_first_line(),
+        };
         assert!(next_lines_len == 1);
-        let mut first = first.unwrap();
-        highlight_line(&mut first, &self.last_search);
+        self.decorate_line(&mut first);
         self.screen.push_line(first);
-        return true;
+        true
         // info!("Set cursor to {}", self.byte_cursor);
     }
 
@@ -780,16 +886,42 @@ impl Home {
                 chrono::LocalResult::Ambiguous(_, _) => {}
             }
         }*/
-        let spot = bin_search(self.mmap.as_bstr(), &dt);
+        let spot = self
+            .timestamps
+            .bin_search(self.mmap.as_bstr(), &dt, self.first_day());
         match spot {
+            Ok(cursor) if cursor >= self.mmap.len() => self.goto_end(),
             Ok(cursor) => self.byte_cursor = cursor,
+            Err(TsBinSearchError::NotTimeOrdered(offset)) => {
+                // Better to show where it went wrong than to land on a guess.
+                self.byte_cursor = offset;
+                self.notice = Some(format!(
+                    "Can't go to {}: time goes backwards by line {}, the file isn't in time order.",
+                    dt.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+                    self.mmap[..offset].find_iter("\n").count() + 1
+                ));
+            }
             Err(tbe) => {
                 error!("{:?}", tbe);
-                // spot.unwrap();
+                self.notice =
+                    Some("Can't go to a time: no timestamps found in the file.".to_string());
             }
         }
     }
 
+    /// Goes to `delta` after the timestamp of the record at the top of the screen.
+    pub fn goto_delta(&mut self, delta: TimeDelta) {
+        let default_date = self.first_day();
+        match self
+            .timestamps
+            .find_before(&self.mmap, self.byte_cursor, default_date)
+            .and_then(|(_, ts)| ts.checked_add_signed(delta))
+        {
+            Some(dt) => self.goto_dt(dt),
+            None => error!("No timestamp at the cursor to go {} from", delta),
+        }
+    }
+
     pub fn goto_pct(&mut self, pct: f64) {
         self.byte_cursor = find_start_line_pct(&self.mmap, pct);
     }
@@ -803,51 +935,45 @@ impl Home {
         self.byte_cursor = find_line_starting_before(&self.mmap, self.mmap.len());
     }
 
+    /// Moves the cursor to the next line containing `needle` whose record the filters let through.
     pub fn put_cursor_on_line_search(&mut self, needle: &str) {
+        self.last_search = needle.to_owned();
         let mut cursor = self.byte_cursor;
-        let mut last_cursor = usize::MAX;
         info!("Search starting at {:?}", cursor);
-        loop {
-            let haystack = &self.mmap[self.byte_cursor..];
-            let result = haystack.find(needle);
-            self.last_search = needle.to_owned();
-            if cursor == last_cursor {
-                return;
+        while let Some(idx) = self.mmap.get(cursor..).and_then(|rest| rest.find(needle)) {
+            let line_start = find_line_starting_before(&self.mmap, cursor + idx);
+            let record = self.records.record_at(&self.mmap, line_start);
+            if !line_allowed(
+                &self.filter_screen.items,
+                &mut self.drain_parser,
+                self.field_source.as_ref(),
+                &self.mmap[record.clone()].to_str_lossy(),
+            )
+            .0
+            {
+                info!(
+                    "Skipping record starting at {} due to filter.",
+                    record.start
+                );
+                cursor = record.end + 1;
+                continue;
             }
-            last_cursor = cursor;
-            match result {
-                Some(idx) => {
-                    let maybe_cursor = self.byte_cursor + find_line_starting_before(haystack, idx);
-                    if !line_allowed(
-                        &self.filter_screen.items,
-                        // TODO Explicitly search to the next newline only
-                        &self.mmap[maybe_cursor..self.mmap.len().max(maybe_cursor + 4096)]
-                            .to_str_lossy(),
-                    )
-                    .0
-                    {
-                        info!("Skipping line starting at {} due to filter.", maybe_cursor);
-                        cursor = maybe_cursor;
-                        continue;
-                    }
-                    self.byte_cursor = maybe_cursor;
-                    self.search_visits.push(self.byte_cursor);
-                    info!(
-                        "Found result at idx {}, found line starting at {}",
-                        idx, maybe_cursor
-                    );
-                    return;
-                    // https://github.com/rhysd/tui-textarea/blob/main/src/highlight.rs#L101
-                    // Great reference for using Spans to highlight lines.
-                    // self.search_screen.textarea.input().th
-                    // self.search_screen.textarea.set_search_pattern(needle);
-                }
-                None => {
-                    info!("Nothing found with term: {:?}", needle);
-                    return;
-                }
+            // Don't leave what was found folded out of sight.
+            if self.folds.hidden_lines(&self.mmap, &record) > 0 && line_start > record.start {
+                self.folds.toggle(&self.mmap, &record);
             }
+            self.byte_cursor = line_start;
+            self.search_visits.push(self.byte_cursor);
+            info!(
+                "Found result at idx {}, found line starting at {}",
+                cursor + idx,
+                line_start
+            );
+            // https://github.com/rhysd/tui-textarea/blob/main/src/highlight.rs#L101
+            // Great reference for using Spans to highlight lines.
+            return;
         }
+        info!("Nothing found with term: {:?}", needle);
     }
 
     pub fn new_search(&mut self) {
@@ -894,13 +1020,162 @@ impl Home {
 
     fn update_view(&mut self) {
         self.screen.view = get_visible_lines(
-            self.mmap[self.byte_cursor..].as_bstr(),
+            self.mmap.as_bstr(),
+            &self.records,
+            &self.folds,
             &self.filter_screen.items,
+            &mut self.drain_parser,
+            self.field_source.as_ref(),
             200,
             600,
             self.byte_cursor,
         );
-        highlight_lines(&mut self.screen.view, &self.last_search);
+        let mut view = std::mem::take(&mut self.screen.view);
+        for line in &mut view {
+            self.decorate_line(line);
+        }
+        self.screen.view = view;
+    }
+
+    /// Shows the fields and timestamp of `line` the way they've been asked for, then highlights the last
+    /// search in it. Continuation lines are left alone, only the timestamp a record starts with is rewritten.
+    fn decorate_line(&self, line: &mut DispLine) {
+        let default_date = self.first_day();
+        let FileLoc(start, end) = line.file_loc;
+        if self.field_view != FieldView::AsWritten && self.show_fields(line) {
+            highlight_line(line, &self.last_search);
+            mark_folded(line);
+            return;
+        }
+        let is_record_start = start == line.record.0;
+        let mut level = self
+            .level_locator
+            .as_ref()
+            .filter(|_| is_record_start)
+            .and_then(|locator| Some(locator.find(&line.line.spans[0].content)?.range()));
+        if self.time_display != TimeDisplay::AsWritten && is_record_start {
+            let shown = self
+                .timestamps
+                .locate_at(&self.mmap, start, default_date)
+                .and_then(|(range, ts)| Some((range, self.time_display.format(ts)?)));
+            let content = &mut line.line.spans[0].content;
+            if let Some((range, shown)) =
+                shown.filter(|(range, _)| content.get(range.clone()).is_some())
+            {
+                // Keep the level where it is relative to the text around it.
+                level = level.and_then(|level| match level.start >= range.end {
+                    true => {
+                        let shift = |at: usize| at + shown.len() - range.len();
+                        Some(shift(level.start)..shift(level.end))
+                    }
+                    false => (level.end <= range.start).then_some(level),
+                });
+                content.to_mut().replace_range(range, &shown);
+            }
+        }
+        if let Some((level, key)) = level.zip(self.config.level_key.as_deref()) {
+            color_range(&mut line.line, level, key);
+        }
+        highlight_line(line, &self.last_search);
+        mark_folded(line);
+    }
+
+    /// Replaces `line` with its fields the way [`Self::field_view`] asks for, false if it has none.
+    fn show_fields(&self, line: &mut DispLine) -> bool {
+        let FileLoc(start, end) = line.file_loc;
+        let Some(mut fields) = self
+            .field_source
+            .as_ref()
+            .and_then(|source| source.parse(&self.mmap[start..end].to_str_lossy()))
+        else {
+            return false;
+        };
+        if let Some(ts_key) = &self.config.ts_key {
+            let shown = self
+                .timestamps
+                .locate_at(&self.mmap, start, self.first_day())
+                .and_then(|(_, ts)| self.time_display.format(ts));
+            if let Some(shown) = shown {
+                fields.set(ts_key, shown.into());
+            }
+        }
+        match self.field_view {
+            FieldView::AsWritten => return false,
+            FieldView::Collapsed => {
+                fields.retain(&self.config.show_keys, &self.config.hide_keys);
+                line.line = fields.collapsed();
+            }
+            FieldView::Pretty => {
+                fields.retain(&self.config.show_keys, &self.config.hide_keys);
+                let mut rows = fields.pretty().into_iter();
+                line.line = rows.next().unwrap_or_default();
+                line.more = rows.collect();
+            }
+            FieldView::Columns => {
+                line.line = fields.collapsed();
+                line.fields = Some(fields);
+            }
+        }
+        true
+    }
+
+    /// As written -> collapsed -> pretty -> columns -> as written, for records with fields.
+    fn cycle_field_view(&mut self) {
+        if self.field_source.is_none() {
+            self.notice = Some(
+                "No fields: records aren't JSON or logfmt, and there's no --log-format".to_string(),
+            );
+            return;
+        }
+        self.field_view = self.field_view.next();
+        info!("Showing fields {:?}", self.field_view);
+        self.update_view();
+    }
+
+    /// Folds or unfolds the record at the top of the screen.
+    fn toggle_fold(&mut self) {
+        let Some(FileLoc(start, end)) = self.screen.view.first().map(|line| line.record) else {
+            return;
+        };
+        if !self.folds.toggle(&self.mmap, &(start..end)) {
+            self.notice = Some(format!(
+                "Only records of more than {} lines fold",
+                records::FOLD_AFTER_LINES
+            ));
+            return;
+        }
+        // Folding from partway down the record brings its first line back to the top.
+        self.byte_cursor = start;
+        self.update_view();
+    }
+
+    fn toggle_fold_all(&mut self) {
+        self.folds.toggle_all();
+        info!("Folding all long records: {}", self.folds.all_folded());
+        self.update_view();
+    }
+
+    /// As written -> local -> UTC -> relative to the record at the top of the screen -> as written.
+    fn cycle_time_display(&mut self) {
+        // Shown dates are off until the midnights are found; the view is redrawn once they are.
+        self.days_ready();
+        let default_date = self.first_day();
+        self.time_display = match self.time_display {
+            TimeDisplay::AsWritten => TimeDisplay::Local,
+            TimeDisplay::Local => TimeDisplay::Utc,
+            TimeDisplay::Utc => {
+                match self
+                    .timestamps
+                    .find_before(&self.mmap, self.byte_cursor, default_date)
+                {
+                    Some((_, anchor)) => TimeDisplay::Relative(anchor),
+                    None => TimeDisplay::AsWritten,
+                }
+            }
+            TimeDisplay::Relative(_) => TimeDisplay::AsWritten,
+        };
+        info!("Showing timestamps {:?}", self.time_display);
+        self.update_view();
     }
 
     fn parse_filename_for_date(&self, filename: &str) -> Option<NaiveDate> {
@@ -908,24 +1183,361 @@ impl Home {
 
         for capture in re.captures_iter(filename) {
             let s = capture.get(0).unwrap().as_str();
-            match NaiveDate::parse_from_str(s, "%Y%m%d") {
-                Ok(nd) => return Some(nd),
-                Err(_) => {}
-            };
+            if let Ok(nd) = NaiveDate::parse_from_str(s, "%Y%m%d") {
+                return Some(nd);
+            }
         }
         None
     }
 
+    /// Scrolls by a screen's worth of rows rather than lines, since in the pretty field view
+    /// a record takes several rows, and paging by lines would skip records that were never shown.
     fn move_screenful(&mut self, dir: Direction) {
-        let h = self.screen.screen_size.height;
-        for _ in 0..h + 1 {
+        let h = usize::from(self.screen.screen_size.height);
+        let mut scrolled = 0;
+        while scrolled < h {
             match dir {
-                Direction::Prev => self.prev_line(),
+                Direction::Prev => {
+                    let Some(top) = self.screen.view.first().map(|line| line.file_loc) else {
+                        return;
+                    };
+                    self.prev_line();
+                    let rows = match self.screen.view.first() {
+                        Some(first) if first.file_loc != top => first.rows(),
+                        _ => return,
+                    };
+                    // Same going back: don't push the old top off the screen, unless nothing else fit.
+                    if scrolled > 0 && scrolled + rows > h {
+                        self.screen.view.remove(0);
+                        self.byte_cursor = top.0;
+                        return;
+                    }
+                    scrolled += rows;
+                }
                 Direction::Next => {
-                    self.next_line();
+                    // Stop short of scrolling past a record that doesn't fit on screen yet.
+                    let rows = self.screen.view.first().map_or(1, DispLine::rows);
+                    if scrolled > 0 && scrolled + rows > h {
+                        return;
+                    }
+                    if !self.next_line() {
+                        return;
+                    }
+                    scrolled += rows;
+                }
+            }
+        }
+    }
+
+    /// Drain template of the record at `loc`, cached since drain parsing isn't free.
+    fn template_id_of(&mut self, loc: FileLoc) -> Option<i32> {
+        if let Some(tid) = self.drain_parsed.get(&loc) {
+            return Some(*tid);
+        }
+        let record = &self.mmap[loc.0..loc.1].to_str_lossy();
+        let template_id = self.drain_parser.template_id(record)?;
+        let small_tid = i32::try_from(template_id).unwrap();
+        self.drain_parsed.insert(loc, small_tid);
+        Some(small_tid)
+    }
+
+    fn open_template_screen(&mut self) {
+        // The list fills in with the whole file's templates once they're counted.
+        self.template_index_ready();
+        // Make sure at least what's on screen has been through drain, so the list isn't empty.
+        let locs: Vec<_> = self.screen.view.iter().map(|l| l.record).collect();
+        for loc in locs {
+            self.template_id_of(loc);
+        }
+        self.template_screen
+            .set_items(self.drain_parser.templates.clone());
+        self.template_screen.show = true;
+    }
+
+    fn add_template_filter(&mut self, template_id: usize, filter_type: FilterType) {
+        let template = match self.drain_parser.templates.get(template_id) {
+            Some(template) => template.clone(),
+            None => {
+                error!("No such template: {}", template_id);
+                return;
+            }
+        };
+        self.filter_screen
+            .add(LineFilter::new_template(template_id, template, filter_type));
+    }
+
+    fn add_template_filter_at_cursor(&mut self, filter_type: FilterType) {
+        let loc = match self.screen.view.first() {
+            Some(line) => line.record,
+            None => return,
+        };
+        match self.template_id_of(loc) {
+            Some(tid) => self.add_template_filter(tid as usize, filter_type),
+            None => info!("Drain couldn't find a template for the line at {:?}", loc),
+        }
+    }
+
+    /// Collects every occurrence of the template into a table. This reads the file up to the last one.
+    fn show_params(&mut self, template_id: usize) {
+        let template = match self.drain_parser.templates.get(template_id) {
+            Some(template) => template.clone(),
+            None => {
+                error!("No such template: {}", template_id);
+                return;
+            }
+        };
+        // No need to look before the first occurrence if we already know where that is.
+        let (start, count) = self
+            .template_index
+            .as_ref()
+            .and_then(|index| index.stats.get(template_id))
+            .filter(|stats| stats.count > 0)
+            .map_or((0, usize::MAX), |stats| (stats.first_seen, stats.count));
+        let default_date = self.first_day();
+        let mut rows = Vec::new();
+        let mut truncated = false;
+        for record in self.records.forward(&self.mmap, start) {
+            if record.start - start > MAX_SCAN_BYTES {
+                truncated = true;
+                break;
+            }
+            let text = self.mmap[record.clone()].to_str_lossy();
+            if let RecordParsedResult::NewTemplate(rp) | RecordParsedResult::RecordParsed(rp) =
+                self.drain_parser.parse_record(&text)
+            {
+                if rp.template_id == template_id {
+                    if rows.len() == MAX_PARAM_ROWS {
+                        truncated = true;
+                        break;
+                    }
+                    rows.push(ParamRow {
+                        file_offset: record.start,
+                        timestamp: self
+                            .timestamps
+                            .parse_at(&self.mmap, record.start, default_date),
+                        values: rp.values.iter().map(|v| v.to_string()).collect(),
+                    });
+                    if rows.len() == count {
+                        break;
+                    }
+                }
+            }
+        }
+        self.template_screen.show = false;
+        self.params_screen.set_rows(template, rows, truncated);
+        self.params_screen.show = true;
+    }
+
+    /// Shows the report from `wd diff`, this file being the bad log.
+    pub fn show_template_diff(&mut self, diffs: Vec<TemplateDiff>) {
+        self.diff_screen.set_items(diffs);
+        self.diff_screen.show = true;
+    }
+
+    /// Starts counting templates if that isn't done or underway yet. True once the counts are in.
+    fn template_index_ready(&mut self) -> bool {
+        if self.template_index.is_some() {
+            return true;
+        }
+        if self.template_index_rx.is_none() {
+            self.start_template_index();
+        }
+        false
+    }
+
+    /// Counting templates means reading the whole file, so it's done off to the side, and only once asked for.
+    fn start_template_index(&mut self) {
+        let (tx, rx) = channel();
+        let mmap = Arc::clone(&self.mmap);
+        let mut parser = self.config.record_parser();
+        let records = self.config.records();
+        std::thread::spawn(move || {
+            let index =
+                TemplateIndex::build_parallel(&mmap, &mut parser, &records, default_threads());
+            // If nobody's listening anymore, we're shutting down anyway.
+            let _ = tx.send((index, parser));
+        });
+        self.template_index_rx = Some(rx);
+    }
+
+    /// Starts looking for midnights if that hasn't been done yet. True once the dates of timestamps are right.
+    fn days_ready(&mut self) -> bool {
+        if let Some(last_line) = self.day_index_from.take() {
+            self.start_day_index(last_line);
+        }
+        self.day_index_rx.is_none()
+    }
+
+    /// Only logs whose timestamps lack a date need one; going by the last line, as init already parsed it.
+    /// Reads every line, so it's only done once times are gone to or shown.
+    fn start_day_index(&mut self, last_line: FileOffset) {
+        if !self.timestamps.is_time_only(&self.mmap, last_line) {
+            return;
+        }
+        let (tx, rx) = channel();
+        let mmap = Arc::clone(&self.mmap);
+        let parser = self.config.timestamp_parser();
+        std::thread::spawn(move || {
+            let _ = tx.send(DayIndex::build(&mmap, &parser));
+        });
+        self.day_index_rx = Some(rx);
+    }
+
+    fn poll_day_index(&mut self) {
+        let rx = match &self.day_index_rx {
+            Some(rx) => rx,
+            None => return,
+        };
+        let days = match rx.try_recv() {
+            Ok(days) => days,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                error!("Day indexing thread died, times past midnight may be misplaced.");
+                self.day_index_rx = None;
+                return;
+            }
+        };
+        info!(
+            "Log of times of day goes past midnight {} times.",
+            days.len()
+        );
+        self.timestamps.set_days(days);
+        self.day_index_rx = None;
+        match self.pending_move.take() {
+            Some(cm) => {
+                self.dispatch(Action::CursorMove(cm));
+            }
+            None => self.update_view(),
+        }
+    }
+
+    /// Date of the first record, for timestamps that don't say. `today` is the date of the last one.
+    fn first_day(&self) -> NaiveDate {
+        let today = self.today.unwrap_or(Local::now().date_naive());
+        today
+            .checked_sub_days(Days::new(self.timestamps.days().len() as u64))
+            .unwrap_or(today)
+    }
+
+    fn poll_template_index(&mut self) {
+        let rx = match &self.template_index_rx {
+            Some(rx) => rx,
+            None => return,
+        };
+        let (index, mut parser) = match rx.try_recv() {
+            Ok(done) => done,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                error!("Template counting thread died, rare records won't be marked.");
+                self.template_index_rx = None;
+                return;
+            }
+        };
+        info!(
+            "Counted {} templates across the whole file.",
+            parser.templates.len()
+        );
+        // Take over the parser that has seen the whole file so ids line up with the index.
+        // Template filters made in the meantime are carried over by a record they matched,
+        // or failing that by their template text. If neither works they're turned off,
+        // since the old id could mean any template to the new parser.
+        for filter in self.filter_screen.items.iter_mut() {
+            let template_id = match &mut filter.kind {
+                FilterKind::Template(template_id) => template_id,
+                _ => continue,
+            };
+            let example = self
+                .drain_parsed
+                .iter()
+                .find(|(_, &tid)| usize::try_from(tid) == Ok(*template_id))
+                .map(|(loc, _)| *loc);
+            let new_id = example
+                .and_then(|loc| parser.template_id(&self.mmap[loc.0..loc.1].to_str_lossy()))
+                .or_else(|| parser.templates.iter().position(|t| *t == filter.needle));
+            match new_id {
+                Some(new_id) => {
+                    *template_id = new_id;
+                    filter.needle = parser.templates[new_id].clone();
+                }
+                None => {
+                    info!(
+                        "Lost track of template filter {:?}, turning it off.",
+                        filter.needle
+                    );
+                    filter.enabled = false;
+                }
+            }
+        }
+        self.drain_parsed.clear();
+        self.drain_parser = parser;
+        self.template_index = Some(index);
+        self.template_index_rx = None;
+        if self.template_screen.show {
+            self.template_screen
+                .set_items(self.drain_parser.templates.clone());
+        }
+        self.update_view();
+    }
+
+    fn is_rare(&mut self, loc: FileLoc) -> bool {
+        if self.template_index.is_none() {
+            return false;
+        }
+        let template_id = match self.template_id_of(loc) {
+            Some(tid) => tid as usize,
+            None => return false,
+        };
+        self.template_index.as_ref().is_some_and(|index| {
+            index.is_rare(
+                template_id,
+                self.config.rare_below,
+                self.config.rare_after_pct,
+            )
+        })
+    }
+
+    fn next_rare(&mut self) {
+        if !self.template_index_ready() {
+            self.notice =
+                Some("Counting templates across the file, try again once that's done".into());
+            return;
+        }
+        let Some(index) = &self.template_index else {
+            return;
+        };
+        // Start on the record after the cursor's so that pressing the key again moves on.
+        let record = self.records.record_at(&self.mmap, self.byte_cursor);
+        if record.end == self.mmap.len() {
+            return;
+        }
+        let start = record.end + 1;
+        for record in self.records.forward(&self.mmap, start) {
+            if record.start - start > MAX_SCAN_BYTES {
+                self.notice = Some(format!(
+                    "No rare records within {} MB after the cursor",
+                    MAX_SCAN_BYTES >> 20
+                ));
+                return;
+            }
+            let text = self.mmap[record.clone()].to_str_lossy();
+            if line_allowed(
+                &self.filter_screen.items,
+                &mut self.drain_parser,
+                self.field_source.as_ref(),
+                &text,
+            )
+            .0
+            {
+                let rare = self.drain_parser.template_id(&text).is_some_and(|tid| {
+                    index.is_rare(tid, self.config.rare_below, self.config.rare_after_pct)
+                });
+                if rare {
+                    self.byte_cursor = record.start;
+                    return;
                 }
             }
         }
+        info!("No rare records after the cursor.");
     }
 
     fn autoskip(&mut self) {
@@ -938,70 +1550,34 @@ impl Home {
         // the update step is almost done, then come back and do it in one pass.
         // This will also speed it up for PGUP/DOWN. For now, it's fine as-is.
         let mut templates_on_screen: HashSet<i32> = HashSet::new();
-        for line in &self.screen.view {
-            let entry = self.drain_parsed.entry(line.file_loc);
-            match entry {
-                std::collections::hash_map::Entry::Occupied(entry) => {
-                    templates_on_screen.insert(*entry.get());
-                }
-                std::collections::hash_map::Entry::Vacant(entry) => {
-                    let raw_line = &self.mmap[line.file_loc.0..line.file_loc.1].to_str_lossy();
-                    let template_id = match self.drain_parser.parse_record(raw_line) {
-                        crate::drainrs::RecordParsedResult::NewTemplate(rp) => rp.template_id,
-                        crate::drainrs::RecordParsedResult::RecordParsed(rp) => rp.template_id,
-                        crate::drainrs::RecordParsedResult::ParseError(e) => {
-                            error!("{}", e);
-                            continue;
-                        }
-                    };
-                    let small_tid = i32::try_from(template_id).unwrap();
-                    templates_on_screen.insert(small_tid);
-                    entry.insert(small_tid);
-                }
+        let locs: Vec<_> = self.screen.view.iter().map(|l| l.record).collect();
+        for loc in locs {
+            if let Some(tid) = self.template_id_of(loc) {
+                templates_on_screen.insert(tid);
             }
         }
 
-        for _ in [0..1_000] {
+        for _ in 0..1_000 {
             // Now, skip until we get a line we haven't seen before.
             if !self.next_line() {
                 info!("Reached end of file during autoskip.");
                 return;
             }
-            let line = self.screen.view.last();
-            let line = match line {
-                Some(line) => line,
+            let loc = match self.screen.view.last() {
+                Some(line) => line.record,
                 None => return,
             };
-            let raw_line = &self.mmap[line.file_loc.0..line.file_loc.1].to_str_lossy();
-            let template_id = match self.drain_parser.parse_record(raw_line) {
-                crate::drainrs::RecordParsedResult::NewTemplate(rp) => rp.template_id,
-                crate::drainrs::RecordParsedResult::RecordParsed(rp) => rp.template_id,
-                crate::drainrs::RecordParsedResult::ParseError(e) => {
-                    error!("{}", e);
-                    continue;
-                }
+            let tid = match self.template_id_of(loc) {
+                Some(tid) => tid,
+                None => continue,
             };
-            let small_tid = i32::try_from(template_id).unwrap();
-
-            let entry = self.drain_parsed.entry(line.file_loc);
-            match entry {
-                std::collections::hash_map::Entry::Occupied(entry) => {
-                    debug!(
-                        "Skipping line {:?} with template: {}",
-                        line.file_loc, self.drain_parser.templates[template_id]
-                    );
-                    continue;
-                }
-                std::collections::hash_map::Entry::Vacant(entry) => {
-                    // Found one!
-                    entry.insert(small_tid);
-                    info!(
-                        "Found line with new template: {}",
-                        self.drain_parser.templates[template_id]
-                    );
-                    return;
-                }
+            let template = &self.drain_parser.templates[tid as usize];
+            if templates_on_screen.insert(tid) {
+                // Found one!
+                info!("Found line with new template: {}", template);
+                return;
             }
+            debug!("Skipping line {:?} with template: {}", loc, template);
         }
     }
 }
@@ -1012,7 +1588,7 @@ impl Component for Home {
         self.update_view();
         let byte_offset = find_line_starting_before(self.mmap.as_bstr(), self.mmap.len() - 1);
         let default_date = self.parse_filename_for_date(&self.filename);
-        let maybe_ts = find_date_before(
+        let maybe_ts = self.timestamps.find_before(
             self.mmap.as_bstr(),
             byte_offset,
             default_date.unwrap_or(Local::now().date_naive()),
@@ -1022,6 +1598,9 @@ impl Component for Home {
             None => default_date,
         };
         self.go_screen.set_today(self.today);
+        self.go_screen.set_tz(self.config.tz);
+        self.go_screen.set_end(maybe_ts.map(|(_, ts)| ts));
+        self.day_index_from = maybe_ts.map(|(last_line, _)| last_line);
         self.go_screen.init()
     }
 
@@ -1038,6 +1617,24 @@ impl Component for Home {
                 return caught;
             }
         }
+        if self.template_screen.show {
+            let caught = self.template_screen.on_key_event(key);
+            if caught != Action::Tick {
+                return caught;
+            }
+        }
+        if self.params_screen.show {
+            let caught = self.params_screen.on_key_event(key);
+            if caught != Action::Tick {
+                return caught;
+            }
+        }
+        if self.diff_screen.show {
+            let caught = self.diff_screen.on_key_event(key);
+            if caught != Action::Tick {
+                return caught;
+            }
+        }
         if self.go_screen.show {
             // if let Action::TextEntry(KeyEvent { code: KeyCode::Char('g'), modifiers: KeyModifiers::NONE, kind: KeyEventKind::Press, state: KeyEventState::NONE }) = action {
             // TODO Let 'g' be handled by the go screen so it can close itself.
@@ -1049,6 +1646,12 @@ impl Component for Home {
                 return caught;
             }
         }
+        if self.field_view == FieldView::Columns {
+            let caught = self.field_table.on_key_event(key);
+            if caught != Action::Tick {
+                return caught;
+            }
+        }
         match key.code {
             KeyCode::Char('q') => Action::Quit,
             KeyCode::Char('l') => Action::ToggleShowLogger,
@@ -1080,11 +1683,27 @@ impl Component for Home {
             KeyCode::Char('N') => Action::RepeatSearch(crate::action::Direction::Prev),
             KeyCode::Char('f') => Action::FilterListAction(FilterListAction::OpenFilterScreen),
             KeyCode::Char('s') => Action::AutoSkip,
+            KeyCode::Char('r') => Action::NextRare,
+            KeyCode::Char('T') => Action::CycleTimeDisplay,
+            KeyCode::Char('J') => Action::CycleFieldView,
+            KeyCode::Char('z') => Action::ToggleFold,
+            KeyCode::Char('Z') => Action::ToggleFoldAll,
+            KeyCode::Char('D') if !self.diff_screen.is_empty() => {
+                Action::DiffListAction(DiffListAction::OpenDiffScreen)
+            }
+            KeyCode::Char('t') => {
+                Action::TemplateListAction(TemplateListAction::OpenTemplateScreen)
+            }
             _ => Action::Tick,
         }
     }
 
     fn dispatch(&mut self, action: Action) -> Option<Action> {
+        self.poll_template_index();
+        self.poll_day_index();
+        if !matches!(action, Action::Tick | Action::Noop | Action::Resize(..)) {
+            self.notice = None;
+        }
         let mut followup_action = None;
         if self.go_screen.show {
             if action == Action::CursorMove(CursorMove::End(crate::action::Direction::Prev)) {
@@ -1102,6 +1721,10 @@ impl Component for Home {
             Action::Tick => self.tick(),
             Action::ToggleShowLogger => self.show_logger = !self.show_logger,
             Action::CursorMove(cm) => match cm {
+                CursorMove::Timestamp(_) | CursorMove::TimeDelta(_) if !self.days_ready() => {
+                    self.pending_move = Some(cm);
+                    self.notice = Some("Finding where the log goes past midnight first".into());
+                }
                 CursorMove::OneLine(dir) => match dir {
                     crate::action::Direction::Prev => self.prev_line(),
                     crate::action::Direction::Next => {
@@ -1115,12 +1738,21 @@ impl Component for Home {
                 CursorMove::Timestamp(ts) => {
                     self.goto_dt(ts);
                 }
+                CursorMove::TimeDelta(delta) => {
+                    self.goto_delta(delta);
+                }
+                CursorMove::LineNo(line_no) => {
+                    self.byte_cursor = find_line_number(&self.mmap, line_no);
+                }
                 CursorMove::Percentage(pct) => {
                     self.goto_pct(pct);
                 }
                 CursorMove::Screenful(dir) => {
                     self.move_screenful(dir);
                 }
+                CursorMove::ByteOffset(offset) => {
+                    self.byte_cursor = find_line_starting_before(&self.mmap, offset);
+                }
             },
             Action::OpenGoScreen => {
                 self.go_screen.show = true;
@@ -1131,6 +1763,12 @@ impl Component for Home {
                     crate::action::FilterListAction::OpenFilterScreen => {
                         self.show_filter_screen = true
                     }
+                    crate::action::FilterListAction::NewTemplateAtCursor(filter_type) => {
+                        self.add_template_filter_at_cursor(filter_type);
+                    }
+                    crate::action::FilterListAction::AddTemplate(template_id, filter_type) => {
+                        self.add_template_filter(template_id, filter_type);
+                    }
                     _ => {
                         let opt = self.filter_screen.dispatch(action);
                         followup_action = opt;
@@ -1138,7 +1776,32 @@ impl Component for Home {
                     }
                 }
             }
+            Action::TemplateListAction(ta) => match ta {
+                TemplateListAction::OpenTemplateScreen => self.open_template_screen(),
+                TemplateListAction::CloseList => self.template_screen.show = false,
+                TemplateListAction::ShowParams => {
+                    if let Some(template_id) = self.template_screen.selected() {
+                        self.show_params(template_id);
+                    }
+                }
+                _ => followup_action = self.template_screen.dispatch(action),
+            },
+            Action::ColumnsAction(_) => followup_action = self.field_table.dispatch(action),
+            Action::ParamsAction(pa) => match pa {
+                ParamsAction::Close => self.params_screen.show = false,
+                _ => followup_action = self.params_screen.dispatch(action),
+            },
+            Action::DiffListAction(da) => match da {
+                DiffListAction::OpenDiffScreen => self.diff_screen.show = true,
+                DiffListAction::CloseList => self.diff_screen.show = false,
+                _ => followup_action = self.diff_screen.dispatch(action),
+            },
             Action::AutoSkip => self.autoskip(),
+            Action::NextRare => self.next_rare(),
+            Action::CycleTimeDisplay => self.cycle_time_display(),
+            Action::CycleFieldView => self.cycle_field_view(),
+            Action::ToggleFold => self.toggle_fold(),
+            Action::ToggleFoldAll => self.toggle_fold_all(),
             Action::TextEntry(_) => {
                 if self.show_filter_screen {
                     self.filter_screen.dispatch(action);
@@ -1219,6 +1882,39 @@ impl Component for Home {
             rect
         };
 
+        let rect = if self.template_screen.show {
+            let chunks = Layout::default()
+                .direction(ratatui::layout::Direction::Vertical)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(rect);
+            self.template_screen.render(f, chunks[1]);
+            chunks[0]
+        } else {
+            rect
+        };
+
+        let rect = if self.params_screen.show {
+            let chunks = Layout::default()
+                .direction(ratatui::layout::Direction::Vertical)
+                .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .split(rect);
+            self.params_screen.render(f, chunks[1]);
+            chunks[0]
+        } else {
+            rect
+        };
+
+        let rect = if self.diff_screen.show {
+            let chunks = Layout::default()
+                .direction(ratatui::layout::Direction::Vertical)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(rect);
+            self.diff_screen.render(f, chunks[1]);
+            chunks[0]
+        } else {
+            rect
+        };
+
         let rect = if self.go_screen.show {
             let chunks = Layout::default()
                 .direction(ratatui::layout::Direction::Vertical)
@@ -1230,7 +1926,70 @@ impl Component for Home {
             rect
         };
 
-        let s: Vec<_> = self.screen.view.iter().map(|dl| dl.line.clone()).collect();
+        let rect = match &self.notice {
+            Some(notice) => {
+                let chunks = Layout::default()
+                    .direction(ratatui::layout::Direction::Vertical)
+                    .constraints([Constraint::Min(1), Constraint::Length(1)])
+                    .split(rect);
+                f.render_widget(
+                    Paragraph::new(notice.as_str()).style(Style::default().fg(Color::Red)),
+                    chunks[1],
+                );
+                chunks[0]
+            }
+            None => rect,
+        };
+
+        let rare: Vec<_> = match self.template_index {
+            Some(_) => {
+                // Only the first line of a record gets the mark.
+                let locs: Vec<_> = self
+                    .screen
+                    .view
+                    .iter()
+                    .map(|dl| (dl.file_loc.0 == dl.record.0).then_some(dl.record))
+                    .collect();
+                let rare = locs
+                    .into_iter()
+                    .map(|loc| loc.is_some_and(|loc| self.is_rare(loc)));
+                rare.map(|rare| {
+                    Some(match rare {
+                        true => Span::styled("▌", Style::default().fg(Color::Red)),
+                        false => Span::raw(" "),
+                    })
+                })
+                .collect()
+            }
+            None => vec![None; self.screen.view.len()],
+        };
+
+        if self.field_view == FieldView::Columns {
+            let rows = zip(&self.screen.view, rare)
+                .map(|(dl, gutter)| FieldRow {
+                    gutter,
+                    fields: dl.fields.clone(),
+                    line: dl.line.clone(),
+                })
+                .collect();
+            self.field_table.set_rows(rows, &self.last_search);
+            self.field_table.render(f, rect);
+            return;
+        }
+
+        let s: Vec<_> = zip(&self.screen.view, rare)
+            .flat_map(|(dl, gutter)| {
+                let mut line = dl.line.clone();
+                let mut more = dl.more.clone();
+                if let Some(gutter) = gutter {
+                    line.spans.insert(0, gutter);
+                    for more in &mut more {
+                        more.spans.insert(0, Span::raw(" "));
+                    }
+                }
+                std::iter::once(line).chain(more)
+            })
+            .collect();
         f.render_widget(
             Paragraph::new(s)
                 .alignment(Alignment::Left)
@@ -11,15 +11,18 @@ use crate::{
     event::Event,
 };
 
+pub mod diff_screen;
+pub mod field_table;
 pub(crate) mod filter;
 pub mod filter_screen;
 pub mod go_screen;
 pub mod home;
 pub mod logger;
+pub mod params_screen;
+pub mod template_screen;
 pub mod text_entry;
 
 #[allow(async_fn_in_trait)]
-
 pub trait Component {
     // I wanted to add an argument to this but it will be a more involved refactor then i want to take at this moment.
     // At this time it's not clear why init is a property of the Component trait if there's never a call through the trait to it.
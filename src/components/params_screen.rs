@@ -0,0 +1,256 @@
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Row, Sparkline, Table, TableState},
+};
+
+use crate::action::{Action, CursorMove, ParamsAction};
+
+use super::{Component, Frame};
+
+/// One occurrence of a template, with the values that filled in its `<*>`s.
+#[derive(Debug, Clone)]
+pub struct ParamRow {
+    pub file_offset: usize,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub values: Vec<String>,
+}
+
+/// The parameters of a template as a table, one column per `<*>`, with the selected column charted.
+#[derive(Default)]
+pub struct ParamsScreen {
+    pub show: bool,
+    template: String,
+    rows: Vec<ParamRow>,
+    /// Set if there were more occurrences than we were willing to collect.
+    truncated: bool,
+    column: usize,
+    state: TableState,
+}
+
+impl ParamsScreen {
+    pub fn set_rows(&mut self, template: String, rows: Vec<ParamRow>, truncated: bool) {
+        self.template = template;
+        self.rows = rows;
+        self.truncated = truncated;
+        self.column = 0;
+        self.state.select((!self.rows.is_empty()).then_some(0));
+    }
+
+    fn num_columns(&self) -> usize {
+        self.rows.iter().map(|r| r.values.len()).max().unwrap_or(0)
+    }
+
+    /// The selected column as numbers, squashed into `width` buckets by averaging.
+    /// Buckets split the time between the first and last occurrence evenly, and are None if nothing
+    /// happened in them; without timestamps to go by, they split the occurrences evenly instead.
+    /// Values are shifted so the smallest is 0, since the sparkline can't go negative.
+    /// Also returns whether the buckets are by time.
+    fn sparkline_data(&self, width: usize) -> (Vec<Option<u64>>, bool) {
+        let numbers: Vec<(Option<DateTime<Utc>>, f64)> = self
+            .rows
+            .iter()
+            .filter_map(|r| Some((r.timestamp, leading_number(r.values.get(self.column)?)?)))
+            .collect();
+        if numbers.is_empty() || width == 0 {
+            return (Vec::new(), false);
+        }
+        let min = numbers.iter().map(|n| n.1).fold(f64::INFINITY, f64::min);
+        // Keep some precision for small values like 0.25s.
+        let scale = |avg: f64| ((avg - min) * 1000.0) as u64;
+
+        let times = numbers.iter().filter_map(|n| n.0);
+        let span = times.clone().min().zip(times.max());
+        let bucket_of = match span {
+            Some((first, last)) if first < last => {
+                let millis = (last - first).num_milliseconds() as f64;
+                move |ts: Option<DateTime<Utc>>| {
+                    let frac = (ts? - first).num_milliseconds() as f64 / millis;
+                    Some(((frac * width as f64) as usize).min(width - 1))
+                }
+            }
+            _ => {
+                let per_bucket = numbers.len().div_ceil(width);
+                let data = numbers
+                    .chunks(per_bucket)
+                    .map(|bucket| {
+                        Some(scale(
+                            bucket.iter().map(|n| n.1).sum::<f64>() / bucket.len() as f64,
+                        ))
+                    })
+                    .collect();
+                return (data, false);
+            }
+        };
+        let mut sums = vec![(0.0, 0); width];
+        for (ts, n) in &numbers {
+            // Occurrences without a timestamp can't be placed in time.
+            if let Some(bucket) = bucket_of(*ts) {
+                sums[bucket].0 += n;
+                sums[bucket].1 += 1;
+            }
+        }
+        let data = sums
+            .into_iter()
+            .map(|(sum, count)| (count > 0).then(|| scale(sum / count as f64)))
+            .collect();
+        (data, true)
+    }
+}
+
+/// `"5ms"` => 5.0, `"-1.5"` => -1.5, `"abc"` => None.
+fn leading_number(s: &str) -> Option<f64> {
+    let end = s
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && (c == '-' || c == '+'))))
+        .map_or(s.len(), |(i, _)| i);
+    s[..end].parse().ok()
+}
+
+impl Component for ParamsScreen {
+    fn init(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_key_event(&self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => Action::ParamsAction(ParamsAction::Close),
+            KeyCode::Char('j') | KeyCode::Down => Action::ParamsAction(ParamsAction::NextRow),
+            KeyCode::Char('k') | KeyCode::Up => Action::ParamsAction(ParamsAction::PrevRow),
+            KeyCode::Char('l') | KeyCode::Right => Action::ParamsAction(ParamsAction::NextColumn),
+            KeyCode::Char('h') | KeyCode::Left => Action::ParamsAction(ParamsAction::PrevColumn),
+            KeyCode::Enter => Action::ParamsAction(ParamsAction::GoToRow),
+            _ => Action::Tick,
+        }
+    }
+
+    fn dispatch(&mut self, action: Action) -> Option<Action> {
+        if self.rows.is_empty() {
+            return None;
+        }
+        let last = self.rows.len() - 1;
+        let curr = self.state.selected();
+        let columns = self.num_columns().max(1);
+        if let Action::ParamsAction(pa) = action {
+            match pa {
+                ParamsAction::NextRow => {
+                    self.state
+                        .select(curr.map(|c| if c == last { 0 } else { c + 1 }));
+                }
+                ParamsAction::PrevRow => {
+                    self.state
+                        .select(curr.map(|c| if c == 0 { last } else { c - 1 }));
+                }
+                ParamsAction::NextColumn => self.column = (self.column + 1) % columns,
+                ParamsAction::PrevColumn => self.column = (self.column + columns - 1) % columns,
+                ParamsAction::GoToRow => {
+                    self.show = false;
+                    return Some(Action::CursorMove(CursorMove::ByteOffset(
+                        self.rows.get(curr?)?.file_offset,
+                    )));
+                }
+                ParamsAction::Close => unimplemented!(),
+            }
+        }
+        None
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, rect: Rect) {
+        let chunks = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(6)])
+            .split(rect);
+
+        let columns = self.num_columns();
+        let header = Row::new(
+            std::iter::once("time".to_string())
+                .chain((0..columns).map(|c| match c == self.column {
+                    true => format!("[${}]", c + 1),
+                    false => format!("${}", c + 1),
+                }))
+                .collect::<Vec<_>>(),
+        )
+        .style(Style::default().add_modifier(Modifier::UNDERLINED));
+        let rows = self.rows.iter().map(|r| {
+            let ts = r.timestamp.map_or(String::new(), |ts| {
+                ts.with_timezone(&Local)
+                    .format("%m-%d %H:%M:%S%.3f")
+                    .to_string()
+            });
+            Row::new(std::iter::once(ts).chain(r.values.iter().cloned()))
+        });
+        let widths = std::iter::once(Constraint::Length(18))
+            .chain((0..columns).map(|_| Constraint::Fill(1)))
+            .collect::<Vec<_>>();
+        let truncated = match self.truncated {
+            true => " (first occurrences only)",
+            false => "",
+        };
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(
+                Block::default()
+                    .title(format!(
+                        "Params of {}{}: (h/l) Column (enter) Go to row (q/escape) Close ",
+                        self.template, truncated
+                    ))
+                    .borders(Borders::ALL.difference(Borders::BOTTOM)),
+            )
+            .style(Style::default().fg(Color::White))
+            .row_highlight_style(Style::default().add_modifier(Modifier::BOLD))
+            .highlight_symbol(">> ");
+        f.render_stateful_widget(table, chunks[0], &mut self.state);
+
+        let (data, by_time) = self.sparkline_data(chunks[1].width.saturating_sub(2).into());
+        let title = match (data.is_empty(), by_time) {
+            (true, _) => format!("${} is not numeric", self.column + 1),
+            (false, true) => format!("${} over time", self.column + 1),
+            (false, false) => format!("${} by occurrence", self.column + 1),
+        };
+        let sparkline = Sparkline::default()
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .data(&data)
+            .style(Style::default().fg(Color::Yellow));
+        f.render_widget(sparkline, chunks[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn row(secs: i64, value: &str) -> ParamRow {
+        ParamRow {
+            file_offset: 0,
+            timestamp: Some(Utc.timestamp_opt(secs, 0).unwrap()),
+            values: vec![value.to_string()],
+        }
+    }
+
+    #[test]
+    fn test_sparkline_by_time() {
+        let mut screen = ParamsScreen::default();
+        // A burst at the start, then one occurrence much later.
+        screen.set_rows(
+            "took <*>".to_string(),
+            vec![row(0, "1ms"), row(1, "3ms"), row(2, "2ms"), row(99, "5ms")],
+            false,
+        );
+        let (data, by_time) = screen.sparkline_data(4);
+        assert!(by_time);
+        assert_eq!(data, vec![Some(1000), None, None, Some(4000)]);
+
+        // Without timestamps, buckets go by occurrence.
+        for row in screen.rows.iter_mut() {
+            row.timestamp = None;
+        }
+        let (data, by_time) = screen.sparkline_data(2);
+        assert!(!by_time);
+        assert_eq!(data, vec![Some(1000), Some(2500)]);
+    }
+}
@@ -0,0 +1,111 @@
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, List, ListItem, ListState},
+};
+
+use crate::action::{Action, FilterListAction, FilterType, TemplateListAction};
+
+use super::{Component, Frame};
+
+/// Lists the drain templates recognized so far, so that one can be picked for filtering.
+#[derive(Default)]
+pub struct TemplateScreen {
+    pub show: bool,
+    /// Index is the template id.
+    items: Vec<String>,
+    state: ListState,
+}
+
+impl TemplateScreen {
+    pub fn set_items(&mut self, items: Vec<String>) {
+        self.items = items;
+        if self.state.selected().is_none() && !self.items.is_empty() {
+            self.state.select(Some(0));
+        }
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.state.selected().filter(|&idx| idx < self.items.len())
+    }
+}
+
+impl Component for TemplateScreen {
+    fn init(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn on_key_event(&self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => {
+                Action::TemplateListAction(TemplateListAction::CloseList)
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                Action::TemplateListAction(TemplateListAction::NextItem)
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                Action::TemplateListAction(TemplateListAction::PrevItem)
+            }
+            KeyCode::Char('i') => {
+                Action::TemplateListAction(TemplateListAction::Filter(FilterType::In))
+            }
+            KeyCode::Char('o') => {
+                Action::TemplateListAction(TemplateListAction::Filter(FilterType::Out))
+            }
+            KeyCode::Char('p') => Action::TemplateListAction(TemplateListAction::ShowParams),
+            _ => Action::Tick,
+        }
+    }
+
+    fn dispatch(&mut self, action: Action) -> Option<Action> {
+        if self.items.is_empty() {
+            return None;
+        }
+        let last = self.items.len() - 1;
+        let curr = self.selected();
+        if let Action::TemplateListAction(ta) = action {
+            match ta {
+                TemplateListAction::NextItem => {
+                    self.state
+                        .select(curr.map(|c| if c == last { 0 } else { c + 1 }));
+                }
+                TemplateListAction::PrevItem => {
+                    self.state
+                        .select(curr.map(|c| if c == 0 { last } else { c - 1 }));
+                }
+                TemplateListAction::Filter(filter_type) => {
+                    self.show = false;
+                    return Some(Action::FilterListAction(FilterListAction::AddTemplate(
+                        curr?,
+                        filter_type,
+                    )));
+                }
+                TemplateListAction::OpenTemplateScreen => unimplemented!(),
+                TemplateListAction::CloseList => unimplemented!(),
+                TemplateListAction::ShowParams => unimplemented!(),
+            }
+        }
+        None
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, rect: Rect) {
+        let items: Vec<_> = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(id, template)| ListItem::new(format!("{:>4} | {}", id, template)))
+            .collect();
+        let l = List::new(items)
+            .block(
+                Block::default()
+                    .title("Templates: (i) Filter In (o) Filter Out (p) Params (q/enter/escape) Close ")
+                    .borders(Borders::ALL.difference(Borders::BOTTOM)),
+            )
+            .style(Style::default().fg(Color::White))
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+            .highlight_symbol(">> ");
+        f.render_stateful_widget(l, rect, &mut self.state);
+    }
+}
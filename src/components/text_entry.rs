@@ -58,12 +58,9 @@ impl<'a> Component for TextEntry<'a> {
     }
 
     fn dispatch(&mut self, action: Action) -> Option<Action> {
-        match action {
-            Action::TextEntry(key) => {
-                self.textarea.input(key);
-                // info!("Received: {:?}", action);
-            }
-            _ => {}
+        if let Action::TextEntry(key) = action {
+            self.textarea.input(key);
+            // info!("Received: {:?}", action);
         }
         None
     }
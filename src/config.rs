@@ -0,0 +1,220 @@
+//! Options which change how a log file is interpreted, as opposed to how wd itself runs.
+
+use bstr::ByteSlice;
+use chrono_tz::Tz;
+use clap::Args;
+use regex::Regex;
+
+use crate::{
+    drainrs::{DrainConfig, LogFormat, RecordBoundary, RecordParser},
+    fields::{FieldFormat, FieldSource, Locator, DETECT_RECORDS, LEVEL_KEYS, MSG_KEYS, TS_KEYS},
+    records::{RecordStart, Records},
+    timestamps::{TimestampParser, TsFormat},
+};
+
+#[derive(Args, Debug, Clone)]
+pub struct Config {
+    /// Layout of each record, e.g. "<Date> <Time> <Level> <Component>: <Content>".
+    /// Only <Content> is used to recognize templates, so timestamps and the like don't become part of them.
+    #[arg(long, global = true)]
+    pub log_format: Option<LogFormat>,
+
+    /// Fraction of a record's tokens that have to match a template for the record to belong to it.
+    #[arg(long, global = true, default_value_t = DrainConfig::default().similarity_threshold)]
+    pub similarity_threshold: f64,
+
+    /// Depth of drain's prefix tree; the first depth-1 tokens of a record must match a template's exactly.
+    #[arg(long, global = true, default_value_t = DrainConfig::default().depth, value_parser = parse_depth)]
+    pub depth: usize,
+
+    /// Most distinct tokens to branch on at each level of drain's prefix tree.
+    #[arg(long, global = true, default_value_t = DrainConfig::default().max_children)]
+    pub max_children: usize,
+
+    /// Tokens that entirely match this regex are always parameters, e.g. "0x[0-9a-f]+". Can be repeated.
+    #[arg(long = "mask", value_name = "REGEX", global = true, value_parser = parse_mask)]
+    pub masks: Vec<Regex>,
+
+    /// strftime-style format of record timestamps, e.g. "%Y-%m-%d|%H:%M:%S%.3f", for ones wd doesn't recognize.
+    /// The timestamp has to start the line, or whatever --ts-regex finds.
+    #[arg(long, global = true)]
+    pub ts_format: Option<TsFormat>,
+
+    /// Regex that finds the timestamp of a record anywhere in its first line, e.g. "ts=(\d+)".
+    /// If it has a capture group, the first group is the timestamp, otherwise the whole match is.
+    #[arg(long, global = true)]
+    pub ts_regex: Option<Regex>,
+
+    /// IANA name of the zone that log timestamps without an offset are in, e.g. "Europe/Berlin" or "UTC".
+    /// Also the zone of times typed into the go box. Defaults to local time.
+    #[arg(long, global = true)]
+    pub tz: Option<Tz>,
+
+    /// Which lines start a record, the rest continuing the one above them: "line", "indent" (lines that aren't
+    /// indented, empty or a "Caused by:"), "timestamp", or a regex, e.g. '^\d{4}-'.
+    #[arg(long, global = true, default_value = "indent")]
+    pub record_start: RecordStart,
+
+    /// How records are made of named fields, which can then be shown, filtered and timestamped by name.
+    /// Detected from the first records if not given and there's no --record-regex or --log-format, whose fields
+    /// are used otherwise.
+    #[arg(long, global = true)]
+    pub fields: Option<FieldFormat>,
+
+    /// Regex matching whole records whose named groups are their fields, e.g.
+    /// '^(?P<ts>\S+ \S+) (?P<level>\w+)\s+:(?P<component>[^:]+): (?P<msg>.*)$'.
+    #[arg(long, global = true)]
+    pub record_regex: Option<Regex>,
+
+    /// Field holding the timestamp of records with fields. By default the first of "ts", "time", "timestamp"
+    /// and the like that the first record has.
+    #[arg(long, global = true)]
+    pub ts_key: Option<String>,
+
+    /// Fields shown in the column view, each optionally with a width, e.g. "ts,level:5,msg". By default the
+    /// timestamp, level and message, then every other field of the first record.
+    #[arg(long, global = true, value_delimiter = ',')]
+    pub columns: Vec<String>,
+
+    /// Field holding the severity of records with fields, found by [`Self::detect_fields`].
+    #[arg(skip)]
+    pub level_key: Option<String>,
+
+    /// Only fields shown in the collapsed and pretty views, e.g. "ts,level,msg,err". By default all of them.
+    #[arg(long, global = true, value_delimiter = ',')]
+    pub show_keys: Vec<String>,
+
+    /// Fields left out of the collapsed and pretty views, and hidden in the column view, e.g. "caller,hostname".
+    #[arg(long, global = true, value_delimiter = ',')]
+    pub hide_keys: Vec<String>,
+
+    /// Mark records rare if their template occurs fewer than this many times in the whole file.
+    #[arg(long, global = true, default_value_t = 3)]
+    pub rare_below: usize,
+
+    /// Also mark records rare if their template first occurs after this percent of the file.
+    #[arg(long, global = true)]
+    pub rare_after_pct: Option<f64>,
+}
+
+fn parse_depth(s: &str) -> Result<usize, String> {
+    match s.parse() {
+        Ok(depth) if depth >= 2 => Ok(depth),
+        Ok(_) => Err("must be at least 2".to_string()),
+        Err(e) => Err(format!("{e}")),
+    }
+}
+
+fn parse_mask(s: &str) -> Result<Regex, regex::Error> {
+    Regex::new(&format!("^(?:{s})$"))
+}
+
+impl Config {
+    pub fn drain_config(&self) -> DrainConfig {
+        DrainConfig {
+            similarity_threshold: self.similarity_threshold,
+            depth: self.depth,
+            max_children: self.max_children,
+            masks: self.masks.clone(),
+        }
+    }
+
+    pub fn timestamp_parser(&self) -> TimestampParser {
+        let by_key = self
+            .field_source()
+            .zip(self.ts_key.as_deref())
+            .and_then(|(source, key)| source.key_locator(key));
+        TimestampParser::default()
+            .with_format(self.ts_format.clone())
+            .with_locator(self.ts_regex.clone().map(Locator::new).or(by_key))
+            .with_tz(self.tz)
+    }
+
+    /// Splits this log into records.
+    pub fn records(&self) -> Records {
+        Records::new(self.record_start.clone(), self.timestamp_parser())
+    }
+
+    /// Where the fields of records come from, if they have any.
+    pub fn field_source(&self) -> Option<FieldSource> {
+        match (self.fields, &self.record_regex, &self.log_format) {
+            (Some(format), _, _) => Some(FieldSource::Format(format)),
+            (None, Some(re), _) => Some(FieldSource::Regex(re.clone())),
+            (None, None, Some(log_format)) => Some(FieldSource::LogFormat(log_format.clone())),
+            (None, None, None) => None,
+        }
+    }
+
+    /// Fills in whether records have fields, which one is the timestamp and which are the columns,
+    /// going by the first records of `source`, wherever they weren't given.
+    pub fn detect_fields(&mut self, source: &[u8]) {
+        if self.record_regex.is_none() && self.log_format.is_none() {
+            self.fields = self.fields.or_else(|| FieldFormat::detect(source));
+        }
+        let first = match self.field_source().and_then(|fields| {
+            source
+                .lines()
+                .take(DETECT_RECORDS)
+                .find_map(|line| fields.parse(&line.to_str_lossy()))
+        }) {
+            Some(first) => first,
+            None => return,
+        };
+        if self.ts_key.is_none() {
+            self.ts_key = first.first_of(&TS_KEYS).map(str::to_string);
+        }
+        self.level_key = first.first_of(&LEVEL_KEYS).map(str::to_string);
+        if self.columns.is_empty() {
+            let mut columns: Vec<_> = [
+                self.ts_key.as_deref(),
+                self.level_key.as_deref(),
+                first.first_of(&MSG_KEYS),
+            ]
+            .into_iter()
+            .flatten()
+            .map(str::to_string)
+            .collect();
+            for (key, _) in first.iter() {
+                if !columns.iter().any(|column| column == key) {
+                    columns.push(key.to_string());
+                }
+            }
+            self.columns = columns;
+        }
+    }
+
+    /// A parser for records of this log, which hasn't seen any of them yet.
+    /// It's given whole records, as split up by [`Self::records`].
+    pub fn record_parser(&self) -> RecordParser {
+        RecordParser::default()
+            .with_config(self.drain_config())
+            .with_format(self.log_format.clone())
+            .with_boundary(RecordBoundary::Whole)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let drain = DrainConfig::default();
+        Self {
+            log_format: None,
+            similarity_threshold: drain.similarity_threshold,
+            depth: drain.depth,
+            max_children: drain.max_children,
+            masks: drain.masks,
+            ts_format: None,
+            ts_regex: None,
+            tz: None,
+            record_start: RecordStart::default(),
+            fields: None,
+            ts_key: None,
+            record_regex: None,
+            level_key: None,
+            columns: Vec::new(),
+            show_keys: Vec::new(),
+            hide_keys: Vec::new(),
+            rare_below: 3,
+            rare_after_pct: None,
+        }
+    }
+}
@@ -7,7 +7,185 @@ use chrono::prelude::*;
 use lazy_static::lazy_static;
 use regex::Regex;
 
-/// Parse struct has methods implemented parsers for accepted formats.
+/// The groups of formats that [`parse_partial`] tries one after another, in that order.
+/// A log file nearly always sticks to one, so callers can remember which matched and pass it as a hint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Family {
+    UnixTimestamp,
+    Rfc2822,
+    Ymd,
+    Hms,
+    MonthYmd,
+    MonthMdy,
+    MonthDmy,
+    SlashMdy,
+    SlashYmd,
+    DotMdyOrYmd,
+    MysqlLog,
+    ChineseYmd,
+    /// BSD syslog, which has no year.
+    Syslog,
+    /// Apache and NGINX access logs.
+    Clf,
+    /// Kubernetes and everything else using klog or glog, which has no year either.
+    Klog,
+}
+
+impl Family {
+    pub const ALL: [Family; 15] = [
+        Family::UnixTimestamp,
+        Family::Rfc2822,
+        Family::Ymd,
+        Family::Hms,
+        // Before MonthMdy, which would take the year to be this one.
+        Family::Syslog,
+        Family::MonthYmd,
+        Family::MonthMdy,
+        Family::MonthDmy,
+        Family::SlashMdy,
+        Family::SlashYmd,
+        Family::DotMdyOrYmd,
+        Family::MysqlLog,
+        Family::ChineseYmd,
+        Family::Clf,
+        Family::Klog,
+    ];
+
+    /// None if `input` doesn't look like anything in this family.
+    pub fn parse(self, input: &str) -> Option<Result<Partial>> {
+        match self {
+            Family::UnixTimestamp => unix_timestamp(input),
+            Family::Rfc2822 => rfc2822(input),
+            Family::Ymd => ymd_family(input),
+            Family::Hms => hms_family(input),
+            Family::MonthYmd => month_ymd(input),
+            Family::MonthMdy => month_mdy_family(input),
+            Family::MonthDmy => month_dmy_family(input),
+            Family::SlashMdy => slash_mdy_family(input),
+            Family::SlashYmd => slash_ymd_family(input),
+            Family::DotMdyOrYmd => dot_mdy_or_ymd(input),
+            Family::MysqlLog => mysql_log_timestamp(input),
+            Family::ChineseYmd => chinese_ymd_family(input),
+            Family::Syslog => syslog(input),
+            Family::Clf => clf(input),
+            Family::Klog => klog(input),
+        }
+    }
+}
+
+/// What a timestamp actually said. Whatever it left out is for the caller to fill in from context,
+/// say from the rest of the file, or with [`Parse::resolve`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Partial {
+    pub date: Option<PartialDate>,
+    pub time: Option<NaiveTime>,
+    /// Only if the timestamp had a zone or offset of its own.
+    pub offset: Option<FixedOffset>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartialDate {
+    Full(NaiveDate),
+    /// As written by syslog and klog.
+    MonthDay {
+        month: u32,
+        day: u32,
+    },
+    /// As in `2014.03`.
+    YearMonth {
+        year: i32,
+        month: u32,
+    },
+}
+
+impl Partial {
+    fn local(datetime: NaiveDateTime) -> Self {
+        Self {
+            date: Some(PartialDate::Full(datetime.date())),
+            time: Some(datetime.time()),
+            offset: None,
+        }
+    }
+
+    fn instant<Tz: TimeZone>(datetime: DateTime<Tz>) -> Self {
+        let datetime = datetime.fixed_offset();
+        Self {
+            offset: Some(*datetime.offset()),
+            ..Self::local(datetime.naive_local())
+        }
+    }
+
+    fn date(date: NaiveDate) -> Self {
+        Self {
+            date: Some(PartialDate::Full(date)),
+            time: None,
+            offset: None,
+        }
+    }
+
+    fn time(time: NaiveTime) -> Self {
+        Self {
+            date: None,
+            time: Some(time),
+            offset: None,
+        }
+    }
+
+    fn with_offset(self, offset: FixedOffset) -> Self {
+        Self {
+            offset: Some(offset),
+            ..self
+        }
+    }
+}
+
+impl PartialDate {
+    /// Fills in what's missing from `default_day`. A date without a year is taken to be in the default day's
+    /// year, unless that would put it over a month after the default day, as with December's logs read in
+    /// January; then it's from the year before. A date without a day gets the default day's, or the month's
+    /// last day if the month is shorter.
+    pub fn on(self, default_day: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            PartialDate::Full(date) => Some(date),
+            PartialDate::MonthDay { month, day } => {
+                let year = default_day.year();
+                [year, year - 1].into_iter().find_map(|year| {
+                    NaiveDate::from_ymd_opt(year, month, day)
+                        .filter(|date| (*date - default_day).num_days() <= 31)
+                })
+            }
+            PartialDate::YearMonth { year, month } => (1..=default_day.day())
+                .rev()
+                .find_map(|day| NaiveDate::from_ymd_opt(year, month, day)),
+        }
+    }
+}
+
+/// Parses `input` with the formats of every family, starting with `hint`, and says which family matched.
+/// Nothing that `input` doesn't say is filled in, so the result depends on nothing else.
+///
+/// ```
+/// use chrono::prelude::*;
+/// use wd::dateparser::datetime::{parse_partial, Family, PartialDate};
+///
+/// let (partial, family) = parse_partial("Oct 11 22:14:15", None).unwrap();
+/// assert_eq!(family, Family::Syslog);
+/// assert_eq!(partial.date, Some(PartialDate::MonthDay { month: 10, day: 11 }));
+/// assert_eq!(partial.time, NaiveTime::from_hms_opt(22, 14, 15));
+/// assert_eq!(partial.offset, None);
+/// ```
+pub fn parse_partial(input: &str, hint: Option<Family>) -> Result<(Partial, Family)> {
+    if let Some(Ok(parsed)) = hint.and_then(|family| family.parse(input)) {
+        return Ok((parsed, hint.unwrap()));
+    }
+    Family::ALL
+        .into_iter()
+        .filter(|&family| Some(family) != hint)
+        .find_map(|family| Some(family.parse(input)?.map(|p| (p, family))))
+        .unwrap_or_else(|| Err(anyhow!("{} did not match any formats.", input)))
+}
+
+/// Parse struct fills in whatever a timestamp left out, to get to a [`DateTime`].
 pub struct Parse<'z, Tz2> {
     tz: &'z Tz2,
     default_time: Option<NaiveTime>,
@@ -19,7 +197,8 @@ where
     Tz2: TimeZone,
 {
     /// Create a new instrance of [`Parse`] with a custom parsing timezone that handles the
-    /// datetime string without time offset.
+    /// datetime string without time offset. Without a default time, a date alone is at the current time;
+    /// without a default day, a time alone is today.
     pub fn new(
         tz: &'z Tz2,
         default_time: Option<NaiveTime>,
@@ -28,801 +207,860 @@ where
         Self {
             tz,
             default_time,
-            default_day: default_day.unwrap_or(Local::now().with_timezone(tz).date_naive()),
+            default_day: default_day.unwrap_or_else(|| Local::now().with_timezone(tz).date_naive()),
         }
     }
 
     /// This method tries to parse the input datetime string with a list of accepted formats. See
     /// more exmaples from [`Parse`], [`crate::parse()`] and [`crate::parse_with_timezone()`].
     pub fn parse(&self, input: &str) -> Result<DateTime<Utc>> {
-        self.unix_timestamp(input)
-            .or_else(|| self.rfc2822(input))
-            .or_else(|| self.ymd_family(input))
-            .or_else(|| self.hms_family(input))
-            .or_else(|| self.month_ymd(input))
-            .or_else(|| self.month_mdy_family(input))
-            .or_else(|| self.month_dmy_family(input))
-            .or_else(|| self.slash_mdy_family(input))
-            .or_else(|| self.slash_ymd_family(input))
-            .or_else(|| self.dot_mdy_or_ymd(input))
-            .or_else(|| self.mysql_log_timestamp(input))
-            .or_else(|| self.chinese_ymd_family(input))
-            .unwrap_or_else(|| Err(anyhow!("{} did not match any formats.", input)))
-    }
-
-    fn ymd_family(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"^[0-9]{4}-[0-9]{2}").unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
-        self.rfc3339(input)
-            .or_else(|| self.postgres_timestamp(input))
-            .or_else(|| self.ymd_hms(input))
-            .or_else(|| self.ymd_hms_z(input))
-            .or_else(|| self.ymd(input))
-            .or_else(|| self.ymd_z(input))
+        self.parse_hinted(input, None).map(|(parsed, _)| parsed)
     }
 
-    fn hms_family(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"^[0-9]{1,2}:[0-9]{2}").unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
-        self.hms(input).or_else(|| self.hms_z(input))
+    /// Same as [`Self::parse`], but tries the `hint` family first, and says which family matched.
+    /// Every family bails out early on input that doesn't look like it, but going through all of them
+    /// still adds up when parsing a timestamp per line.
+    ///
+    /// ```
+    /// use chrono::prelude::*;
+    /// use wd::dateparser::datetime::{Family, Parse};
+    ///
+    /// let parse = Parse::new(&Utc, None, None);
+    /// let (first, family) = parse.parse_hinted("03/22/2022 08:51:06", None).unwrap();
+    /// assert_eq!(family, Family::SlashMdy);
+    /// let (second, _) = parse.parse_hinted("03/22/2022 08:51:08", Some(family)).unwrap();
+    /// assert_eq!((second - first).num_seconds(), 2);
+    /// // A wrong hint only costs time.
+    /// assert_eq!(parse.parse_hinted("08:51:06", Some(family)).unwrap().1, Family::Hms);
+    /// ```
+    pub fn parse_hinted(
+        &self,
+        input: &str,
+        hint: Option<Family>,
+    ) -> Result<(DateTime<Utc>, Family)> {
+        let (partial, family) = parse_partial(input, hint)?;
+        Ok((self.resolve(partial)?, family))
     }
 
-    fn month_mdy_family(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"^[a-zA-Z]{3,9}\.?\s+[0-9]{1,2}").unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
-        self.month_md_hms(input)
-            .or_else(|| self.month_mdy_hms(input))
-            .or_else(|| self.month_mdy_hms_z(input))
-            .or_else(|| self.month_mdy(input))
+    /// None if `input` doesn't look like anything in `family`.
+    pub fn parse_family(&self, family: Family, input: &str) -> Option<Result<DateTime<Utc>>> {
+        family
+            .parse(input)
+            .map(|parsed| parsed.and_then(|partial| self.resolve(partial)))
     }
 
-    fn month_dmy_family(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"^[0-9]{1,2}\s+[a-zA-Z]{3,9}").unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
-        self.month_dmy_hms(input).or_else(|| self.month_dmy(input))
+    /// Fills in the date from the default day, the time from the default time, and the zone from the
+    /// timezone, wherever `partial` doesn't have them. A local time that happens twice is the earlier one.
+    ///
+    /// ```
+    /// use chrono::prelude::*;
+    /// use wd::dateparser::datetime::{parse_partial, Parse};
+    ///
+    /// let (partial, _) = parse_partial("Dec 31 23:59:59", None).unwrap();
+    /// let parse = Parse::new(&Utc, None, NaiveDate::from_ymd_opt(2024, 1, 1));
+    /// assert_eq!(
+    ///     parse.resolve(partial).unwrap(),
+    ///     Utc.with_ymd_and_hms(2023, 12, 31, 23, 59, 59).unwrap(),
+    /// );
+    /// ```
+    pub fn resolve(&self, partial: Partial) -> Result<DateTime<Utc>> {
+        let date = match partial.date {
+            Some(date) => date
+                .on(self.default_day)
+                .ok_or_else(|| anyhow!("{:?} isn't a date around {}", date, self.default_day))?,
+            None => self.default_day,
+        };
+        let time = partial
+            .time
+            .or(self.default_time)
+            .unwrap_or_else(|| match partial.offset {
+                Some(offset) => Utc::now().with_timezone(&offset).time(),
+                None => Utc::now().with_timezone(self.tz).time(),
+            });
+        let datetime = date.and_time(time);
+        match partial.offset {
+            Some(offset) => offset
+                .from_local_datetime(&datetime)
+                .earliest()
+                .map(|at_tz| at_tz.with_timezone(&Utc)),
+            None => self
+                .tz
+                .from_local_datetime(&datetime)
+                .earliest()
+                .map(|at_tz| at_tz.with_timezone(&Utc)),
+        }
+        .ok_or_else(|| anyhow!("no valid time"))
     }
+}
 
-    fn slash_mdy_family(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"^[0-9]{1,2}/[0-9]{1,2}").unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
-        self.slash_mdy_hms(input).or_else(|| self.slash_mdy(input))
+fn ymd_family(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^[0-9]{4}-[0-9]{2}").unwrap();
     }
+    if !RE.is_match(input) {
+        return None;
+    }
+    rfc3339(input)
+        .or_else(|| postgres_timestamp(input))
+        .or_else(|| ymd_hms(input))
+        .or_else(|| ymd_hms_z(input))
+        .or_else(|| ymd(input))
+        .or_else(|| ymd_z(input))
+}
 
-    fn slash_ymd_family(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"^[0-9]{4}/[0-9]{1,2}").unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
-        self.slash_ymd_hms(input).or_else(|| self.slash_ymd(input))
+fn hms_family(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^[0-9]{1,2}:[0-9]{2}").unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
     }
+    hms(input).or_else(|| hms_z(input))
+}
 
-    fn chinese_ymd_family(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"^[0-9]{4}年[0-9]{2}月").unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
-        self.chinese_ymd_hms(input)
-            .or_else(|| self.chinese_ymd(input))
+fn month_mdy_family(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^[a-zA-Z]{3,9}\.?\s+[0-9]{1,2}").unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
     }
+    month_md_hms(input)
+        .or_else(|| month_mdy_hms(input))
+        .or_else(|| month_mdy_hms_z(input))
+        .or_else(|| month_mdy(input))
+}
 
-    // unix timestamp
-    // - 1511648546
-    // - 1620021848429
-    // - 1620024872717915000
-    fn unix_timestamp(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"^[0-9]{10,19}$").unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
+fn month_dmy_family(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^[0-9]{1,2}\s+[a-zA-Z]{3,9}").unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
+    month_dmy_hms(input).or_else(|| month_dmy(input))
+}
 
-        input
-            .parse::<i64>()
-            .ok()
-            .and_then(|timestamp| {
-                match input.len() {
-                    10 => Some(Utc.timestamp(timestamp, 0)),
-                    13 => Some(Utc.timestamp_millis(timestamp)),
-                    19 => Some(Utc.timestamp_nanos(timestamp)),
-                    _ => None,
-                }
-                .map(|datetime| datetime.with_timezone(&Utc))
-            })
-            .map(Ok)
-    }
-
-    // rfc3339
-    // - 2021-05-01T01:17:02.604456Z
-    // - 2017-11-25T22:34:50Z
-    fn rfc3339(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        DateTime::parse_from_rfc3339(input)
-            .ok()
-            .map(|parsed| parsed.with_timezone(&Utc))
-            .map(Ok)
-    }
-
-    // rfc2822
-    // - Wed, 02 Jun 2021 06:31:39 GMT
-    fn rfc2822(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        DateTime::parse_from_rfc2822(input)
-            .ok()
-            .map(|parsed| parsed.with_timezone(&Utc))
-            .map(Ok)
-    }
-
-    // postgres timestamp yyyy-mm-dd hh:mm:ss z
-    // - 2019-11-29 08:08-08
-    // - 2019-11-29 08:08:05-08
-    // - 2021-05-02 23:31:36.0741-07
-    // - 2021-05-02 23:31:39.12689-07
-    // - 2019-11-29 08:15:47.624504-08
-    // - 2017-07-19 03:21:51+00:00
-    fn postgres_timestamp(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(
-                r"^[0-9]{4}-[0-9]{2}-[0-9]{2}\s+[0-9]{2}:[0-9]{2}(:[0-9]{2})?(\.[0-9]{1,9})?[+-:0-9]{3,6}$",
-            )
-            .unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
+fn slash_mdy_family(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^[0-9]{1,2}/[0-9]{1,2}").unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
+    slash_mdy_hms(input).or_else(|| slash_mdy(input))
+}
 
-        DateTime::parse_from_str(input, "%Y-%m-%d %H:%M:%S%#z")
-            .or_else(|_| DateTime::parse_from_str(input, "%Y-%m-%d %H:%M:%S%.f%#z"))
-            .or_else(|_| DateTime::parse_from_str(input, "%Y-%m-%d %H:%M%#z"))
-            .ok()
-            .map(|parsed| parsed.with_timezone(&Utc))
-            .map(Ok)
-    }
-
-    // yyyy-mm-dd hh:mm:ss
-    // - 2014-04-26 05:24:37 PM
-    // - 2021-04-30 21:14
-    // - 2021-04-30 21:14:10
-    // - 2021-04-30 21:14:10.052282
-    // - 2014-04-26 17:24:37.123
-    // - 2014-04-26 17:24:37.3186369
-    // - 2012-08-03 18:31:59.257000000
-    fn ymd_hms(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(
-                r"^[0-9]{4}-[0-9]{2}-[0-9]{2}\s+[0-9]{2}:[0-9]{2}(:[0-9]{2})?(\.[0-9]{1,9})?\s*(am|pm|AM|PM)?$",
-            )
-            .unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
+fn slash_ymd_family(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^[0-9]{4}/[0-9]{1,2}").unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
+    slash_ymd_hms(input).or_else(|| slash_ymd(input))
+}
 
-        self.tz
-            .datetime_from_str(input, "%Y-%m-%d %H:%M:%S")
-            .or_else(|_| self.tz.datetime_from_str(input, "%Y-%m-%d %H:%M"))
-            .or_else(|_| self.tz.datetime_from_str(input, "%Y-%m-%d %H:%M:%S%.f"))
-            .or_else(|_| self.tz.datetime_from_str(input, "%Y-%m-%d %I:%M:%S %P"))
-            .or_else(|_| self.tz.datetime_from_str(input, "%Y-%m-%d %I:%M %P"))
-            .ok()
-            .map(|parsed| parsed.with_timezone(&Utc))
-            .map(Ok)
-    }
-
-    // yyyy-mm-dd hh:mm:ss z
-    // - 2017-11-25 13:31:15 PST
-    // - 2017-11-25 13:31 PST
-    // - 2014-12-16 06:20:00 UTC
-    // - 2014-12-16 06:20:00 GMT
-    // - 2014-04-26 13:13:43 +0800
-    // - 2014-04-26 13:13:44 +09:00
-    // - 2012-08-03 18:31:59.257000000 +0000
-    // - 2015-09-30 18:48:56.35272715 UTC
-    fn ymd_hms_z(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(
-                r"^[0-9]{4}-[0-9]{2}-[0-9]{2}\s+[0-9]{2}:[0-9]{2}(:[0-9]{2})?(\.[0-9]{1,9})?(?P<tz>\s*[+-:a-zA-Z0-9]{3,6})$",
-            ).unwrap();
-        }
+fn chinese_ymd_family(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^[0-9]{4}年[0-9]{2}月").unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
+    chinese_ymd_hms(input).or_else(|| chinese_ymd(input))
+}
 
-        if !RE.is_match(input) {
-            return None;
-        }
-        if let Some(caps) = RE.captures(input) {
-            if let Some(matched_tz) = caps.name("tz") {
-                let parse_from_str = NaiveDateTime::parse_from_str;
-                return match timezone::parse(matched_tz.as_str().trim()) {
-                    Ok(offset) => parse_from_str(input, "%Y-%m-%d %H:%M:%S %Z")
-                        .or_else(|_| parse_from_str(input, "%Y-%m-%d %H:%M %Z"))
-                        .or_else(|_| parse_from_str(input, "%Y-%m-%d %H:%M:%S%.f %Z"))
-                        .ok()
-                        .and_then(|parsed| offset.from_local_datetime(&parsed).single())
-                        .map(|datetime| datetime.with_timezone(&Utc))
-                        .map(Ok),
-                    Err(err) => Some(Err(err)),
-                };
-            }
-        }
-        None
+// unix timestamp
+// - 1511648546
+// - 1620021848429
+// - 1620021848429123 (journald's __REALTIME_TIMESTAMP)
+// - 1620024872717915000
+fn unix_timestamp(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^[0-9]{10,19}$").unwrap();
+        static ref FRACTIONAL: Regex = Regex::new(r"^([0-9]{10})\.([0-9]{1,9})$").unwrap();
+    }
+    // Seconds with a fraction, as JSON loggers like zap write them.
+    if let Some(caps) = FRACTIONAL.captures(input) {
+        let secs = caps[1].parse::<i64>().ok()?;
+        let nanos = format!("{:0<9}", &caps[2]).parse::<u32>().ok()?;
+        return DateTime::from_timestamp(secs, nanos)
+            .map(Partial::instant)
+            .map(Ok);
+    }
+    if !RE.is_match(input) {
+        return None;
     }
 
-    // yyyy-mm-dd
-    // - 2021-02-21
-    fn ymd(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"^[0-9]{4}-[0-9]{2}-[0-9]{2}$").unwrap();
-        }
+    input
+        .parse::<i64>()
+        .ok()
+        .and_then(|timestamp| match input.len() {
+            10 => Some(Utc.timestamp(timestamp, 0)),
+            13 => Some(Utc.timestamp_millis(timestamp)),
+            16 => DateTime::from_timestamp_micros(timestamp),
+            19 => Some(Utc.timestamp_nanos(timestamp)),
+            _ => None,
+        })
+        .map(Partial::instant)
+        .map(Ok)
+}
 
-        if !RE.is_match(input) {
-            return None;
-        }
+// rfc3339
+// - 2021-05-01T01:17:02.604456Z
+// - 2017-11-25T22:34:50Z
+fn rfc3339(input: &str) -> Option<Result<Partial>> {
+    DateTime::parse_from_rfc3339(input)
+        .ok()
+        .map(Partial::instant)
+        .map(Ok)
+}
 
-        // set time to use
-        let time = match self.default_time {
-            Some(v) => v,
-            None => Utc::now().with_timezone(self.tz).time(),
-        };
+// rfc2822
+// - Wed, 02 Jun 2021 06:31:39 GMT
+fn rfc2822(input: &str) -> Option<Result<Partial>> {
+    DateTime::parse_from_rfc2822(input)
+        .ok()
+        .map(Partial::instant)
+        .map(Ok)
+}
 
-        NaiveDate::parse_from_str(input, "%Y-%m-%d")
-            .ok()
-            .map(|parsed| parsed.and_time(time))
-            .and_then(|datetime| self.tz.from_local_datetime(&datetime).single())
-            .map(|at_tz| at_tz.with_timezone(&Utc))
-            .map(Ok)
-    }
-
-    // yyyy-mm-dd z
-    // - 2021-02-21 PST
-    // - 2021-02-21 UTC
-    // - 2020-07-20+08:00 (yyyy-mm-dd-07:00)
-    fn ymd_z(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex =
-                Regex::new(r"^[0-9]{4}-[0-9]{2}-[0-9]{2}(?P<tz>\s*[+-:a-zA-Z0-9]{3,6})$").unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
+// postgres timestamp yyyy-mm-dd hh:mm:ss z
+// - 2019-11-29 08:08-08
+// - 2019-11-29 08:08:05-08
+// - 2021-05-02 23:31:36.0741-07
+// - 2021-05-02 23:31:39.12689-07
+// - 2019-11-29 08:15:47.624504-08
+// - 2017-07-19 03:21:51+00:00
+fn postgres_timestamp(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"^[0-9]{4}-[0-9]{2}-[0-9]{2}\s+[0-9]{2}:[0-9]{2}(:[0-9]{2})?(\.[0-9]{1,9})?[+-:0-9]{3,6}$",
+        )
+        .unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
 
-        if let Some(caps) = RE.captures(input) {
-            if let Some(matched_tz) = caps.name("tz") {
-                return match timezone::parse(matched_tz.as_str().trim()) {
-                    Ok(offset) => {
-                        // set time to use
-                        let time = match self.default_time {
-                            Some(v) => v,
-                            None => Utc::now().with_timezone(&offset).time(),
-                        };
-                        NaiveDate::parse_from_str(input, "%Y-%m-%d %Z")
-                            .ok()
-                            .map(|parsed| parsed.and_time(time))
-                            .and_then(|datetime| offset.from_local_datetime(&datetime).single())
-                            .map(|at_tz| at_tz.with_timezone(&Utc))
-                            .map(Ok)
-                    }
-                    Err(err) => Some(Err(err)),
-                };
-            }
-        }
-        None
-    }
-
-    // hh:mm:ss
-    // - 01:06:06
-    // - 4:00pm
-    // - 6:00 AM
-    fn hms(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex =
-                Regex::new(r"^[0-9]{1,2}:[0-9]{2}(:[0-9]{2}(\.[0-9]{1,9})?)?\s*(am|pm|AM|PM)?$")
-                    .unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
+    DateTime::parse_from_str(input, "%Y-%m-%d %H:%M:%S%#z")
+        .or_else(|_| DateTime::parse_from_str(input, "%Y-%m-%d %H:%M:%S%.f%#z"))
+        .or_else(|_| DateTime::parse_from_str(input, "%Y-%m-%d %H:%M%#z"))
+        .ok()
+        .map(Partial::instant)
+        .map(Ok)
+}
 
-        let today = self.default_day; // .with_timezone(self.tz);
-        NaiveTime::parse_from_str(input, "%H:%M:%S%.f")
-            .or_else(|_| NaiveTime::parse_from_str(input, "%H:%M"))
-            .or_else(|_| NaiveTime::parse_from_str(input, "%I:%M:%S %P"))
-            .or_else(|_| NaiveTime::parse_from_str(input, "%I:%M %P"))
-            .ok()
-            // .unwrap_or_else(|| Err(anyhow!("{} did not match any formats.", input)))
-            .map(
-                |parsed| match self.tz.from_local_datetime(&today.and_time(parsed)) {
-                    chrono::LocalResult::None => Err(anyhow!("no valid time")),
-                    chrono::LocalResult::Single(t) => Ok(t.with_timezone(&Utc)),
-                    chrono::LocalResult::Ambiguous(t1, t2) => Ok(t1.with_timezone(&Utc)),
-                },
-            )
-        // .map(Ok)
-    }
-
-    // hh:mm:ss z
-    // - 01:06:06 PST
-    // - 4:00pm PST
-    // - 6:00 AM PST
-    // - 6:00pm UTC
-    fn hms_z(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(
-                r"^[0-9]{1,2}:[0-9]{2}(:[0-9]{2})?\s*(am|pm|AM|PM)?(?P<tz>\s+[+-:a-zA-Z0-9]{3,6})$",
-            )
-            .unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
+// yyyy-mm-dd hh:mm:ss
+// - 2014-04-26 05:24:37 PM
+// - 2021-04-30 21:14
+// - 2021-04-30 21:14:10
+// - 2021-04-30 21:14:10.052282
+// - 2014-04-26 17:24:37.123
+// - 2014-04-26 17:24:37.3186369
+// - 2012-08-03 18:31:59.257000000
+fn ymd_hms(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"^[0-9]{4}-[0-9]{2}-[0-9]{2}\s+[0-9]{2}:[0-9]{2}(:[0-9]{2})?(\.[0-9]{1,9})?\s*(am|pm|AM|PM)?$",
+        )
+        .unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
 
-        if let Some(caps) = RE.captures(input) {
-            if let Some(matched_tz) = caps.name("tz") {
-                return match timezone::parse(matched_tz.as_str().trim()) {
-                    Ok(offset) => {
-                        let now = Utc::now().with_timezone(&offset);
-                        NaiveTime::parse_from_str(input, "%H:%M:%S %Z")
-                            .or_else(|_| NaiveTime::parse_from_str(input, "%H:%M %Z"))
-                            .or_else(|_| NaiveTime::parse_from_str(input, "%I:%M:%S %P %Z"))
-                            .or_else(|_| NaiveTime::parse_from_str(input, "%I:%M %P %Z"))
-                            .ok()
-                            .map(|parsed| now.date().naive_local().and_time(parsed))
-                            .and_then(|datetime| offset.from_local_datetime(&datetime).single())
-                            .map(|at_tz| at_tz.with_timezone(&Utc))
-                            .map(Ok)
-                    }
-                    Err(err) => Some(Err(err)),
-                };
-            }
-        }
-        None
+    let parse_from_str = NaiveDateTime::parse_from_str;
+    parse_from_str(input, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| parse_from_str(input, "%Y-%m-%d %H:%M"))
+        .or_else(|_| parse_from_str(input, "%Y-%m-%d %H:%M:%S%.f"))
+        .or_else(|_| parse_from_str(input, "%Y-%m-%d %I:%M:%S %P"))
+        .or_else(|_| parse_from_str(input, "%Y-%m-%d %I:%M %P"))
+        .ok()
+        .map(Partial::local)
+        .map(Ok)
+}
+
+// yyyy-mm-dd hh:mm:ss z
+// - 2017-11-25 13:31:15 PST
+// - 2017-11-25 13:31 PST
+// - 2014-12-16 06:20:00 UTC
+// - 2014-12-16 06:20:00 GMT
+// - 2014-04-26 13:13:43 +0800
+// - 2014-04-26 13:13:44 +09:00
+// - 2012-08-03 18:31:59.257000000 +0000
+// - 2015-09-30 18:48:56.35272715 UTC
+fn ymd_hms_z(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"^[0-9]{4}-[0-9]{2}-[0-9]{2}\s+[0-9]{2}:[0-9]{2}(:[0-9]{2})?(\.[0-9]{1,9})?(?P<tz>\s*[+-:a-zA-Z0-9]{3,6})$",
+        ).unwrap();
     }
 
-    // yyyy-mon-dd
-    // - 2021-Feb-21
-    fn month_ymd(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"^[0-9]{4}-[a-zA-Z]{3,9}-[0-9]{2}$").unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
+    if !RE.is_match(input) {
+        return None;
+    }
+    if let Some(caps) = RE.captures(input) {
+        if let Some(matched_tz) = caps.name("tz") {
+            let parse_from_str = NaiveDateTime::parse_from_str;
+            return match timezone::parse(matched_tz.as_str().trim()) {
+                Ok(offset) => parse_from_str(input, "%Y-%m-%d %H:%M:%S %Z")
+                    .or_else(|_| parse_from_str(input, "%Y-%m-%d %H:%M %Z"))
+                    .or_else(|_| parse_from_str(input, "%Y-%m-%d %H:%M:%S%.f %Z"))
+                    .ok()
+                    .map(|parsed| Partial::local(parsed).with_offset(offset))
+                    .map(Ok),
+                Err(err) => Some(Err(err)),
+            };
         }
+    }
+    None
+}
 
-        // set time to use
-        let time = match self.default_time {
-            Some(v) => v,
-            None => Utc::now().with_timezone(self.tz).time(),
-        };
+// yyyy-mm-dd
+// - 2021-02-21
+fn ymd(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^[0-9]{4}-[0-9]{2}-[0-9]{2}$").unwrap();
+    }
 
-        NaiveDate::parse_from_str(input, "%Y-%m-%d")
-            .or_else(|_| NaiveDate::parse_from_str(input, "%Y-%b-%d"))
-            .ok()
-            .map(|parsed| parsed.and_time(time))
-            .and_then(|datetime| self.tz.from_local_datetime(&datetime).single())
-            .map(|at_tz| at_tz.with_timezone(&Utc))
-            .map(Ok)
-    }
-
-    // Mon dd hh:mm:ss
-    // - May 6 at 9:24 PM
-    // - May 27 02:45:27
-    fn month_md_hms(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(
-                r"^[a-zA-Z]{3}\s+[0-9]{1,2}\s*(at)?\s+[0-9]{1,2}:[0-9]{2}(:[0-9]{2})?\s*(am|pm|AM|PM)?$",
-            )
-            .unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
+    if !RE.is_match(input) {
+        return None;
+    }
 
-        let now = Utc::now().with_timezone(self.tz);
-        let with_year = format!("{} {}", now.year(), input);
-        self.tz
-            .datetime_from_str(&with_year, "%Y %b %d at %I:%M %P")
-            .or_else(|_| self.tz.datetime_from_str(&with_year, "%Y %b %d %H:%M:%S"))
-            .ok()
-            .map(|parsed| parsed.with_timezone(&Utc))
-            .map(Ok)
-    }
-
-    // Mon dd, yyyy, hh:mm:ss
-    // - May 8, 2009 5:57:51 PM
-    // - September 17, 2012 10:09am
-    // - September 17, 2012, 10:10:09
-    fn month_mdy_hms(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(
-                r"^[a-zA-Z]{3,9}\.?\s+[0-9]{1,2},\s+[0-9]{2,4},?\s+[0-9]{1,2}:[0-9]{2}(:[0-9]{2})?\s*(am|pm|AM|PM)?$",
-            ).unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
+    NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .ok()
+        .map(Partial::date)
+        .map(Ok)
+}
 
-        let dt = input.replace(", ", " ").replace(". ", " ");
-        self.tz
-            .datetime_from_str(&dt, "%B %d %Y %H:%M:%S")
-            .or_else(|_| self.tz.datetime_from_str(&dt, "%B %d %Y %H:%M"))
-            .or_else(|_| self.tz.datetime_from_str(&dt, "%B %d %Y %I:%M:%S %P"))
-            .or_else(|_| self.tz.datetime_from_str(&dt, "%B %d %Y %I:%M %P"))
-            .ok()
-            .map(|at_tz| at_tz.with_timezone(&Utc))
-            .map(Ok)
-    }
-
-    // Mon dd, yyyy hh:mm:ss z
-    // - May 02, 2021 15:51:31 UTC
-    // - May 02, 2021 15:51 UTC
-    // - May 26, 2021, 12:49 AM PDT
-    // - September 17, 2012 at 10:09am PST
-    fn month_mdy_hms_z(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(
-                r"^[a-zA-Z]{3,9}\s+[0-9]{1,2},?\s+[0-9]{4}\s*,?(at)?\s+[0-9]{2}:[0-9]{2}(:[0-9]{2})?\s*(am|pm|AM|PM)?(?P<tz>\s+[+-:a-zA-Z0-9]{3,6})$",
-            ).unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
+// yyyy-mm-dd z
+// - 2021-02-21 PST
+// - 2021-02-21 UTC
+// - 2020-07-20+08:00 (yyyy-mm-dd-07:00)
+fn ymd_z(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"^[0-9]{4}-[0-9]{2}-[0-9]{2}(?P<tz>\s*[+-:a-zA-Z0-9]{3,6})$").unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
 
-        if let Some(caps) = RE.captures(input) {
-            if let Some(matched_tz) = caps.name("tz") {
-                let parse_from_str = NaiveDateTime::parse_from_str;
-                return match timezone::parse(matched_tz.as_str().trim()) {
-                    Ok(offset) => {
-                        let dt = input.replace(',', "").replace("at", "");
-                        parse_from_str(&dt, "%B %d %Y %H:%M:%S %Z")
-                            .or_else(|_| parse_from_str(&dt, "%B %d %Y %H:%M %Z"))
-                            .or_else(|_| parse_from_str(&dt, "%B %d %Y %I:%M:%S %P %Z"))
-                            .or_else(|_| parse_from_str(&dt, "%B %d %Y %I:%M %P %Z"))
-                            .ok()
-                            .and_then(|parsed| offset.from_local_datetime(&parsed).single())
-                            .map(|datetime| datetime.with_timezone(&Utc))
-                            .map(Ok)
-                    }
-                    Err(err) => Some(Err(err)),
-                };
-            }
-        }
-        None
-    }
-
-    // Mon dd, yyyy
-    // - May 25, 2021
-    // - oct 7, 1970
-    // - oct 7, 70
-    // - oct. 7, 1970
-    // - oct. 7, 70
-    // - October 7, 1970
-    fn month_mdy(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex =
-                Regex::new(r"^[a-zA-Z]{3,9}\.?\s+[0-9]{1,2},\s+[0-9]{2,4}$").unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
+    if let Some(caps) = RE.captures(input) {
+        if let Some(matched_tz) = caps.name("tz") {
+            return match timezone::parse(matched_tz.as_str().trim()) {
+                Ok(offset) => NaiveDate::parse_from_str(input, "%Y-%m-%d %Z")
+                    .ok()
+                    .map(|parsed| Partial::date(parsed).with_offset(offset))
+                    .map(Ok),
+                Err(err) => Some(Err(err)),
+            };
         }
+    }
+    None
+}
 
-        // set time to use
-        let time = match self.default_time {
-            Some(v) => v,
-            None => Utc::now().with_timezone(self.tz).time(),
-        };
+// hh:mm:ss
+// - 01:06:06
+// - 4:00pm
+// - 6:00 AM
+fn hms(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"^[0-9]{1,2}:[0-9]{2}(:[0-9]{2}(\.[0-9]{1,9})?)?\s*(am|pm|AM|PM)?$")
+                .unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
 
-        let dt = input.replace(", ", " ").replace(". ", " ");
-        NaiveDate::parse_from_str(&dt, "%B %d %y")
-            .or_else(|_| NaiveDate::parse_from_str(&dt, "%B %d %Y"))
-            .ok()
-            .map(|parsed| parsed.and_time(time))
-            .and_then(|datetime| self.tz.from_local_datetime(&datetime).single())
-            .map(|at_tz| at_tz.with_timezone(&Utc))
-            .map(Ok)
-    }
-
-    // dd Mon yyyy hh:mm:ss
-    // - 12 Feb 2006, 19:17
-    // - 12 Feb 2006 19:17
-    // - 14 May 2019 19:11:40.164
-    fn month_dmy_hms(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(
-                r"^[0-9]{1,2}\s+[a-zA-Z]{3,9}\s+[0-9]{2,4},?\s+[0-9]{1,2}:[0-9]{2}(:[0-9]{2})?(\.[0-9]{1,9})?$",
-            ).unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
+    NaiveTime::parse_from_str(input, "%H:%M:%S%.f")
+        .or_else(|_| NaiveTime::parse_from_str(input, "%H:%M"))
+        .or_else(|_| NaiveTime::parse_from_str(input, "%I:%M:%S %P"))
+        .or_else(|_| NaiveTime::parse_from_str(input, "%I:%M %P"))
+        .ok()
+        .map(Partial::time)
+        .map(Ok)
+}
 
-        let dt = input.replace(", ", " ");
-        self.tz
-            .datetime_from_str(&dt, "%d %B %Y %H:%M:%S")
-            .or_else(|_| self.tz.datetime_from_str(&dt, "%d %B %Y %H:%M"))
-            .or_else(|_| self.tz.datetime_from_str(&dt, "%d %B %Y %H:%M:%S%.f"))
-            .or_else(|_| self.tz.datetime_from_str(&dt, "%d %B %Y %I:%M:%S %P"))
-            .or_else(|_| self.tz.datetime_from_str(&dt, "%d %B %Y %I:%M %P"))
-            .ok()
-            .map(|at_tz| at_tz.with_timezone(&Utc))
-            .map(Ok)
-    }
-
-    // dd Mon yyyy
-    // - 7 oct 70
-    // - 7 oct 1970
-    // - 03 February 2013
-    // - 1 July 2013
-    fn month_dmy(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex =
-                Regex::new(r"^[0-9]{1,2}\s+[a-zA-Z]{3,9}\s+[0-9]{2,4}$").unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
+// hh:mm:ss z
+// - 01:06:06 PST
+// - 4:00pm PST
+// - 6:00 AM PST
+// - 6:00pm UTC
+fn hms_z(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"^[0-9]{1,2}:[0-9]{2}(:[0-9]{2})?\s*(am|pm|AM|PM)?(?P<tz>\s+[+-:a-zA-Z0-9]{3,6})$",
+        )
+        .unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
+
+    if let Some(caps) = RE.captures(input) {
+        if let Some(matched_tz) = caps.name("tz") {
+            return match timezone::parse(matched_tz.as_str().trim()) {
+                Ok(offset) => NaiveTime::parse_from_str(input, "%H:%M:%S %Z")
+                    .or_else(|_| NaiveTime::parse_from_str(input, "%H:%M %Z"))
+                    .or_else(|_| NaiveTime::parse_from_str(input, "%I:%M:%S %P %Z"))
+                    .or_else(|_| NaiveTime::parse_from_str(input, "%I:%M %P %Z"))
+                    .ok()
+                    .map(|parsed| Partial::time(parsed).with_offset(offset))
+                    .map(Ok),
+                Err(err) => Some(Err(err)),
+            };
         }
+    }
+    None
+}
 
-        // set time to use
-        let time = match self.default_time {
-            Some(v) => v,
-            None => Utc::now().with_timezone(self.tz).time(),
-        };
+// yyyy-mon-dd
+// - 2021-Feb-21
+fn month_ymd(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^[0-9]{4}-[a-zA-Z]{3,9}-[0-9]{2}$").unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
 
-        NaiveDate::parse_from_str(input, "%d %B %y")
-            .or_else(|_| NaiveDate::parse_from_str(input, "%d %B %Y"))
-            .ok()
-            .map(|parsed| parsed.and_time(time))
-            .and_then(|datetime| self.tz.from_local_datetime(&datetime).single())
-            .map(|at_tz| at_tz.with_timezone(&Utc))
-            .map(Ok)
-    }
-
-    // mm/dd/yyyy hh:mm:ss
-    // - 4/8/2014 22:05
-    // - 04/08/2014 22:05
-    // - 4/8/14 22:05
-    // - 04/2/2014 03:00:51
-    // - 8/8/1965 12:00:00 AM
-    // - 8/8/1965 01:00:01 PM
-    // - 8/8/1965 01:00 PM
-    // - 8/8/1965 1:00 PM
-    // - 8/8/1965 12:00 AM
-    // - 4/02/2014 03:00:51
-    // - 03/19/2012 10:11:59
-    // - 03/19/2012 10:11:59.3186369
-    fn slash_mdy_hms(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(
-                r"^[0-9]{1,2}/[0-9]{1,2}/[0-9]{2,4}\s+[0-9]{1,2}:[0-9]{2}(:[0-9]{2})?(\.[0-9]{1,9})?\s*(am|pm|AM|PM)?$"
-            )
-            .unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
+    NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(input, "%Y-%b-%d"))
+        .ok()
+        .map(Partial::date)
+        .map(Ok)
+}
 
-        self.tz
-            .datetime_from_str(input, "%m/%d/%y %H:%M:%S")
-            .or_else(|_| self.tz.datetime_from_str(input, "%m/%d/%y %H:%M"))
-            .or_else(|_| self.tz.datetime_from_str(input, "%m/%d/%y %H:%M:%S%.f"))
-            .or_else(|_| self.tz.datetime_from_str(input, "%m/%d/%y %I:%M:%S %P"))
-            .or_else(|_| self.tz.datetime_from_str(input, "%m/%d/%y %I:%M %P"))
-            .or_else(|_| self.tz.datetime_from_str(input, "%m/%d/%Y %H:%M:%S"))
-            .or_else(|_| self.tz.datetime_from_str(input, "%m/%d/%Y %H:%M"))
-            .or_else(|_| self.tz.datetime_from_str(input, "%m/%d/%Y %H:%M:%S%.f"))
-            .or_else(|_| self.tz.datetime_from_str(input, "%m/%d/%Y %I:%M:%S %P"))
-            .or_else(|_| self.tz.datetime_from_str(input, "%m/%d/%Y %I:%M %P"))
-            .ok()
-            .map(|at_tz| at_tz.with_timezone(&Utc))
-            .map(Ok)
-    }
-
-    // mm/dd/yyyy
-    // - 3/31/2014
-    // - 03/31/2014
-    // - 08/21/71
-    // - 8/1/71
-    fn slash_mdy(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"^[0-9]{1,2}/[0-9]{1,2}/[0-9]{2,4}$").unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
+// Mon dd hh:mm:ss
+// - May 6 at 9:24 PM
+// - May 27 02:45:27
+fn month_md_hms(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"^[a-zA-Z]{3}\s+[0-9]{1,2}\s*(at)?\s+[0-9]{1,2}:[0-9]{2}(:[0-9]{2})?\s*(am|pm|AM|PM)?$",
+        )
+        .unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
 
-        // set time to use
-        let time = match self.default_time {
-            Some(v) => v,
-            None => Utc::now().with_timezone(self.tz).time(),
-        };
+    without_year(input, "%b %d at %I:%M %P")
+        .or_else(|| without_year(input, "%b %d %H:%M:%S"))
+        .map(Ok)
+}
 
-        NaiveDate::parse_from_str(input, "%m/%d/%y")
-            .or_else(|_| NaiveDate::parse_from_str(input, "%m/%d/%Y"))
-            .ok()
-            .map(|parsed| parsed.and_time(time))
-            .and_then(|datetime| self.tz.from_local_datetime(&datetime).single())
-            .map(|at_tz| at_tz.with_timezone(&Utc))
-            .map(Ok)
-    }
-
-    // yyyy/mm/dd hh:mm:ss
-    // - 2014/4/8 22:05
-    // - 2014/04/08 22:05
-    // - 2014/04/2 03:00:51
-    // - 2014/4/02 03:00:51
-    // - 2012/03/19 10:11:59
-    // - 2012/03/19 10:11:59.3186369
-    fn slash_ymd_hms(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(
-                r"^[0-9]{4}/[0-9]{1,2}/[0-9]{1,2}\s+[0-9]{1,2}:[0-9]{2}(:[0-9]{2})?(\.[0-9]{1,9})?\s*(am|pm|AM|PM)?$"
-            )
-            .unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
+// Mon dd, yyyy, hh:mm:ss
+// - May 8, 2009 5:57:51 PM
+// - September 17, 2012 10:09am
+// - September 17, 2012, 10:10:09
+fn month_mdy_hms(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"^[a-zA-Z]{3,9}\.?\s+[0-9]{1,2},\s+[0-9]{2,4},?\s+[0-9]{1,2}:[0-9]{2}(:[0-9]{2})?\s*(am|pm|AM|PM)?$",
+        ).unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
 
-        self.tz
-            .datetime_from_str(input, "%Y/%m/%d %H:%M:%S")
-            .or_else(|_| self.tz.datetime_from_str(input, "%Y/%m/%d %H:%M"))
-            .or_else(|_| self.tz.datetime_from_str(input, "%Y/%m/%d %H:%M:%S%.f"))
-            .or_else(|_| self.tz.datetime_from_str(input, "%Y/%m/%d %I:%M:%S %P"))
-            .or_else(|_| self.tz.datetime_from_str(input, "%Y/%m/%d %I:%M %P"))
-            .ok()
-            .map(|at_tz| at_tz.with_timezone(&Utc))
-            .map(Ok)
-    }
-
-    // yyyy/mm/dd
-    // - 2014/3/31
-    // - 2014/03/31
-    fn slash_ymd(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"^[0-9]{4}/[0-9]{1,2}/[0-9]{1,2}$").unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
+    let dt = input.replace(", ", " ").replace(". ", " ");
+    let parse_from_str = NaiveDateTime::parse_from_str;
+    parse_from_str(&dt, "%B %d %Y %H:%M:%S")
+        .or_else(|_| parse_from_str(&dt, "%B %d %Y %H:%M"))
+        .or_else(|_| parse_from_str(&dt, "%B %d %Y %I:%M:%S %P"))
+        .or_else(|_| parse_from_str(&dt, "%B %d %Y %I:%M %P"))
+        .ok()
+        .map(Partial::local)
+        .map(Ok)
+}
 
-        // set time to use
-        let time = match self.default_time {
-            Some(v) => v,
-            None => Utc::now().with_timezone(self.tz).time(),
-        };
+// Mon dd, yyyy hh:mm:ss z
+// - May 02, 2021 15:51:31 UTC
+// - May 02, 2021 15:51 UTC
+// - May 26, 2021, 12:49 AM PDT
+// - September 17, 2012 at 10:09am PST
+fn month_mdy_hms_z(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"^[a-zA-Z]{3,9}\s+[0-9]{1,2},?\s+[0-9]{4}\s*,?(at)?\s+[0-9]{2}:[0-9]{2}(:[0-9]{2})?\s*(am|pm|AM|PM)?(?P<tz>\s+[+-:a-zA-Z0-9]{3,6})$",
+        ).unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
 
-        NaiveDate::parse_from_str(input, "%Y/%m/%d")
-            .ok()
-            .map(|parsed| parsed.and_time(time))
-            .and_then(|datetime| self.tz.from_local_datetime(&datetime).single())
-            .map(|at_tz| at_tz.with_timezone(&Utc))
-            .map(Ok)
-    }
-
-    // mm.dd.yyyy
-    // - 3.31.2014
-    // - 03.31.2014
-    // - 08.21.71
-    // yyyy.mm.dd
-    // - 2014.03.30
-    // - 2014.03
-    fn dot_mdy_or_ymd(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"[0-9]{1,4}.[0-9]{1,4}[0-9]{1,4}").unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
+    if let Some(caps) = RE.captures(input) {
+        if let Some(matched_tz) = caps.name("tz") {
+            let parse_from_str = NaiveDateTime::parse_from_str;
+            return match timezone::parse(matched_tz.as_str().trim()) {
+                Ok(offset) => {
+                    let dt = input.replace(',', "").replace("at", "");
+                    parse_from_str(&dt, "%B %d %Y %H:%M:%S %Z")
+                        .or_else(|_| parse_from_str(&dt, "%B %d %Y %H:%M %Z"))
+                        .or_else(|_| parse_from_str(&dt, "%B %d %Y %I:%M:%S %P %Z"))
+                        .or_else(|_| parse_from_str(&dt, "%B %d %Y %I:%M %P %Z"))
+                        .ok()
+                        .map(|parsed| Partial::local(parsed).with_offset(offset))
+                        .map(Ok)
+                }
+                Err(err) => Some(Err(err)),
+            };
         }
+    }
+    None
+}
 
-        // set time to use
-        let time = match self.default_time {
-            Some(v) => v,
-            None => Utc::now().with_timezone(self.tz).time(),
-        };
+// Mon dd, yyyy
+// - May 25, 2021
+// - oct 7, 1970
+// - oct 7, 70
+// - oct. 7, 1970
+// - oct. 7, 70
+// - October 7, 1970
+fn month_mdy(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"^[a-zA-Z]{3,9}\.?\s+[0-9]{1,2},\s+[0-9]{2,4}$").unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
 
-        NaiveDate::parse_from_str(input, "%m.%d.%y")
-            .or_else(|_| NaiveDate::parse_from_str(input, "%m.%d.%Y"))
-            .or_else(|_| NaiveDate::parse_from_str(input, "%Y.%m.%d"))
-            .or_else(|_| {
-                NaiveDate::parse_from_str(&format!("{}.{}", input, Utc::now().day()), "%Y.%m.%d")
-            })
-            .ok()
-            .map(|parsed| parsed.and_time(time))
-            .and_then(|datetime| self.tz.from_local_datetime(&datetime).single())
-            .map(|at_tz| at_tz.with_timezone(&Utc))
-            .map(Ok)
-    }
-
-    // yymmdd hh:mm:ss mysql log
-    // - 171113 14:14:20
-    fn mysql_log_timestamp(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"[0-9]{6}\s+[0-9]{2}:[0-9]{2}:[0-9]{2}").unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
+    let dt = input.replace(", ", " ").replace(". ", " ");
+    NaiveDate::parse_from_str(&dt, "%B %d %y")
+        .or_else(|_| NaiveDate::parse_from_str(&dt, "%B %d %Y"))
+        .ok()
+        .map(Partial::date)
+        .map(Ok)
+}
 
-        self.tz
-            .datetime_from_str(input, "%y%m%d %H:%M:%S")
-            .ok()
-            .map(|at_tz| at_tz.with_timezone(&Utc))
-            .map(Ok)
+// dd Mon yyyy hh:mm:ss
+// - 12 Feb 2006, 19:17
+// - 12 Feb 2006 19:17
+// - 14 May 2019 19:11:40.164
+fn month_dmy_hms(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"^[0-9]{1,2}\s+[a-zA-Z]{3,9}\s+[0-9]{2,4},?\s+[0-9]{1,2}:[0-9]{2}(:[0-9]{2})?(\.[0-9]{1,9})?$",
+        ).unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
     }
 
-    // chinese yyyy mm dd hh mm ss
-    // - 2014年04月08日11时25分18秒
-    fn chinese_ymd_hms(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex =
-                Regex::new(r"^[0-9]{4}年[0-9]{2}月[0-9]{2}日[0-9]{2}时[0-9]{2}分[0-9]{2}秒$")
-                    .unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
+    let dt = input.replace(", ", " ");
+    let parse_from_str = NaiveDateTime::parse_from_str;
+    parse_from_str(&dt, "%d %B %Y %H:%M:%S")
+        .or_else(|_| parse_from_str(&dt, "%d %B %Y %H:%M"))
+        .or_else(|_| parse_from_str(&dt, "%d %B %Y %H:%M:%S%.f"))
+        .or_else(|_| parse_from_str(&dt, "%d %B %Y %I:%M:%S %P"))
+        .or_else(|_| parse_from_str(&dt, "%d %B %Y %I:%M %P"))
+        .ok()
+        .map(Partial::local)
+        .map(Ok)
+}
 
-        self.tz
-            .datetime_from_str(input, "%Y年%m月%d日%H时%M分%S秒")
-            .ok()
-            .map(|at_tz| at_tz.with_timezone(&Utc))
-            .map(Ok)
+// dd Mon yyyy
+// - 7 oct 70
+// - 7 oct 1970
+// - 03 February 2013
+// - 1 July 2013
+fn month_dmy(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^[0-9]{1,2}\s+[a-zA-Z]{3,9}\s+[0-9]{2,4}$").unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
     }
 
-    // chinese yyyy mm dd
-    // - 2014年04月08日
-    fn chinese_ymd(&self, input: &str) -> Option<Result<DateTime<Utc>>> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"^[0-9]{4}年[0-9]{2}月[0-9]{2}日$").unwrap();
-        }
-        if !RE.is_match(input) {
-            return None;
-        }
+    NaiveDate::parse_from_str(input, "%d %B %y")
+        .or_else(|_| NaiveDate::parse_from_str(input, "%d %B %Y"))
+        .ok()
+        .map(Partial::date)
+        .map(Ok)
+}
 
-        // set time to use
-        let time = match self.default_time {
-            Some(v) => v,
-            None => Utc::now().with_timezone(self.tz).time(),
-        };
+// mm/dd/yyyy hh:mm:ss
+// - 4/8/2014 22:05
+// - 04/08/2014 22:05
+// - 4/8/14 22:05
+// - 04/2/2014 03:00:51
+// - 8/8/1965 12:00:00 AM
+// - 8/8/1965 01:00:01 PM
+// - 8/8/1965 01:00 PM
+// - 8/8/1965 1:00 PM
+// - 8/8/1965 12:00 AM
+// - 4/02/2014 03:00:51
+// - 03/19/2012 10:11:59
+// - 03/19/2012 10:11:59.3186369
+fn slash_mdy_hms(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"^[0-9]{1,2}/[0-9]{1,2}/[0-9]{2,4}\s+[0-9]{1,2}:[0-9]{2}(:[0-9]{2})?(\.[0-9]{1,9})?\s*(am|pm|AM|PM)?$"
+        )
+        .unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
+
+    let parse_from_str = NaiveDateTime::parse_from_str;
+    parse_from_str(input, "%m/%d/%y %H:%M:%S")
+        .or_else(|_| parse_from_str(input, "%m/%d/%y %H:%M"))
+        .or_else(|_| parse_from_str(input, "%m/%d/%y %H:%M:%S%.f"))
+        .or_else(|_| parse_from_str(input, "%m/%d/%y %I:%M:%S %P"))
+        .or_else(|_| parse_from_str(input, "%m/%d/%y %I:%M %P"))
+        .or_else(|_| parse_from_str(input, "%m/%d/%Y %H:%M:%S"))
+        .or_else(|_| parse_from_str(input, "%m/%d/%Y %H:%M"))
+        .or_else(|_| parse_from_str(input, "%m/%d/%Y %H:%M:%S%.f"))
+        .or_else(|_| parse_from_str(input, "%m/%d/%Y %I:%M:%S %P"))
+        .or_else(|_| parse_from_str(input, "%m/%d/%Y %I:%M %P"))
+        .ok()
+        .map(Partial::local)
+        .map(Ok)
+}
+
+// mm/dd/yyyy
+// - 3/31/2014
+// - 03/31/2014
+// - 08/21/71
+// - 8/1/71
+fn slash_mdy(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^[0-9]{1,2}/[0-9]{1,2}/[0-9]{2,4}$").unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
+
+    NaiveDate::parse_from_str(input, "%m/%d/%y")
+        .or_else(|_| NaiveDate::parse_from_str(input, "%m/%d/%Y"))
+        .ok()
+        .map(Partial::date)
+        .map(Ok)
+}
+
+// yyyy/mm/dd hh:mm:ss
+// - 2014/4/8 22:05
+// - 2014/04/08 22:05
+// - 2014/04/2 03:00:51
+// - 2014/4/02 03:00:51
+// - 2012/03/19 10:11:59
+// - 2012/03/19 10:11:59.3186369
+fn slash_ymd_hms(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"^[0-9]{4}/[0-9]{1,2}/[0-9]{1,2}\s+[0-9]{1,2}:[0-9]{2}(:[0-9]{2})?(\.[0-9]{1,9})?\s*(am|pm|AM|PM)?$"
+        )
+        .unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
+
+    let parse_from_str = NaiveDateTime::parse_from_str;
+    parse_from_str(input, "%Y/%m/%d %H:%M:%S")
+        .or_else(|_| parse_from_str(input, "%Y/%m/%d %H:%M"))
+        .or_else(|_| parse_from_str(input, "%Y/%m/%d %H:%M:%S%.f"))
+        .or_else(|_| parse_from_str(input, "%Y/%m/%d %I:%M:%S %P"))
+        .or_else(|_| parse_from_str(input, "%Y/%m/%d %I:%M %P"))
+        .ok()
+        .map(Partial::local)
+        .map(Ok)
+}
+
+// yyyy/mm/dd
+// - 2014/3/31
+// - 2014/03/31
+fn slash_ymd(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^[0-9]{4}/[0-9]{1,2}/[0-9]{1,2}$").unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
+
+    NaiveDate::parse_from_str(input, "%Y/%m/%d")
+        .ok()
+        .map(Partial::date)
+        .map(Ok)
+}
+
+// mm.dd.yyyy
+// - 3.31.2014
+// - 03.31.2014
+// - 08.21.71
+// yyyy.mm.dd
+// - 2014.03.30
+// - 2014.03
+fn dot_mdy_or_ymd(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"[0-9]{1,4}.[0-9]{1,4}[0-9]{1,4}").unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
+
+    NaiveDate::parse_from_str(input, "%m.%d.%y")
+        .or_else(|_| NaiveDate::parse_from_str(input, "%m.%d.%Y"))
+        .or_else(|_| NaiveDate::parse_from_str(input, "%Y.%m.%d"))
+        .ok()
+        .map(Partial::date)
+        .or_else(|| {
+            // Any day will do to check the year and month; the caller fills in the actual one.
+            NaiveDate::parse_from_str(&format!("{}.1", input), "%Y.%m.%d")
+                .ok()
+                .map(|parsed| Partial {
+                    date: Some(PartialDate::YearMonth {
+                        year: parsed.year(),
+                        month: parsed.month(),
+                    }),
+                    time: None,
+                    offset: None,
+                })
+        })
+        .map(Ok)
+}
+
+// yymmdd hh:mm:ss mysql log
+// - 171113 14:14:20
+fn mysql_log_timestamp(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"[0-9]{6}\s+[0-9]{2}:[0-9]{2}:[0-9]{2}").unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
+
+    NaiveDateTime::parse_from_str(input, "%y%m%d %H:%M:%S")
+        .ok()
+        .map(Partial::local)
+        .map(Ok)
+}
+
+// chinese yyyy mm dd hh mm ss
+// - 2014年04月08日11时25分18秒
+fn chinese_ymd_hms(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"^[0-9]{4}年[0-9]{2}月[0-9]{2}日[0-9]{2}时[0-9]{2}分[0-9]{2}秒$").unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
 
-        NaiveDate::parse_from_str(input, "%Y年%m月%d日")
-            .ok()
-            .map(|parsed| parsed.and_time(time))
-            .and_then(|datetime| self.tz.from_local_datetime(&datetime).single())
-            .map(|at_tz| at_tz.with_timezone(&Utc))
-            .map(Ok)
+    NaiveDateTime::parse_from_str(input, "%Y年%m月%d日%H时%M分%S秒")
+        .ok()
+        .map(Partial::local)
+        .map(Ok)
+}
+
+// chinese yyyy mm dd
+// - 2014年04月08日
+fn chinese_ymd(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^[0-9]{4}年[0-9]{2}月[0-9]{2}日$").unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
+
+    NaiveDate::parse_from_str(input, "%Y年%m月%d日")
+        .ok()
+        .map(Partial::date)
+        .map(Ok)
+}
+
+// bsd syslog, and journalctl's short-precise
+// - Oct 11 22:14:15
+// - Mar  2 08:51:06.123456
+fn syslog(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"^[a-zA-Z]{3}\s+[0-9]{1,2}\s+[0-9]{2}:[0-9]{2}:[0-9]{2}(\.[0-9]{1,9})?$")
+                .unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
     }
+
+    let input = input.split_whitespace().collect::<Vec<_>>().join(" ");
+    without_year(&input, "%b %d %H:%M:%S%.f").map(Ok)
+}
+
+// apache/nginx common log format
+// - 10/Oct/2000:13:55:36 -0700
+// - 10/Oct/2000:13:55:36
+fn clf(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"^[0-9]{2}/[a-zA-Z]{3}/[0-9]{4}:[0-9]{2}:[0-9]{2}:[0-9]{2}(\s+[+-][0-9]{4})?$"
+        )
+        .unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
+
+    DateTime::parse_from_str(input, "%d/%b/%Y:%H:%M:%S %z")
+        .map(Partial::instant)
+        .or_else(|_| NaiveDateTime::parse_from_str(input, "%d/%b/%Y:%H:%M:%S").map(Partial::local))
+        .ok()
+        .map(Ok)
+}
+
+// klog/glog: severity, then mmdd
+// - I0322 08:51:06.123456
+// - E1011 22:14:15
+fn klog(input: &str) -> Option<Result<Partial>> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"^[IWEF][0-9]{4}\s+[0-9]{2}:[0-9]{2}:[0-9]{2}(\.[0-9]{1,9})?$").unwrap();
+    }
+    if !RE.is_match(input) {
+        return None;
+    }
+
+    let input = input[1..].split_whitespace().collect::<Vec<_>>().join(" ");
+    without_year(&input, "%m%d %H:%M:%S%.f").map(Ok)
+}
+
+/// Parses `input`, which has no year, as if in a leap year so that the 29th of February gets through.
+fn without_year(input: &str, format: &str) -> Option<Partial> {
+    NaiveDateTime::parse_from_str(&format!("2000 {input}"), &format!("%Y {format}"))
+        .ok()
+        .map(|parsed| Partial {
+            date: Some(PartialDate::MonthDay {
+                month: parsed.month(),
+                day: parsed.day(),
+            }),
+            time: Some(parsed.time()),
+            offset: None,
+        })
 }
 
-/*
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const DAY: NaiveDate = NaiveDate::from_ymd_opt(2022, 3, 22).unwrap();
+    const TIME: NaiveTime = NaiveTime::from_hms_opt(8, 51, 6).unwrap();
+
+    /// Fills in what a parser left out with a fixed day and time, instead of today and now.
+    fn at(parsed: Option<Result<Partial>>) -> DateTime<Utc> {
+        Parse::new(&Utc, Some(TIME), Some(DAY))
+            .resolve(parsed.unwrap().unwrap())
+            .unwrap()
+    }
+
     #[test]
     fn unix_timestamp() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![
+        let test_cases = [
             ("0000000000", Utc.ymd(1970, 1, 1).and_hms(0, 0, 0)),
             ("0000000000000", Utc.ymd(1970, 1, 1).and_hms(0, 0, 0)),
             ("0000000000000000000", Utc.ymd(1970, 1, 1).and_hms(0, 0, 0)),
@@ -835,28 +1073,28 @@ mod tests {
                 "1620024872717915000",
                 Utc.ymd(2021, 5, 3).and_hms_nano(6, 54, 32, 717915000),
             ),
+            (
+                "1620021848.4290",
+                Utc.ymd(2021, 5, 3).and_hms_milli(6, 4, 8, 429),
+            ),
         ];
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(
-                parse.unix_timestamp(input).unwrap().unwrap(),
+                at(super::unix_timestamp(input)),
                 want,
                 "unix_timestamp/{}",
                 input
             )
         }
-        assert!(parse.unix_timestamp("15116").is_none());
-        assert!(parse
-            .unix_timestamp("16200248727179150001620024872717915000")
-            .is_none());
-        assert!(parse.unix_timestamp("not-a-ts").is_none());
+        assert!(super::unix_timestamp("15116").is_none());
+        assert!(super::unix_timestamp("16200248727179150001620024872717915000").is_none());
+        assert!(super::unix_timestamp("not-a-ts").is_none());
     }
 
     #[test]
     fn rfc3339() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![
+        let test_cases = [
             (
                 "2021-05-01T01:17:02.604456Z",
                 Utc.ymd(2021, 5, 1).and_hms_nano(1, 17, 2, 604456000),
@@ -868,22 +1106,15 @@ mod tests {
         ];
 
         for &(input, want) in test_cases.iter() {
-            assert_eq!(
-                parse.rfc3339(input).unwrap().unwrap(),
-                want,
-                "rfc3339/{}",
-                input
-            )
+            assert_eq!(at(super::rfc3339(input)), want, "rfc3339/{}", input)
         }
-        assert!(parse.rfc3339("2017-11-25 22:34:50").is_none());
-        assert!(parse.rfc3339("not-date-time").is_none());
+        assert!(super::rfc3339("2017-11-25 22:34:50").is_none());
+        assert!(super::rfc3339("not-date-time").is_none());
     }
 
     #[test]
     fn rfc2822() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![
+        let test_cases = [
             (
                 "Wed, 02 Jun 2021 06:31:39 GMT",
                 Utc.ymd(2021, 6, 2).and_hms(6, 31, 39),
@@ -895,22 +1126,15 @@ mod tests {
         ];
 
         for &(input, want) in test_cases.iter() {
-            assert_eq!(
-                parse.rfc2822(input).unwrap().unwrap(),
-                want,
-                "rfc2822/{}",
-                input
-            )
+            assert_eq!(at(super::rfc2822(input)), want, "rfc2822/{}", input)
         }
-        assert!(parse.rfc2822("02 Jun 2021 06:31:39").is_none());
-        assert!(parse.rfc2822("not-date-time").is_none());
+        assert!(super::rfc2822("02 Jun 2021 06:31:39").is_none());
+        assert!(super::rfc2822("not-date-time").is_none());
     }
 
     #[test]
     fn postgres_timestamp() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![
+        let test_cases = [
             (
                 "2019-11-29 08:08-08",
                 Utc.ymd(2019, 11, 29).and_hms(16, 8, 0),
@@ -939,20 +1163,18 @@ mod tests {
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(
-                parse.postgres_timestamp(input).unwrap().unwrap(),
+                at(super::postgres_timestamp(input)),
                 want,
                 "postgres_timestamp/{}",
                 input
             )
         }
-        assert!(parse.postgres_timestamp("not-date-time").is_none());
+        assert!(super::postgres_timestamp("not-date-time").is_none());
     }
 
     #[test]
     fn ymd_hms() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![
+        let test_cases = [
             ("2021-04-30 21:14", Utc.ymd(2021, 4, 30).and_hms(21, 14, 0)),
             (
                 "2021-04-30 21:14:10",
@@ -981,21 +1203,14 @@ mod tests {
         ];
 
         for &(input, want) in test_cases.iter() {
-            assert_eq!(
-                parse.ymd_hms(input).unwrap().unwrap(),
-                want,
-                "ymd_hms/{}",
-                input
-            )
+            assert_eq!(at(super::ymd_hms(input)), want, "ymd_hms/{}", input)
         }
-        assert!(parse.ymd_hms("not-date-time").is_none());
+        assert!(super::ymd_hms("not-date-time").is_none());
     }
 
     #[test]
     fn ymd_hms_z() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![
+        let test_cases = [
             (
                 "2017-11-25 13:31:15 PST",
                 Utc.ymd(2017, 11, 25).and_hms(21, 31, 15),
@@ -1031,223 +1246,155 @@ mod tests {
         ];
 
         for &(input, want) in test_cases.iter() {
-            assert_eq!(
-                parse.ymd_hms_z(input).unwrap().unwrap(),
-                want,
-                "ymd_hms_z/{}",
-                input
-            )
+            assert_eq!(at(super::ymd_hms_z(input)), want, "ymd_hms_z/{}", input)
         }
-        assert!(parse.ymd_hms_z("not-date-time").is_none());
+        assert!(super::ymd_hms_z("not-date-time").is_none());
     }
 
     #[test]
     fn ymd() {
-        let parse = Parse::new(&Utc, Some(Utc::now().time()));
-
-        let test_cases = vec![(
-            "2021-02-21",
-            Utc.ymd(2021, 2, 21).and_time(Utc::now().time()),
-        )];
+        let test_cases = [("2021-02-21", Utc.ymd(2021, 2, 21).and_time(TIME))];
 
         for &(input, want) in test_cases.iter() {
-            assert_eq!(
-                parse
-                    .ymd(input)
-                    .unwrap()
-                    .unwrap()
-                    .trunc_subsecs(0)
-                    .with_second(0)
-                    .unwrap(),
-                want.unwrap().trunc_subsecs(0).with_second(0).unwrap(),
-                "ymd/{}",
-                input
-            )
+            assert_eq!(at(super::ymd(input)), want.unwrap(), "ymd/{}", input)
         }
-        assert!(parse.ymd("not-date-time").is_none());
+        assert!(super::ymd("not-date-time").is_none());
     }
 
     #[test]
     fn ymd_z() {
-        let parse = Parse::new(&Utc, None);
-        let now_at_pst = Utc::now().with_timezone(&FixedOffset::west(8 * 3600));
-        let now_at_cst = Utc::now().with_timezone(&FixedOffset::east(8 * 3600));
-
-        let test_cases = vec![
+        let test_cases = [
             (
                 "2021-02-21 PST",
                 FixedOffset::west(8 * 3600)
                     .ymd(2021, 2, 21)
-                    .and_time(now_at_pst.time())
+                    .and_time(TIME)
                     .map(|dt| dt.with_timezone(&Utc)),
             ),
             (
                 "2021-02-21 UTC",
                 FixedOffset::west(0)
                     .ymd(2021, 2, 21)
-                    .and_time(Utc::now().time())
+                    .and_time(TIME)
                     .map(|dt| dt.with_timezone(&Utc)),
             ),
             (
                 "2020-07-20+08:00",
                 FixedOffset::east(8 * 3600)
                     .ymd(2020, 7, 20)
-                    .and_time(now_at_cst.time())
+                    .and_time(TIME)
                     .map(|dt| dt.with_timezone(&Utc)),
             ),
         ];
 
         for &(input, want) in test_cases.iter() {
-            assert_eq!(
-                parse
-                    .ymd_z(input)
-                    .unwrap()
-                    .unwrap()
-                    .trunc_subsecs(0)
-                    .with_second(0)
-                    .unwrap(),
-                want.unwrap().trunc_subsecs(0).with_second(0).unwrap(),
-                "ymd_z/{}",
-                input
-            )
+            assert_eq!(at(super::ymd_z(input)), want.unwrap(), "ymd_z/{}", input)
         }
-        assert!(parse.ymd_z("not-date-time").is_none());
+        assert!(super::ymd_z("not-date-time").is_none());
     }
 
     #[test]
     fn hms() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![
+        let test_cases = [
             (
                 "01:06:06",
-                Utc::now().date().and_time(NaiveTime::from_hms(1, 6, 6)),
+                Utc.from_utc_date(&DAY)
+                    .and_time(NaiveTime::from_hms(1, 6, 6)),
             ),
             (
                 "4:00pm",
-                Utc::now().date().and_time(NaiveTime::from_hms(16, 0, 0)),
+                Utc.from_utc_date(&DAY)
+                    .and_time(NaiveTime::from_hms(16, 0, 0)),
             ),
             (
                 "6:00 AM",
-                Utc::now().date().and_time(NaiveTime::from_hms(6, 0, 0)),
+                Utc.from_utc_date(&DAY)
+                    .and_time(NaiveTime::from_hms(6, 0, 0)),
             ),
         ];
 
         for &(input, want) in test_cases.iter() {
-            assert_eq!(
-                parse.hms(input).unwrap().unwrap(),
-                want.unwrap(),
-                "hms/{}",
-                input
-            )
+            assert_eq!(at(super::hms(input)), want.unwrap(), "hms/{}", input)
         }
-        assert!(parse.hms("not-date-time").is_none());
+        assert!(super::hms("not-date-time").is_none());
     }
 
     #[test]
     fn hms_z() {
-        let parse = Parse::new(&Utc, None);
-        let now_at_pst = Utc::now().with_timezone(&FixedOffset::west(8 * 3600));
-
-        let test_cases = vec![
+        let test_cases = [
             (
                 "01:06:06 PST",
                 FixedOffset::west(8 * 3600)
-                    .from_local_date(&now_at_pst.date().naive_local())
+                    .from_local_date(&DAY)
                     .and_time(NaiveTime::from_hms(1, 6, 6))
                     .map(|dt| dt.with_timezone(&Utc)),
             ),
             (
                 "4:00pm PST",
                 FixedOffset::west(8 * 3600)
-                    .from_local_date(&now_at_pst.date().naive_local())
+                    .from_local_date(&DAY)
                     .and_time(NaiveTime::from_hms(16, 0, 0))
                     .map(|dt| dt.with_timezone(&Utc)),
             ),
             (
                 "6:00 AM PST",
                 FixedOffset::west(8 * 3600)
-                    .from_local_date(&now_at_pst.date().naive_local())
+                    .from_local_date(&DAY)
                     .and_time(NaiveTime::from_hms(6, 0, 0))
                     .map(|dt| dt.with_timezone(&Utc)),
             ),
             (
                 "6:00pm UTC",
                 FixedOffset::west(0)
-                    .from_local_date(&Utc::now().date().naive_local())
+                    .from_local_date(&DAY)
                     .and_time(NaiveTime::from_hms(18, 0, 0))
                     .map(|dt| dt.with_timezone(&Utc)),
             ),
         ];
 
         for &(input, want) in test_cases.iter() {
-            assert_eq!(
-                parse.hms_z(input).unwrap().unwrap(),
-                want.unwrap(),
-                "hms_z/{}",
-                input
-            )
+            assert_eq!(at(super::hms_z(input)), want.unwrap(), "hms_z/{}", input)
         }
-        assert!(parse.hms_z("not-date-time").is_none());
+        assert!(super::hms_z("not-date-time").is_none());
     }
 
     #[test]
     fn month_ymd() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![(
-            "2021-Feb-21",
-            Utc.ymd(2021, 2, 21).and_time(Utc::now().time()),
-        )];
+        let test_cases = [("2021-Feb-21", Utc.ymd(2021, 2, 21).and_time(TIME))];
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(
-                parse
-                    .month_ymd(input)
-                    .unwrap()
-                    .unwrap()
-                    .trunc_subsecs(0)
-                    .with_second(0)
-                    .unwrap(),
-                want.unwrap().trunc_subsecs(0).with_second(0).unwrap(),
+                at(super::month_ymd(input)),
+                want.unwrap(),
                 "month_ymd/{}",
                 input
             )
         }
-        assert!(parse.month_ymd("not-date-time").is_none());
+        assert!(super::month_ymd("not-date-time").is_none());
     }
 
     #[test]
     fn month_md_hms() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![
-            (
-                "May 6 at 9:24 PM",
-                Utc.ymd(Utc::now().year(), 5, 6).and_hms(21, 24, 0),
-            ),
-            (
-                "May 27 02:45:27",
-                Utc.ymd(Utc::now().year(), 5, 27).and_hms(2, 45, 27),
-            ),
+        // Months after DAY, so last year's.
+        let test_cases = [
+            ("May 6 at 9:24 PM", Utc.ymd(2021, 5, 6).and_hms(21, 24, 0)),
+            ("May 27 02:45:27", Utc.ymd(2021, 5, 27).and_hms(2, 45, 27)),
         ];
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(
-                parse.month_md_hms(input).unwrap().unwrap(),
+                at(super::month_md_hms(input)),
                 want,
                 "month_md_hms/{}",
                 input
             )
         }
-        assert!(parse.month_md_hms("not-date-time").is_none());
+        assert!(super::month_md_hms("not-date-time").is_none());
     }
 
     #[test]
     fn month_mdy_hms() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![
+        let test_cases = [
             (
                 "May 8, 2009 5:57:51 PM",
                 Utc.ymd(2009, 5, 8).and_hms(17, 57, 51),
@@ -1264,20 +1411,18 @@ mod tests {
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(
-                parse.month_mdy_hms(input).unwrap().unwrap(),
+                at(super::month_mdy_hms(input)),
                 want,
                 "month_mdy_hms/{}",
                 input
             )
         }
-        assert!(parse.month_mdy_hms("not-date-time").is_none());
+        assert!(super::month_mdy_hms("not-date-time").is_none());
     }
 
     #[test]
     fn month_mdy_hms_z() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![
+        let test_cases = [
             (
                 "May 02, 2021 15:51:31 UTC",
                 Utc.ymd(2021, 5, 2).and_hms(15, 51, 31),
@@ -1298,68 +1443,40 @@ mod tests {
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(
-                parse.month_mdy_hms_z(input).unwrap().unwrap(),
+                at(super::month_mdy_hms_z(input)),
                 want,
                 "month_mdy_hms_z/{}",
                 input
             )
         }
-        assert!(parse.month_mdy_hms_z("not-date-time").is_none());
+        assert!(super::month_mdy_hms_z("not-date-time").is_none());
     }
 
     #[test]
     fn month_mdy() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![
-            (
-                "May 25, 2021",
-                Utc.ymd(2021, 5, 25).and_time(Utc::now().time()),
-            ),
-            (
-                "oct 7, 1970",
-                Utc.ymd(1970, 10, 7).and_time(Utc::now().time()),
-            ),
-            (
-                "oct 7, 70",
-                Utc.ymd(1970, 10, 7).and_time(Utc::now().time()),
-            ),
-            (
-                "oct. 7, 1970",
-                Utc.ymd(1970, 10, 7).and_time(Utc::now().time()),
-            ),
-            (
-                "oct. 7, 70",
-                Utc.ymd(1970, 10, 7).and_time(Utc::now().time()),
-            ),
-            (
-                "October 7, 1970",
-                Utc.ymd(1970, 10, 7).and_time(Utc::now().time()),
-            ),
+        let test_cases = [
+            ("May 25, 2021", Utc.ymd(2021, 5, 25).and_time(TIME)),
+            ("oct 7, 1970", Utc.ymd(1970, 10, 7).and_time(TIME)),
+            ("oct 7, 70", Utc.ymd(1970, 10, 7).and_time(TIME)),
+            ("oct. 7, 1970", Utc.ymd(1970, 10, 7).and_time(TIME)),
+            ("oct. 7, 70", Utc.ymd(1970, 10, 7).and_time(TIME)),
+            ("October 7, 1970", Utc.ymd(1970, 10, 7).and_time(TIME)),
         ];
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(
-                parse
-                    .month_mdy(input)
-                    .unwrap()
-                    .unwrap()
-                    .trunc_subsecs(0)
-                    .with_second(0)
-                    .unwrap(),
-                want.unwrap().trunc_subsecs(0).with_second(0).unwrap(),
+                at(super::month_mdy(input)),
+                want.unwrap(),
                 "month_mdy/{}",
                 input
             )
         }
-        assert!(parse.month_mdy("not-date-time").is_none());
+        assert!(super::month_mdy("not-date-time").is_none());
     }
 
     #[test]
     fn month_dmy_hms() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![
+        let test_cases = [
             (
                 "12 Feb 2006, 19:17",
                 Utc.ymd(2006, 2, 12).and_hms(19, 17, 0),
@@ -1373,57 +1490,38 @@ mod tests {
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(
-                parse.month_dmy_hms(input).unwrap().unwrap(),
+                at(super::month_dmy_hms(input)),
                 want,
                 "month_dmy_hms/{}",
                 input
             )
         }
-        assert!(parse.month_dmy_hms("not-date-time").is_none());
+        assert!(super::month_dmy_hms("not-date-time").is_none());
     }
 
     #[test]
     fn month_dmy() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![
-            ("7 oct 70", Utc.ymd(1970, 10, 7).and_time(Utc::now().time())),
-            (
-                "7 oct 1970",
-                Utc.ymd(1970, 10, 7).and_time(Utc::now().time()),
-            ),
-            (
-                "03 February 2013",
-                Utc.ymd(2013, 2, 3).and_time(Utc::now().time()),
-            ),
-            (
-                "1 July 2013",
-                Utc.ymd(2013, 7, 1).and_time(Utc::now().time()),
-            ),
+        let test_cases = [
+            ("7 oct 70", Utc.ymd(1970, 10, 7).and_time(TIME)),
+            ("7 oct 1970", Utc.ymd(1970, 10, 7).and_time(TIME)),
+            ("03 February 2013", Utc.ymd(2013, 2, 3).and_time(TIME)),
+            ("1 July 2013", Utc.ymd(2013, 7, 1).and_time(TIME)),
         ];
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(
-                parse
-                    .month_dmy(input)
-                    .unwrap()
-                    .unwrap()
-                    .trunc_subsecs(0)
-                    .with_second(0)
-                    .unwrap(),
-                want.unwrap().trunc_subsecs(0).with_second(0).unwrap(),
+                at(super::month_dmy(input)),
+                want.unwrap(),
                 "month_dmy/{}",
                 input
             )
         }
-        assert!(parse.month_dmy("not-date-time").is_none());
+        assert!(super::month_dmy("not-date-time").is_none());
     }
 
     #[test]
     fn slash_mdy_hms() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![
+        let test_cases = [
             ("4/8/2014 22:05", Utc.ymd(2014, 4, 8).and_hms(22, 5, 0)),
             ("04/08/2014 22:05", Utc.ymd(2014, 4, 8).and_hms(22, 5, 0)),
             ("4/8/14 22:05", Utc.ymd(2014, 4, 8).and_hms(22, 5, 0)),
@@ -1449,54 +1547,38 @@ mod tests {
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(
-                parse.slash_mdy_hms(input).unwrap().unwrap(),
+                at(super::slash_mdy_hms(input)),
                 want,
                 "slash_mdy_hms/{}",
                 input
             )
         }
-        assert!(parse.slash_mdy_hms("not-date-time").is_none());
+        assert!(super::slash_mdy_hms("not-date-time").is_none());
     }
 
     #[test]
     fn slash_mdy() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![
-            (
-                "3/31/2014",
-                Utc.ymd(2014, 3, 31).and_time(Utc::now().time()),
-            ),
-            (
-                "03/31/2014",
-                Utc.ymd(2014, 3, 31).and_time(Utc::now().time()),
-            ),
-            ("08/21/71", Utc.ymd(1971, 8, 21).and_time(Utc::now().time())),
-            ("8/1/71", Utc.ymd(1971, 8, 1).and_time(Utc::now().time())),
+        let test_cases = [
+            ("3/31/2014", Utc.ymd(2014, 3, 31).and_time(TIME)),
+            ("03/31/2014", Utc.ymd(2014, 3, 31).and_time(TIME)),
+            ("08/21/71", Utc.ymd(1971, 8, 21).and_time(TIME)),
+            ("8/1/71", Utc.ymd(1971, 8, 1).and_time(TIME)),
         ];
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(
-                parse
-                    .slash_mdy(input)
-                    .unwrap()
-                    .unwrap()
-                    .trunc_subsecs(0)
-                    .with_second(0)
-                    .unwrap(),
-                want.unwrap().trunc_subsecs(0).with_second(0).unwrap(),
+                at(super::slash_mdy(input)),
+                want.unwrap(),
                 "slash_mdy/{}",
                 input
             )
         }
-        assert!(parse.slash_mdy("not-date-time").is_none());
+        assert!(super::slash_mdy("not-date-time").is_none());
     }
 
     #[test]
     fn slash_ymd_hms() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![
+        let test_cases = [
             ("2014/4/8 22:05", Utc.ymd(2014, 4, 8).and_hms(22, 5, 0)),
             ("2014/04/08 22:05", Utc.ymd(2014, 4, 8).and_hms(22, 5, 0)),
             ("2014/04/2 03:00:51", Utc.ymd(2014, 4, 2).and_hms(3, 0, 51)),
@@ -1513,155 +1595,121 @@ mod tests {
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(
-                parse.slash_ymd_hms(input).unwrap().unwrap(),
+                at(super::slash_ymd_hms(input)),
                 want,
                 "slash_ymd_hms/{}",
                 input
             )
         }
-        assert!(parse.slash_ymd_hms("not-date-time").is_none());
+        assert!(super::slash_ymd_hms("not-date-time").is_none());
     }
 
     #[test]
     fn slash_ymd() {
-        let parse = Parse::new(&Utc, Some(Utc::now().time()));
-
-        let test_cases = vec![
-            (
-                "2014/3/31",
-                Utc.ymd(2014, 3, 31).and_time(Utc::now().time()),
-            ),
-            (
-                "2014/03/31",
-                Utc.ymd(2014, 3, 31).and_time(Utc::now().time()),
-            ),
+        let test_cases = [
+            ("2014/3/31", Utc.ymd(2014, 3, 31).and_time(TIME)),
+            ("2014/03/31", Utc.ymd(2014, 3, 31).and_time(TIME)),
         ];
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(
-                parse
-                    .slash_ymd(input)
-                    .unwrap()
-                    .unwrap()
-                    .trunc_subsecs(0)
-                    .with_second(0)
-                    .unwrap(),
-                want.unwrap().trunc_subsecs(0).with_second(0).unwrap(),
+                at(super::slash_ymd(input)),
+                want.unwrap(),
                 "slash_ymd/{}",
                 input
             )
         }
-        assert!(parse.slash_ymd("not-date-time").is_none());
+        assert!(super::slash_ymd("not-date-time").is_none());
     }
 
     #[test]
     fn dot_mdy_or_ymd() {
-        let parse = Parse::new(&Utc, Some(Utc::now().time()));
-
-        let test_cases = vec![
+        let test_cases = [
             // mm.dd.yyyy
-            (
-                "3.31.2014",
-                Utc.ymd(2014, 3, 31).and_time(Utc::now().time()),
-            ),
-            (
-                "03.31.2014",
-                Utc.ymd(2014, 3, 31).and_time(Utc::now().time()),
-            ),
-            ("08.21.71", Utc.ymd(1971, 8, 21).and_time(Utc::now().time())),
+            ("3.31.2014", Utc.ymd(2014, 3, 31).and_time(TIME)),
+            ("03.31.2014", Utc.ymd(2014, 3, 31).and_time(TIME)),
+            ("08.21.71", Utc.ymd(1971, 8, 21).and_time(TIME)),
             // yyyy.mm.dd
-            (
-                "2014.03.30",
-                Utc.ymd(2014, 3, 30).and_time(Utc::now().time()),
-            ),
-            (
-                "2014.03",
-                Utc.ymd(2014, 3, Utc::now().day())
-                    .and_time(Utc::now().time()),
-            ),
+            ("2014.03.30", Utc.ymd(2014, 3, 30).and_time(TIME)),
+            ("2014.03", Utc.ymd(2014, 3, DAY.day()).and_time(TIME)),
         ];
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(
-                parse
-                    .dot_mdy_or_ymd(input)
-                    .unwrap()
-                    .unwrap()
-                    .trunc_subsecs(0)
-                    .with_second(0)
-                    .unwrap(),
-                want.unwrap().trunc_subsecs(0).with_second(0).unwrap(),
+                at(super::dot_mdy_or_ymd(input)),
+                want.unwrap(),
                 "dot_mdy_or_ymd/{}",
                 input
             )
         }
-        assert!(parse.dot_mdy_or_ymd("not-date-time").is_none());
+        assert!(super::dot_mdy_or_ymd("not-date-time").is_none());
     }
 
     #[test]
-    fn mysql_log_timestamp() {
-        let parse = Parse::new(&Utc, None);
+    fn year_month_on_a_later_day() {
+        let year_month = PartialDate::YearMonth {
+            year: 2024,
+            month: 2,
+        };
+        let default_day = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        assert_eq!(
+            year_month.on(default_day),
+            NaiveDate::from_ymd_opt(2024, 2, 29)
+        );
+        assert_eq!(
+            year_month.on(NaiveDate::from_ymd_opt(2024, 1, 5).unwrap()),
+            NaiveDate::from_ymd_opt(2024, 2, 5)
+        );
+    }
 
-        let test_cases = vec![
+    #[test]
+    fn mysql_log_timestamp() {
+        let test_cases = [
             // yymmdd hh:mm:ss mysql log
             ("171113 14:14:20", Utc.ymd(2017, 11, 13).and_hms(14, 14, 20)),
         ];
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(
-                parse.mysql_log_timestamp(input).unwrap().unwrap(),
+                at(super::mysql_log_timestamp(input)),
                 want,
                 "mysql_log_timestamp/{}",
                 input
             )
         }
-        assert!(parse.mysql_log_timestamp("not-date-time").is_none());
+        assert!(super::mysql_log_timestamp("not-date-time").is_none());
     }
 
     #[test]
     fn chinese_ymd_hms() {
-        let parse = Parse::new(&Utc, None);
-
-        let test_cases = vec![(
+        let test_cases = [(
             "2014年04月08日11时25分18秒",
             Utc.ymd(2014, 4, 8).and_hms(11, 25, 18),
         )];
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(
-                parse.chinese_ymd_hms(input).unwrap().unwrap(),
+                at(super::chinese_ymd_hms(input)),
                 want,
                 "chinese_ymd_hms/{}",
                 input
             )
         }
-        assert!(parse.chinese_ymd_hms("not-date-time").is_none());
+        assert!(super::chinese_ymd_hms("not-date-time").is_none());
     }
 
     #[test]
     fn chinese_ymd() {
-        let parse = Parse::new(&Utc, Some(Utc::now().time()));
-
-        let test_cases = vec![(
-            "2014年04月08日",
-            Utc.ymd(2014, 4, 8).and_time(Utc::now().time()),
-        )];
+        let test_cases = [("2014年04月08日", Utc.ymd(2014, 4, 8).and_time(TIME))];
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(
-                parse
-                    .chinese_ymd(input)
-                    .unwrap()
-                    .unwrap()
-                    .trunc_subsecs(0)
-                    .with_second(0)
-                    .unwrap(),
-                want.unwrap().trunc_subsecs(0).with_second(0).unwrap(),
+                at(super::chinese_ymd(input)),
+                want.unwrap(),
                 "chinese_ymd/{}",
                 input
             )
         }
-        assert!(parse.chinese_ymd("not-date-time").is_none());
+        assert!(super::chinese_ymd("not-date-time").is_none());
     }
 }
-*/
@@ -377,44 +377,36 @@ pub fn parse_with<Tz2: TimeZone>(
     Parse::new(tz, Some(default_time), Some(default_day)).parse(input)
 }
 
-/*  don't feel like fixing the compile errors
-TODO instead of doing that, refactor to not use a default time or day, but only return what was parsed.
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[derive(Clone, Copy)]
-    enum Trunc {
-        Seconds,
-        None,
-    }
+    // Dates without a year, more than a month after this, are taken to be from the year before.
+    const DAY: NaiveDate = NaiveDate::from_ymd_opt(2022, 3, 22).unwrap();
+    const TIME: NaiveTime = NaiveTime::from_hms_opt(8, 51, 6).unwrap();
 
     #[test]
     fn parse_in_local() {
-        let test_cases = vec![
+        let test_cases = [
             (
                 "unix_timestamp",
                 "1511648546",
                 Utc.ymd(2017, 11, 25).and_hms(22, 22, 26),
-                Trunc::None,
             ),
             (
                 "rfc3339",
                 "2017-11-25T22:34:50Z",
                 Utc.ymd(2017, 11, 25).and_hms(22, 34, 50),
-                Trunc::None,
             ),
             (
                 "rfc2822",
                 "Wed, 02 Jun 2021 06:31:39 GMT",
                 Utc.ymd(2021, 6, 2).and_hms(6, 31, 39),
-                Trunc::None,
             ),
             (
                 "postgres_timestamp",
                 "2019-11-29 08:08:05-08",
                 Utc.ymd(2019, 11, 29).and_hms(16, 8, 5),
-                Trunc::None,
             ),
             (
                 "ymd_hms",
@@ -423,77 +415,66 @@ mod tests {
                     .ymd(2021, 4, 30)
                     .and_hms(21, 14, 10)
                     .with_timezone(&Utc),
-                Trunc::None,
             ),
             (
                 "ymd_hms_z",
                 "2017-11-25 13:31:15 PST",
                 Utc.ymd(2017, 11, 25).and_hms(21, 31, 15),
-                Trunc::None,
             ),
             (
                 "ymd",
                 "2021-02-21",
                 Local
                     .ymd(2021, 2, 21)
-                    .and_time(Local::now().time())
+                    .and_time(TIME)
                     .unwrap()
                     .with_timezone(&Utc),
-                Trunc::Seconds,
             ),
             (
                 "ymd_z",
                 "2021-02-21 PST",
                 FixedOffset::west(8 * 3600)
                     .ymd(2021, 2, 21)
-                    .and_time(
-                        Utc::now()
-                            .with_timezone(&FixedOffset::west(8 * 3600))
-                            .time(),
-                    )
+                    .and_time(TIME)
                     .unwrap()
                     .with_timezone(&Utc),
-                Trunc::Seconds,
             ),
             (
                 "hms",
                 "4:00pm",
-                Local::now()
-                    .date()
+                Local
+                    .from_local_date(&DAY)
+                    .unwrap()
                     .and_time(NaiveTime::from_hms(16, 0, 0))
                     .unwrap()
                     .with_timezone(&Utc),
-                Trunc::None,
             ),
             (
                 "hms_z",
                 "6:00 AM PST",
-                Utc::now()
-                    .with_timezone(&FixedOffset::west(8 * 3600))
-                    .date()
+                FixedOffset::west(8 * 3600)
+                    .from_local_date(&DAY)
+                    .unwrap()
                     .and_time(NaiveTime::from_hms(6, 0, 0))
                     .unwrap()
                     .with_timezone(&Utc),
-                Trunc::None,
             ),
             (
                 "month_ymd",
                 "2021-Feb-21",
                 Local
                     .ymd(2021, 2, 21)
-                    .and_time(Local::now().time())
+                    .and_time(TIME)
                     .unwrap()
                     .with_timezone(&Utc),
-                Trunc::Seconds,
             ),
             (
                 "month_md_hms",
                 "May 27 02:45:27",
                 Local
-                    .ymd(Local::now().year(), 5, 27)
+                    .ymd(2021, 5, 27)
                     .and_hms(2, 45, 27)
                     .with_timezone(&Utc),
-                Trunc::None,
             ),
             (
                 "month_mdy_hms",
@@ -502,23 +483,20 @@ mod tests {
                     .ymd(2009, 5, 8)
                     .and_hms(17, 57, 51)
                     .with_timezone(&Utc),
-                Trunc::None,
             ),
             (
                 "month_mdy_hms_z",
                 "May 02, 2021 15:51 UTC",
                 Utc.ymd(2021, 5, 2).and_hms(15, 51, 0),
-                Trunc::None,
             ),
             (
                 "month_mdy",
                 "May 25, 2021",
                 Local
                     .ymd(2021, 5, 25)
-                    .and_time(Local::now().time())
+                    .and_time(TIME)
                     .unwrap()
                     .with_timezone(&Utc),
-                Trunc::Seconds,
             ),
             (
                 "month_dmy_hms",
@@ -527,17 +505,15 @@ mod tests {
                     .ymd(2019, 5, 14)
                     .and_hms_milli(19, 11, 40, 164)
                     .with_timezone(&Utc),
-                Trunc::None,
             ),
             (
                 "month_dmy",
                 "1 July 2013",
                 Local
                     .ymd(2013, 7, 1)
-                    .and_time(Local::now().time())
+                    .and_time(TIME)
                     .unwrap()
                     .with_timezone(&Utc),
-                Trunc::Seconds,
             ),
             (
                 "slash_mdy_hms",
@@ -546,17 +522,15 @@ mod tests {
                     .ymd(2012, 3, 19)
                     .and_hms(10, 11, 59)
                     .with_timezone(&Utc),
-                Trunc::None,
             ),
             (
                 "slash_mdy",
                 "08/21/71",
                 Local
                     .ymd(1971, 8, 21)
-                    .and_time(Local::now().time())
+                    .and_time(TIME)
                     .unwrap()
                     .with_timezone(&Utc),
-                Trunc::Seconds,
             ),
             (
                 "slash_ymd_hms",
@@ -565,27 +539,24 @@ mod tests {
                     .ymd(2012, 3, 19)
                     .and_hms(10, 11, 59)
                     .with_timezone(&Utc),
-                Trunc::None,
             ),
             (
                 "slash_ymd",
                 "2014/3/31",
                 Local
                     .ymd(2014, 3, 31)
-                    .and_time(Local::now().time())
+                    .and_time(TIME)
                     .unwrap()
                     .with_timezone(&Utc),
-                Trunc::Seconds,
             ),
             (
                 "dot_mdy_or_ymd",
                 "2014.03.30",
                 Local
                     .ymd(2014, 3, 30)
-                    .and_time(Local::now().time())
+                    .and_time(TIME)
                     .unwrap()
                     .with_timezone(&Utc),
-                Trunc::Seconds,
             ),
             (
                 "mysql_log_timestamp",
@@ -594,7 +565,6 @@ mod tests {
                     .ymd(2017, 11, 13)
                     .and_hms(14, 14, 20)
                     .with_timezone(&Utc),
-                Trunc::None,
             ),
             (
                 "chinese_ymd_hms",
@@ -603,244 +573,182 @@ mod tests {
                     .ymd(2014, 4, 8)
                     .and_hms(11, 25, 18)
                     .with_timezone(&Utc),
-                Trunc::None,
             ),
             (
                 "chinese_ymd",
                 "2014年04月08日",
                 Local
                     .ymd(2014, 4, 8)
-                    .and_time(Local::now().time())
+                    .and_time(TIME)
                     .unwrap()
                     .with_timezone(&Utc),
-                Trunc::Seconds,
             ),
         ];
 
-        for &(test, input, want, trunc) in test_cases.iter() {
-            match trunc {
-                Trunc::None => {
-                    assert_eq!(
-                        super::parse(input).unwrap(),
-                        want,
-                        "parse_in_local/{}/{}",
-                        test,
-                        input
-                    )
-                }
-                Trunc::Seconds => assert_eq!(
-                    super::parse(input)
-                        .unwrap()
-                        .trunc_subsecs(0)
-                        .with_second(0)
-                        .unwrap(),
-                    want.trunc_subsecs(0).with_second(0).unwrap(),
-                    "parse_in_local/{}/{}",
-                    test,
-                    input
-                ),
-            };
+        for &(test, input, want) in test_cases.iter() {
+            assert_eq!(
+                super::parse_with(input, &Local, TIME, DAY).unwrap(),
+                want,
+                "parse_in_local/{}/{}",
+                test,
+                input
+            )
         }
     }
 
     #[test]
     fn parse_with_timezone_in_utc() {
-        let test_cases = vec![
+        let test_cases = [
             (
                 "unix_timestamp",
                 "1511648546",
                 Utc.ymd(2017, 11, 25).and_hms(22, 22, 26),
-                Trunc::None,
             ),
             (
                 "rfc3339",
                 "2017-11-25T22:34:50Z",
                 Utc.ymd(2017, 11, 25).and_hms(22, 34, 50),
-                Trunc::None,
             ),
             (
                 "rfc2822",
                 "Wed, 02 Jun 2021 06:31:39 GMT",
                 Utc.ymd(2021, 6, 2).and_hms(6, 31, 39),
-                Trunc::None,
             ),
             (
                 "postgres_timestamp",
                 "2019-11-29 08:08:05-08",
                 Utc.ymd(2019, 11, 29).and_hms(16, 8, 5),
-                Trunc::None,
             ),
             (
                 "ymd_hms",
                 "2021-04-30 21:14:10",
                 Utc.ymd(2021, 4, 30).and_hms(21, 14, 10),
-                Trunc::None,
             ),
             (
                 "ymd_hms_z",
                 "2017-11-25 13:31:15 PST",
                 Utc.ymd(2017, 11, 25).and_hms(21, 31, 15),
-                Trunc::None,
             ),
             (
                 "ymd",
                 "2021-02-21",
-                Utc.ymd(2021, 2, 21).and_time(Utc::now().time()).unwrap(),
-                Trunc::Seconds,
+                Utc.ymd(2021, 2, 21).and_time(TIME).unwrap(),
             ),
             (
                 "ymd_z",
                 "2021-02-21 PST",
                 FixedOffset::west(8 * 3600)
                     .ymd(2021, 2, 21)
-                    .and_time(
-                        Utc::now()
-                            .with_timezone(&FixedOffset::west(8 * 3600))
-                            .time(),
-                    )
+                    .and_time(TIME)
                     .unwrap()
                     .with_timezone(&Utc),
-                Trunc::Seconds,
             ),
             (
                 "hms",
                 "4:00pm",
-                Utc::now()
-                    .date()
+                Utc.from_utc_date(&DAY)
                     .and_time(NaiveTime::from_hms(16, 0, 0))
                     .unwrap(),
-                Trunc::None,
             ),
             (
                 "hms_z",
                 "6:00 AM PST",
                 FixedOffset::west(8 * 3600)
                     .from_local_date(
-                        &Utc::now()
-                            .with_timezone(&FixedOffset::west(8 * 3600))
-                            .date()
+                        &FixedOffset::west(8 * 3600)
+                            .from_local_date(&DAY)
+                            .unwrap()
                             .naive_local(),
                     )
                     .and_time(NaiveTime::from_hms(6, 0, 0))
                     .unwrap()
                     .with_timezone(&Utc),
-                Trunc::None,
             ),
             (
                 "month_ymd",
                 "2021-Feb-21",
-                Utc.ymd(2021, 2, 21).and_time(Utc::now().time()).unwrap(),
-                Trunc::Seconds,
+                Utc.ymd(2021, 2, 21).and_time(TIME).unwrap(),
             ),
             (
                 "month_md_hms",
                 "May 27 02:45:27",
-                Utc.ymd(Utc::now().year(), 5, 27).and_hms(2, 45, 27),
-                Trunc::None,
+                Utc.ymd(2021, 5, 27).and_hms(2, 45, 27),
             ),
             (
                 "month_mdy_hms",
                 "May 8, 2009 5:57:51 PM",
                 Utc.ymd(2009, 5, 8).and_hms(17, 57, 51),
-                Trunc::None,
             ),
             (
                 "month_mdy_hms_z",
                 "May 02, 2021 15:51 UTC",
                 Utc.ymd(2021, 5, 2).and_hms(15, 51, 0),
-                Trunc::None,
             ),
             (
                 "month_mdy",
                 "May 25, 2021",
-                Utc.ymd(2021, 5, 25).and_time(Utc::now().time()).unwrap(),
-                Trunc::Seconds,
+                Utc.ymd(2021, 5, 25).and_time(TIME).unwrap(),
             ),
             (
                 "month_dmy_hms",
                 "14 May 2019 19:11:40.164",
                 Utc.ymd(2019, 5, 14).and_hms_milli(19, 11, 40, 164),
-                Trunc::None,
             ),
             (
                 "month_dmy",
                 "1 July 2013",
-                Utc.ymd(2013, 7, 1).and_time(Utc::now().time()).unwrap(),
-                Trunc::Seconds,
+                Utc.ymd(2013, 7, 1).and_time(TIME).unwrap(),
             ),
             (
                 "slash_mdy_hms",
                 "03/19/2012 10:11:59",
                 Utc.ymd(2012, 3, 19).and_hms(10, 11, 59),
-                Trunc::None,
             ),
             (
                 "slash_mdy",
                 "08/21/71",
-                Utc.ymd(1971, 8, 21).and_time(Utc::now().time()).unwrap(),
-                Trunc::Seconds,
+                Utc.ymd(1971, 8, 21).and_time(TIME).unwrap(),
             ),
             (
                 "slash_ymd_hms",
                 "2012/03/19 10:11:59",
                 Utc.ymd(2012, 3, 19).and_hms(10, 11, 59),
-                Trunc::None,
             ),
             (
                 "slash_ymd",
                 "2014/3/31",
-                Utc.ymd(2014, 3, 31).and_time(Utc::now().time()).unwrap(),
-                Trunc::Seconds,
+                Utc.ymd(2014, 3, 31).and_time(TIME).unwrap(),
             ),
             (
                 "dot_mdy_or_ymd",
                 "2014.03.30",
-                Utc.ymd(2014, 3, 30).and_time(Utc::now().time()).unwrap(),
-                Trunc::Seconds,
+                Utc.ymd(2014, 3, 30).and_time(TIME).unwrap(),
             ),
             (
                 "mysql_log_timestamp",
                 "171113 14:14:20",
                 Utc.ymd(2017, 11, 13).and_hms(14, 14, 20),
-                Trunc::None,
             ),
             (
                 "chinese_ymd_hms",
                 "2014年04月08日11时25分18秒",
                 Utc.ymd(2014, 4, 8).and_hms(11, 25, 18),
-                Trunc::None,
             ),
             (
                 "chinese_ymd",
                 "2014年04月08日",
-                Utc.ymd(2014, 4, 8).and_time(Utc::now().time()).unwrap(),
-                Trunc::Seconds,
+                Utc.ymd(2014, 4, 8).and_time(TIME).unwrap(),
             ),
         ];
 
-        for &(test, input, want, trunc) in test_cases.iter() {
-            match trunc {
-                Trunc::None => {
-                    assert_eq!(
-                        super::parse_with_timezone(input, &Utc).unwrap(),
-                        want,
-                        "parse_with_timezone_in_utc/{}/{}",
-                        test,
-                        input
-                    )
-                }
-                Trunc::Seconds => assert_eq!(
-                    super::parse_with_timezone(input, &Utc)
-                        .unwrap()
-                        .trunc_subsecs(0)
-                        .with_second(0)
-                        .unwrap(),
-                    want.trunc_subsecs(0).with_second(0).unwrap(),
-                    "parse_with_timezone_in_utc/{}/{}",
-                    test,
-                    input
-                ),
-            };
+        for &(test, input, want) in test_cases.iter() {
+            assert_eq!(
+                super::parse_with(input, &Utc, TIME, DAY).unwrap(),
+                want,
+                "parse_with_timezone_in_utc/{}/{}",
+                test,
+                input
+            )
         }
     }
 
@@ -855,7 +763,7 @@ mod tests {
         let before_midnight_naive = NaiveTime::from_hms_opt(23, 59, 59).unwrap();
         let us_edt = &FixedOffset::west_opt(4 * 3600).unwrap();
 
-        let edt_test_cases = vec![
+        let edt_test_cases = [
             ("ymd", "2023-04-21"),
             ("ymd_z", "2023-04-21 EDT"),
             ("month_ymd", "2023-Apr-21"),
@@ -872,7 +780,7 @@ mod tests {
 
         for &(test, input) in edt_test_cases.iter() {
             assert_eq!(
-                super::parse_with(input, us_edt, midnight_naive).unwrap(),
+                super::parse_with(input, us_edt, midnight_naive, DAY).unwrap(),
                 us_edt_midnight_as_utc,
                 "parse_with/{test}/{input}",
             )
@@ -882,7 +790,7 @@ mod tests {
         let us_edt_before_midnight_as_utc = Utc.ymd(2023, 4, 22).and_hms(3, 59, 59);
         for &(test, input) in edt_test_cases.iter() {
             assert_eq!(
-                super::parse_with(input, us_edt, before_midnight_naive).unwrap(),
+                super::parse_with(input, us_edt, before_midnight_naive, DAY).unwrap(),
                 us_edt_before_midnight_as_utc,
                 "parse_with/{test}/{input}",
             )
@@ -898,7 +806,7 @@ mod tests {
         let before_midnight_naive = NaiveTime::from_hms_opt(23, 59, 59).unwrap();
         let us_est = &FixedOffset::west(5 * 3600);
 
-        let est_test_cases = vec![
+        let est_test_cases = [
             ("ymd", "2023-12-21"),
             ("ymd_z", "2023-12-21 EST"),
             ("month_ymd", "2023-Dec-21"),
@@ -915,7 +823,7 @@ mod tests {
 
         for &(test, input) in est_test_cases.iter() {
             assert_eq!(
-                super::parse_with(input, us_est, midnight_naive).unwrap(),
+                super::parse_with(input, us_est, midnight_naive, DAY).unwrap(),
                 us_est_midnight_as_utc,
                 "parse_with/{test}/{input}",
             )
@@ -925,7 +833,7 @@ mod tests {
         let us_est_before_midnight_as_utc = Utc.ymd(2023, 12, 22).and_hms(4, 59, 59);
         for &(test, input) in est_test_cases.iter() {
             assert_eq!(
-                super::parse_with(input, us_est, before_midnight_naive).unwrap(),
+                super::parse_with(input, us_est, before_midnight_naive, DAY).unwrap(),
                 us_est_before_midnight_as_utc,
                 "parse_with/{test}/{input}",
             )
@@ -936,7 +844,7 @@ mod tests {
     fn parse_with_utc() {
         let midnight_naive = NaiveTime::from_hms_opt(0, 0, 0).unwrap();
         let before_midnight_naive = NaiveTime::from_hms_opt(23, 59, 59).unwrap();
-        let utc_test_cases = vec![
+        let utc_test_cases = [
             ("ymd", "2023-12-21"),
             ("ymd_z", "2023-12-21 UTC"),
             ("month_ymd", "2023-Dec-21"),
@@ -952,7 +860,7 @@ mod tests {
 
         for &(test, input) in utc_test_cases.iter() {
             assert_eq!(
-                super::parse_with(input, &Utc, midnight_naive).unwrap(),
+                super::parse_with(input, &Utc, midnight_naive, DAY).unwrap(),
                 utc_midnight,
                 "parse_with/{test}/{input}",
             )
@@ -962,7 +870,7 @@ mod tests {
         let utc_before_midnight = Utc.ymd(2023, 12, 21).and_hms(23, 59, 59);
         for &(test, input) in utc_test_cases.iter() {
             assert_eq!(
-                super::parse_with(input, &Utc, before_midnight_naive).unwrap(),
+                super::parse_with(input, &Utc, before_midnight_naive, DAY).unwrap(),
                 utc_before_midnight,
                 "parse_with/{test}/{input}",
             )
@@ -973,7 +881,7 @@ mod tests {
     fn parse_with_local() {
         let midnight_naive = NaiveTime::from_hms_opt(0, 0, 0).unwrap();
         let before_midnight_naive = NaiveTime::from_hms_opt(23, 59, 59).unwrap();
-        let local_test_cases = vec![
+        let local_test_cases = [
             ("ymd", "2023-12-21"),
             ("month_ymd", "2023-Dec-21"),
             ("month_mdy", "December 21, 2023"),
@@ -989,7 +897,7 @@ mod tests {
 
         for &(test, input) in local_test_cases.iter() {
             assert_eq!(
-                super::parse_with(input, &Local, midnight_naive).unwrap(),
+                super::parse_with(input, &Local, midnight_naive, DAY).unwrap(),
                 local_midnight_as_utc,
                 "parse_with/{test}/{input}",
             )
@@ -1003,11 +911,10 @@ mod tests {
 
         for &(test, input) in local_test_cases.iter() {
             assert_eq!(
-                super::parse_with(input, &Local, before_midnight_naive).unwrap(),
+                super::parse_with(input, &Local, before_midnight_naive, DAY).unwrap(),
                 local_before_midnight_as_utc,
                 "parse_with/{test}/{input}",
             )
         }
     }
 }
-*/
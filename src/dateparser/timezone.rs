@@ -1,8 +1,69 @@
 use anyhow::{anyhow, Result};
 use chrono::offset::FixedOffset;
+use chrono_tz::Tz;
+
+/// Zone abbreviations and their offsets in minutes. Some are ambiguous; IST is taken to be India's, as that's
+/// where most logs with it come from, and CST the US one.
+const ABBREVIATIONS: &[(&str, i32)] = &[
+    ("gmt", 0),
+    ("ut", 0),
+    ("utc", 0),
+    ("z", 0),
+    ("wet", 0),
+    ("west", 60),
+    ("bst", 60),
+    ("cet", 60),
+    ("cest", 2 * 60),
+    ("eet", 2 * 60),
+    ("eest", 3 * 60),
+    ("msk", 3 * 60),
+    ("ist", 5 * 60 + 30),
+    ("sgt", 8 * 60),
+    ("hkt", 8 * 60),
+    ("awst", 8 * 60),
+    ("jst", 9 * 60),
+    ("kst", 9 * 60),
+    ("acst", 9 * 60 + 30),
+    ("aest", 10 * 60),
+    ("aedt", 11 * 60),
+    ("nzst", 12 * 60),
+    ("nzdt", 13 * 60),
+    ("hst", -10 * 60),
+    ("akst", -9 * 60),
+    ("akdt", -8 * 60),
+    ("pst", -8 * 60),
+    ("pdt", -7 * 60),
+    ("mst", -7 * 60),
+    ("mdt", -6 * 60),
+    ("cst", -6 * 60),
+    ("cdt", -5 * 60),
+    ("est", -5 * 60),
+    ("edt", -4 * 60),
+];
+
+/// Splits an IANA zone name like `Europe/Berlin` off the end of `s`, if there is one.
+///
+/// ```
+/// use wd::dateparser::timezone::split_zone_name;
+///
+/// assert_eq!(split_zone_name("08:30 Europe/Berlin"), ("08:30", Some(chrono_tz::Europe::Berlin)));
+/// assert_eq!(split_zone_name("08:30 UTC"), ("08:30", Some(chrono_tz::UTC)));
+/// assert_eq!(split_zone_name("08:30"), ("08:30", None));
+/// ```
+pub fn split_zone_name(s: &str) -> (&str, Option<Tz>) {
+    let s = s.trim_end();
+    if let Some((rest, last)) = s.rsplit_once(' ') {
+        if let Ok(tz) = last.parse() {
+            return (rest.trim_end(), Some(tz));
+        }
+    }
+    (s, None)
+}
 
 /// Tries to parse `[-+]\d\d` continued by `\d\d`. Return FixedOffset if possible.
-/// It can parse RFC 2822 legacy timezones. If offset cannot be determined, -0000 will be returned.
+/// It can parse RFC 2822 legacy timezones, and the abbreviations of other common zones.
+/// Anything else is an error, rather than -0000 as RFC 2822 would have it; in a log line, a word after
+/// the time is much more likely to be a level or a name than a zone.
 ///
 /// The additional `colon` may be used to parse a mandatory or optional `:` between hours and minutes,
 /// and should return a valid FixedOffset or `Err` when parsing fails.
@@ -24,22 +85,11 @@ fn parse_offset_2822(s: &str) -> Result<i32> {
         .unwrap_or(s.len());
     if upto > 0 {
         let name = &s[..upto];
-        let offset_hours = |o| Ok(o * 3600);
-        if equals(name, "gmt") || equals(name, "ut") || equals(name, "utc") {
-            offset_hours(0)
-        } else if equals(name, "edt") {
-            offset_hours(-4)
-        } else if equals(name, "est") || equals(name, "cdt") {
-            offset_hours(-5)
-        } else if equals(name, "cst") || equals(name, "mdt") {
-            offset_hours(-6)
-        } else if equals(name, "mst") || equals(name, "pdt") {
-            offset_hours(-7)
-        } else if equals(name, "pst") {
-            offset_hours(-8)
-        } else {
-            Ok(0) // recommended by RFC 2822: consume but treat it as -0000
-        }
+        ABBREVIATIONS
+            .iter()
+            .find(|(abbreviation, _)| equals(name, abbreviation))
+            .map(|(_, minutes)| minutes * 60)
+            .ok_or_else(|| anyhow!("unknown timezone {}", name))
     } else {
         let offset = parse_offset_internal(s, |s| Ok(s), false)?;
         Ok(offset)
@@ -131,17 +181,21 @@ mod tests {
 
     #[test]
     fn parse() {
-        let test_cases = vec![
+        let test_cases = [
             ("-0800", FixedOffset::west(8 * 3600)),
             ("+10:00", FixedOffset::east(10 * 3600)),
             ("PST", FixedOffset::west(8 * 3600)),
             ("PDT", FixedOffset::west(7 * 3600)),
             ("UTC", FixedOffset::west(0)),
             ("GMT", FixedOffset::west(0)),
+            ("CET", FixedOffset::east(3600)),
+            ("IST", FixedOffset::east(5 * 3600 + 30 * 60)),
+            ("jst", FixedOffset::east(9 * 3600)),
         ];
 
         for &(input, want) in test_cases.iter() {
             assert_eq!(super::parse(input).unwrap(), want, "parse/{}", input)
         }
+        assert!(super::parse("INFO").is_err());
     }
 }
@@ -0,0 +1,158 @@
+//! The `split_line_provided` feature of the original drain: a format spec like
+//!
+//!   `<Date> <Time> <Level> <Component>: <Content>`
+//!
+//! splits each record into named header fields and the `<Content>` which is what actually gets clustered.
+//! That way every distinct timestamp doesn't have to be recognized as a `<*>` by the clustering step.
+
+use std::{ops::Range, str::FromStr};
+
+use regex::{CaptureLocations, Regex};
+use thiserror::Error;
+
+/// The one field that drain clusters on; every other field is a header.
+pub const CONTENT_FIELD: &str = "Content";
+
+#[derive(Error, Debug)]
+pub enum FormatError {
+    #[error("log format has no <{CONTENT_FIELD}> field")]
+    NoContent,
+    #[error("log format has an unterminated <")]
+    Unterminated,
+    #[error("log format field <{0}> is not a plain name")]
+    BadField(String),
+    #[error("log format field <{0}> appears twice")]
+    DuplicateField(String),
+    #[error("log format doesn't make a valid regex: {0}")]
+    Regex(#[from] regex::Error),
+}
+
+/// A parsed format spec. See the module docs.
+#[derive(Debug, Clone)]
+pub struct LogFormat {
+    spec: String,
+    /// Names of every field besides `<Content>`, in order of appearance.
+    headers: Vec<String>,
+    /// Capture group index of each header, and of the content.
+    header_groups: Vec<usize>,
+    content_group: usize,
+    re: Regex,
+}
+
+impl LogFormat {
+    pub fn new(spec: &str) -> Result<Self, FormatError> {
+        // Same as drain.py's generate_logformat_regex: literal runs of whitespace match any whitespace.
+        let mut pattern = String::from("^");
+        let mut headers = Vec::new();
+        let mut header_groups = Vec::new();
+        let mut content_group = None;
+        let mut group = 0;
+        let mut rest = spec;
+        while let Some(start) = rest.find('<') {
+            push_literal(&mut pattern, &rest[..start]);
+            let end = rest[start..].find('>').ok_or(FormatError::Unterminated)? + start;
+            let name = &rest[start + 1..end];
+            // It becomes a capture group name, and those can't start with a digit.
+            if name.is_empty()
+                || name.starts_with(|c: char| c.is_ascii_digit())
+                || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            {
+                return Err(FormatError::BadField(name.to_string()));
+            }
+            // Every field is exactly one capture group, in order; the (?s:) wrapper doesn't capture.
+            group += 1;
+            if name == CONTENT_FIELD {
+                if content_group.is_some() {
+                    return Err(FormatError::DuplicateField(name.to_string()));
+                }
+                content_group = Some(group);
+                // Content may span lines, see RecordBoundary.
+                pattern.push_str(&format!("(?s:(?P<{name}>.*?))"));
+            } else {
+                if headers.iter().any(|h| h == name) {
+                    return Err(FormatError::DuplicateField(name.to_string()));
+                }
+                headers.push(name.to_string());
+                header_groups.push(group);
+                pattern.push_str(&format!("(?P<{name}>.*?)"));
+            }
+            rest = &rest[end + 1..];
+        }
+        push_literal(&mut pattern, rest);
+        pattern.push('$');
+        let content_group = content_group.ok_or(FormatError::NoContent)?;
+        Ok(Self {
+            spec: spec.to_string(),
+            headers,
+            header_groups,
+            content_group,
+            re: Regex::new(&pattern)?,
+        })
+    }
+
+    /// Names of the header fields, in the same order as the values returned by [`Self::split`].
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    /// Splits `record` into its header values and its content.
+    /// None if the record doesn't fit the format, e.g. it's a continuation line.
+    pub fn split<'a>(&self, record: &'a str) -> Option<(Vec<&'a str>, &'a str)> {
+        let mut locs = self.capture_locations();
+        let mut headers = Vec::new();
+        let content = self.split_ranges(record, &mut locs, &mut headers)?;
+        let headers = headers.into_iter().map(|h| &record[h]).collect();
+        Some((headers, &record[content]))
+    }
+
+    /// Scratch space for [`Self::split_ranges`], can be reused for any number of records.
+    pub fn capture_locations(&self) -> CaptureLocations {
+        self.re.capture_locations()
+    }
+
+    /// Like [`Self::split`], but doesn't allocate: header ranges are put in `headers`, content range is returned.
+    pub fn split_ranges(
+        &self,
+        record: &str,
+        locs: &mut CaptureLocations,
+        headers: &mut Vec<Range<usize>>,
+    ) -> Option<Range<usize>> {
+        self.re.captures_read(locs, record)?;
+        headers.clear();
+        headers.extend(self.header_groups.iter().map(|&group| {
+            let (start, end) = locs.get(group).unwrap_or((0, 0));
+            start..end
+        }));
+        let (start, end) = locs.get(self.content_group)?;
+        Some(start..end)
+    }
+}
+
+fn push_literal(pattern: &mut String, literal: &str) {
+    let mut in_whitespace = false;
+    for c in literal.chars() {
+        if c.is_ascii_whitespace() {
+            if !in_whitespace {
+                pattern.push_str(r"\s+");
+            }
+            in_whitespace = true;
+        } else {
+            pattern.push_str(&regex::escape(c.encode_utf8(&mut [0; 4])));
+            in_whitespace = false;
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = FormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl std::fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.spec)
+    }
+}
@@ -1,7 +1,7 @@
 //! drainrs implements the [Drain](https://jiemingzhu.github.io/pub/pjhe_icws2017.pdf) algorithm for automatic log parsing.
 //! # Example:
 //! ```bash
-//!  cargo run ./apache-short.log | tail
+//!  wd drain ./apache-short.log | tail
 //! {"template":"[Sat Jun <*> <*> <*> [error] [client <*> script not found or unable to stat: /var/www/cgi-bin/awstats",
 //! "values":["11","03:03:04","2005]","202.133.98.6]"]}
 //! {"template":"[Sat Jun <*> <*> <*> [error] [client <*> script not found or unable to stat: /var/www/cgi-bin/awstats",
@@ -35,22 +35,29 @@
 //!
 //!   `"[<date>] [<log_level>] Digest logline here: <*>"`
 //!
-//! # TODO
-//! * None of the parameters that are configurable in the Python version are yet configurable here.
-//! * The first drain allowed `split_line_provided`, which let you write a simple token-mapper like this:
+//! # Log formats
+//! The first drain allowed `split_line_provided`, which let you write a simple token-mapper like this:
 //!
-//!   `<timestamp> <loglevel> <content>`
+//!   `<Date> <Time> <Level>: <Content>`
 //!
-//! And then drain would only apply its logic to `<content>`.
+//! And then drain would only apply its logic to `<Content>`.
 //!
 //! Drain3 appears to have dropped this in favor of preprocessing on the user-code side, which is fair enough, although
-//! the feature is very helpful from a cli/no-coding perspective.
+//! the feature is very helpful from a cli/no-coding perspective. So it's back, see [`LogFormat`].
 //!
-//! * Drain3 allows "masking", which appears to be for recognizing values like IPs or numbers.
-//! We have preliminary support for masking but it's not configurable from outside of the class and the user interface
-//! to it is not yet defined.
+//! # Configuration
+//! The parameters that are configurable in the Python version, plus drain3's "masking" for recognizing values like
+//! IPs or uuids, are in [`DrainConfig`].
+
+mod format;
+
+pub use format::{FormatError, LogFormat, CONTENT_FIELD};
 
 use std::fmt;
+use std::ops::Range;
+
+use bstr::ByteSlice;
+use regex::CaptureLocations;
 
 use borrowme::borrowme;
 use json_in_type::list::ToJSONList;
@@ -62,21 +69,84 @@ use thiserror::Error;
 
 use std::hash::{BuildHasher, Hash};
 
+use crate::records::Records;
+
+/// The knobs of the drain algorithm. Defaults are the ones from drain3.py.
+#[derive(Debug, Clone)]
+pub struct DrainConfig {
+    /// How many of a record's tokens have to match a template's static tokens for it to belong to that template.
+    pub similarity_threshold: f64,
+    /// Depth of the prefix tree, at least 2. The first `depth - 1` tokens of a record (or all but the last,
+    /// if it's shorter) decide which leaf's templates it's compared against.
+    pub depth: usize,
+    /// Most children a node of the prefix tree can have before new tokens all go down the `<*>` branch.
+    pub max_children: usize,
+    /// Tokens any of these match are always parameters, e.g. `^0x[0-9a-f]+$` for pointers.
+    /// Unanchored masks match tokens that merely contain them.
+    pub masks: Vec<Regex>,
+}
+
+impl Default for DrainConfig {
+    fn default() -> Self {
+        Self {
+            similarity_threshold: 0.4,
+            depth: 4,
+            max_children: 100,
+            masks: Vec::new(),
+        }
+    }
+}
+
+impl DrainConfig {
+    fn is_masked(&self, token: &str) -> bool {
+        self.masks.iter().any(|mask| mask.is_match(token))
+    }
+}
+
 /// In the process of parsing, the drain algo populates a ParseTree. This tree could be saved
 /// and re-used on the next run, to avoid "forgetting" the previously recognized log templates.
 #[derive(Default)]
 pub struct ParseTree {
     root: TreeRoot,
     next_cluster_id: usize,
+    config: DrainConfig,
 }
 
 impl ParseTree {
     pub fn new() -> Self {
+        Self::with_config(DrainConfig::default())
+    }
+
+    pub fn with_config(config: DrainConfig) -> Self {
+        assert!(config.depth >= 2, "drain tree depth must be at least 2");
         Self {
             root: TreeRoot::default(),
             next_cluster_id: 0,
+            config,
         }
     }
+
+    pub fn config(&self) -> &DrainConfig {
+        &self.config
+    }
+
+    /// The id of the template `record` belongs to, learning a new one if needed. All of `record` is clustered
+    /// together, even if it spans lines. Unlike [`RecordParser`], the text of the templates isn't kept around.
+    pub fn template_id(&mut self, record: &str, format: Option<&LogFormat>) -> Option<usize> {
+        let mut rpi = RecordsParsedIter::from(record, self)
+            .with_boundary(RecordBoundary::Whole)
+            .with_format(format);
+        let mut template_id = None;
+        while rpi.next(|result| match result {
+            RecordsParsedResult::NewTemplate(_) => true,
+            RecordsParsedResult::RecordParsed(rp) => {
+                template_id = Some(rp.template_id);
+                false
+            }
+            _ => false,
+        }) {}
+        template_id
+    }
 }
 
 #[derive(Default)]
@@ -212,20 +282,16 @@ enum TokenParse<'a> {
     Token(&'a str),
     MaskedValue(&'a str),
 }
-#[derive(Debug)]
-enum Preprocessed<'a> {
-    Segment(&'a str),
-    Value(&'a str),
-}
-
 #[derive(Error, Debug)]
 pub enum ParseError {
-    #[error("couldn't parse line with user defined template, multiline log msg?")]
+    #[error("record has no tokens to parse")]
     NoTokensInRecord,
+    #[error("couldn't parse line with user defined template, multiline log msg?")]
+    FormatMismatch,
 }
 
 // TODO Tech debt:
-// the iterator impl is dumb I think, callbacks suck.
+// the callback iterator impl is dumb I think, callbacks suck. Prefer RecordStream.
 // use i32/64 something rather than usize.
 
 /// For each log record, contains template_id the record belongs to, and `values` used to create the record.
@@ -241,6 +307,9 @@ pub struct RecordParsed<'a> {
     ///
     /// E.g. ["Thu Jun 09 06:07:05 2005", "notice", "done"]
     pub values: Vec<&'a str>,
+    /// Values of the [`LogFormat`] header fields, e.g. ["2005-06-09", "06:07:05", "notice"].
+    /// Empty when parsing without a format.
+    pub headers: Vec<&'a str>,
     // Can't get this to compile. Doesn't seem to be a big deal perf-wise.
     // pub values: &'short[&'a str],
 }
@@ -262,17 +331,65 @@ pub enum RecordsParsedResult<'a> {
     Done,
 }
 
+/// How the input is split into log records before tokenizing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RecordBoundary {
+    /// Every line is its own record. This is what the original paper does.
+    #[default]
+    Line,
+    /// Empty lines, lines starting with a space or tab, and `Caused by: ` lines continue the previous record.
+    /// This keeps stack traces and pretty-printed blobs in one record, and so one template.
+    Indented,
+    /// The input is already one record, however many lines it has, e.g. split up by the caller's own rules.
+    Whole,
+}
+
+impl RecordBoundary {
+    /// Whether `line` continues the record before it, rather than starting a new one.
+    pub fn is_continuation(&self, line: &[u8]) -> bool {
+        match self {
+            RecordBoundary::Line => false,
+            RecordBoundary::Whole => true,
+            RecordBoundary::Indented => {
+                matches!(line.first(), None | Some(b' ' | b'\t' | b'\r' | b'\n'))
+                    || line.starts_with(b"Caused by: ")
+            }
+        }
+    }
+
+    /// Splits the first record off of `input`, returning it (without its trailing newline) and the rest.
+    pub fn split_record<'a>(&self, input: &'a str) -> (&'a str, &'a str) {
+        let (record, rest) = self.split_record_bytes(input.as_bytes());
+        // Both ends are next to a newline, so these are char boundaries.
+        (&input[..record.len()], &input[input.len() - rest.len()..])
+    }
+
+    /// [`Self::split_record`] for input that may not be UTF-8.
+    pub fn split_record_bytes<'a>(&self, input: &'a [u8]) -> (&'a [u8], &'a [u8]) {
+        let mut end = input.find_byte(b'\n').unwrap_or(input.len());
+        while end + 1 < input.len() && self.is_continuation(&input[end + 1..]) {
+            end = input[end + 1..]
+                .find_byte(b'\n')
+                .map_or(input.len(), |idx| end + 1 + idx);
+        }
+        let record = &input[..end];
+        let rest = &input[(end + 1).min(input.len())..];
+        (record.strip_suffix(b"\r").unwrap_or(record), rest)
+    }
+}
+
 /// Iterator yielding every log record in the input string. A log record is generally a log-line,
-/// but can be multi-line.
+/// but can be multi-line, see [`RecordBoundary`].
 pub struct RecordsParsedIter<'a, 'b: 'a> {
     input: &'a str,
     pub state: &'b mut ParseTree,
+    boundary: RecordBoundary,
+    format: Option<&'b LogFormat>,
     tokens: Vec<TokenParse<'a>>,
     parsed: Vec<&'a str>,
 }
 
 impl<'a, 'b> RecordsParsedIter<'a, 'b> {
-
     pub fn from(
         input: &'a str,
         state: &'b mut ParseTree,
@@ -281,21 +398,29 @@ impl<'a, 'b> RecordsParsedIter<'a, 'b> {
         RecordsParsedIter {
             input,
             state,
+            boundary: RecordBoundary::default(),
+            format: None,
             tokens: Vec::new(),
             parsed: Vec::new(),
         }
     }
 
+    pub fn with_boundary(mut self, boundary: RecordBoundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    /// Only cluster the `<Content>` of each record, see [`LogFormat`].
+    pub fn with_format(mut self, format: Option<&'b LogFormat>) -> Self {
+        self.format = format;
+        self
+    }
+
     fn next<F, R>(&mut self, mut callback: F) -> R
     where
         F: FnMut(RecordsParsedResult<'a>) -> R,
     {
-        // f()
-        let split_result = self.input.split_once('\n');
-        let (line, next_input) = match split_result {
-            Some((line, rest)) => (line.strip_suffix('\r').unwrap_or(line), rest),
-            None => (self.input, &self.input[0..0]),
-        };
+        let (line, next_input) = self.boundary.split_record(self.input);
         self.input = next_input;
         if line.is_empty() {
             return match next_input.is_empty() {
@@ -303,112 +428,278 @@ impl<'a, 'b> RecordsParsedIter<'a, 'b> {
                 false => callback(RecordsParsedResult::UnparsedLine(line)),
             };
         }
-        // TODO we should be able to handle multi-line logs, but the original paper doesn't.
-        // It is easily fixed in the python by looking back, not so simple here.
-        // We will probably have to iterate through lines looking ahead.
+        // The original paper only handles one-line records; multi-line ones are joined by looking ahead
+        // for continuation lines in split_record, so newlines are just more whitespace to the tokenizer.
 
         // Step 1. First we split the line to get all of the tokens.
         // add_log_message from drain3.py
-        // E.g. splits the line into chunks <timestamp> <loglevel> <content>
-        // This was a no-op so I removed it. Should be able to find a way to structure the API in the future to avoid allocating return space.
-        // let line_chunks = split_line_provided(line); // Pre-defined chunks as user-specified, like <Time> <Content>
-        // TODO Copy regex from python, return list of spanning indicators.
-        // if line_chunks.is_none() {
-        // Couldn't parse with the given regex, it's probably a multiline string. Attach it to the last-emitted log.
-        // TODO attach_to_last_line(line);
-        // return callback(RecordsParsedResult::ParseError(
-        // ParseError::NoTokensInRecord,
-        // ));
-        // }
-        // TODO Let Content be something not the last thing in the msg, like for trailing log-line tags.
-        // let line_chunks = line_chunks.unwrap();
-        // let log_content = *line_chunks.iter().rev().next().unwrap();
-        let log_content = line;
-
-        // This is the masking feature from drain3; not clear what scenarios
-        // would be best to use it, but running without it for now.
-        // It seems we could use a combo of map and flatten to end up with an iterator longer than the source iterator.
-        // But preprocessed never gets resized so it's not hurting anything.
-        let mut preprocessed = Vec::new();
-        if false {
-            let re = Regex::new(r"\d+").unwrap();
-            let mut last_index = 0;
-            for mmatch in re.find_iter(log_content) {
-                if mmatch.start() > last_index {
-                    preprocessed.push(Preprocessed::Segment(
-                        &log_content[last_index..mmatch.start()],
-                    ));
+        // E.g. splits the line into chunks <timestamp> <loglevel> <content>, and only <content> is clustered.
+        let (headers, log_content) = match self.format {
+            Some(format) => match format.split(line) {
+                Some(split) => split,
+                // Couldn't parse with the given regex, it's probably a multiline string with the wrong RecordBoundary.
+                None => {
+                    return callback(RecordsParsedResult::ParseError(ParseError::FormatMismatch))
                 }
-                preprocessed.push(Preprocessed::Value(mmatch.as_str()));
-                last_index = mmatch.end();
+            },
+            None => (Vec::new(), line),
+        };
+
+        match parse_content(self.state, log_content, &mut self.tokens, &mut self.parsed) {
+            Err(e) => callback(RecordsParsedResult::ParseError(e)),
+            Ok((template_id, Some(template))) => {
+                // We can't return this because it would imply that our mutable self borrow in Self::next outlives 'a.
+                // RecordStream gets around this by lending out the record instead.
+                callback(RecordsParsedResult::NewTemplate(NewTemplate { template }));
+                callback(RecordsParsedResult::RecordParsed(RecordParsed {
+                    values: self.parsed.to_vec(),
+                    headers,
+                    template_id,
+                }))
             }
-            if last_index != log_content.len() {
-                preprocessed.push(Preprocessed::Segment(&log_content[last_index..]));
+            Ok((template_id, None)) => {
+                debug!("Line {} matched cluster: {:?}", line, template_id);
+                callback(RecordsParsedResult::RecordParsed(RecordParsed {
+                    values: self.parsed.to_vec(),
+                    headers,
+                    template_id,
+                }))
             }
-        } else {
-            preprocessed.push(Preprocessed::Segment(log_content));
         }
+    }
+}
 
-        let tokens = &mut self.tokens;
-        tokens.clear();
-        debug!("preprocessed={:?}", preprocessed);
-        for elem in preprocessed {
-            match elem {
-                Preprocessed::Segment(s) => tokens.extend(
-                    s.split([' ', '\t'])
-                        .filter(|s| !s.is_empty())
-                        .map(TokenParse::Token),
-                ),
-                Preprocessed::Value(v) => tokens.push(TokenParse::MaskedValue(v)),
-            }
-        }
+/// One record from [`RecordStream::next`]. It borrows the stream's buffers, so it has to be dropped before the
+/// next record can be read.
+#[derive(Debug)]
+pub struct StreamedRecord<'s> {
+    /// Byte offset of the record in the input.
+    pub offset: usize,
+    /// The record without its trailing newline, lossily decoded if it wasn't UTF-8.
+    pub record: &'s str,
+    /// Maps 1:1 to the order of new templates seen, same as [`RecordParsed::template_id`].
+    pub template_id: usize,
+    /// Set if this record is the first one seen of its template.
+    pub new_template: Option<&'s LogTemplate>,
+    values: &'s [Range<usize>],
+    headers: &'s [Range<usize>],
+}
 
-        if tokens.is_empty() {
-            return callback(RecordsParsedResult::ParseError(
-                ParseError::NoTokensInRecord,
-            ));
+impl<'s> StreamedRecord<'s> {
+    /// The values used to populate the record from the template, see [`RecordParsed::values`].
+    pub fn values(&self) -> impl ExactSizeIterator<Item = &'s str> + 's {
+        let record = self.record;
+        self.values.iter().map(move |range| &record[range.clone()])
+    }
+
+    /// Values of the [`LogFormat`] header fields, if there's a format.
+    pub fn headers(&self) -> impl ExactSizeIterator<Item = &'s str> + 's {
+        let record = self.record;
+        self.headers.iter().map(move |range| &record[range.clone()])
+    }
+}
+
+/// A record that drain couldn't make sense of, e.g. a blank line or one not matching the [`LogFormat`].
+#[derive(Debug)]
+pub struct UnparsedRecord<'s> {
+    pub offset: usize,
+    pub record: &'s str,
+    pub error: ParseError,
+}
+
+/// Lending iterator over the records in `input`. Unlike [`RecordsParsedIter`], there's no callback,
+/// and values and headers are kept as offsets into the record, in buffers reused from one record to the next.
+/// The tokens of a record borrow from it, so those are collected anew for each record.
+/// ```
+/// use wd::drainrs::{ParseTree, RecordStream};
+///
+/// let mut tree = ParseTree::default();
+/// let mut stream = RecordStream::new(b"took 5 ms\ntook 7 ms\n", &mut tree);
+/// while let Some(record) = stream.next() {
+///     let record = record.unwrap();
+///     assert_eq!(record.template_id, 0);
+///     assert_eq!(record.values().len(), 1);
+/// }
+/// ```
+pub struct RecordStream<'a, 'p> {
+    input: &'a [u8],
+    offset: usize,
+    tree: &'p mut ParseTree,
+    boundary: RecordBoundary,
+    /// Splits records instead of the boundary, if set.
+    records: Option<&'p Records>,
+    format: Option<&'p LogFormat>,
+    values: Vec<Range<usize>>,
+    headers: Vec<Range<usize>>,
+    locs: Option<CaptureLocations>,
+    /// Holds the lossily decoded record when it isn't UTF-8.
+    lossy: String,
+    new_template: Option<LogTemplate>,
+}
+
+impl<'a, 'p> RecordStream<'a, 'p> {
+    pub fn new(input: &'a [u8], tree: &'p mut ParseTree) -> Self {
+        Self {
+            input,
+            offset: 0,
+            tree,
+            boundary: RecordBoundary::default(),
+            records: None,
+            format: None,
+            values: Vec::new(),
+            headers: Vec::new(),
+            locs: None,
+            lossy: String::new(),
+            new_template: None,
         }
+    }
 
-        // Step 2, we map #(num_tokens) => a parse tree with limited depth.
-        let match_cluster = tree_search(&self.state.root, tokens);
-
-        if match_cluster.is_none() {
-            // We could also inline add_seq_to_prefix_tree here,
-            // Either the prefix tree did not exist, in which case we have to add it and a one-cluster leaf-node.
-            // Or, the prefix tree did exist, but no cluster matched above the threshold, so we need to add a cluster there.
-            let match_cluster = Some(add_seq_to_prefix_tree(
-                &mut self.state.root,
-                &tokens,
-                &mut self.state.next_cluster_id,
-            ))
-            .unwrap();
-            self.parsed.clear();
-            zip_tokens_and_template(&match_cluster.template, &tokens, &mut self.parsed);
-            callback(RecordsParsedResult::NewTemplate(NewTemplate {
-                // We can't return this because it would imply that our mutable self borrow in Self::next outlives 'a.
-                // We could make this less-copy by using a streaming-iterator or just taking callbacks to call.
-                // Unclear which would be more idiomatic, so leaving it alone for now.
-                template: match_cluster.template.to_vec(),
-            }));
-            return callback(RecordsParsedResult::RecordParsed(RecordParsed {
-                values: self.parsed.to_vec(),
-                template_id: match_cluster.cluster_id,
-            }));
+    pub fn with_boundary(mut self, boundary: RecordBoundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    /// Split the input into the same records as the rest of wd, e.g. by `--record-start`,
+    /// rather than by the [`RecordBoundary`].
+    pub fn with_records(mut self, records: Option<&'p Records>) -> Self {
+        self.records = records;
+        self
+    }
+
+    /// Only cluster the `<Content>` of each record, see [`LogFormat`].
+    pub fn with_format(mut self, format: Option<&'p LogFormat>) -> Self {
+        self.locs = format.map(LogFormat::capture_locations);
+        self.format = format;
+        self
+    }
+
+    /// Not an [`Iterator`], since each record borrows from the stream.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<Result<StreamedRecord<'_>, UnparsedRecord<'_>>> {
+        if self.offset >= self.input.len() {
+            return None;
         }
-        let match_cluster = match_cluster.unwrap();
-        debug!("Line {} matched cluster: {:?}", line, match_cluster);
-        // It feels like it should be doable to pass tokens without collecting it first,
-        // maintaining its lifetime as pointing to the original record. but skipped for now
-        // since can't figure out how to do that without .collect().
-        self.parsed.clear();
-        zip_tokens_and_template(&match_cluster.template, &tokens, &mut self.parsed);
-        return callback(RecordsParsedResult::RecordParsed(RecordParsed {
-            values: self.parsed.to_vec(),
-            template_id: match_cluster.cluster_id,
+        let offset = self.offset;
+        let record = match self.records {
+            Some(records) => {
+                let record = records.record_from(self.input, offset);
+                self.offset = record.end + 1;
+                // The last record can have the file's last newline, see Records.
+                self.input[record].trim_end_with(|c| c == '\n' || c == '\r')
+            }
+            None => {
+                let (record, rest) = self.boundary.split_record_bytes(&self.input[offset..]);
+                self.offset = self.input.len() - rest.len();
+                record
+            }
+        };
+        let record = match std::str::from_utf8(record) {
+            Ok(record) => record,
+            Err(_) => {
+                self.lossy.clear();
+                self.lossy.push_str(&record.to_str_lossy());
+                &self.lossy
+            }
+        };
+
+        let content = match (self.format, &mut self.locs) {
+            (Some(format), Some(locs)) => {
+                match format.split_ranges(record, locs, &mut self.headers) {
+                    Some(content) => content,
+                    None => {
+                        return Some(Err(UnparsedRecord {
+                            offset,
+                            record,
+                            error: ParseError::FormatMismatch,
+                        }))
+                    }
+                }
+            }
+            _ => {
+                self.headers.clear();
+                0..record.len()
+            }
+        };
+
+        let mut tokens = Vec::new();
+        let mut parsed = Vec::new();
+        let result = parse_content(self.tree, &record[content], &mut tokens, &mut parsed);
+        self.values.clear();
+        self.values.extend(parsed.iter().map(|value| {
+            // Values are slices of the record, so this is their position in it.
+            let start = value.as_ptr() as usize - record.as_ptr() as usize;
+            start..start + value.len()
         }));
+
+        Some(match result {
+            Ok((template_id, new_template)) => {
+                self.new_template = new_template;
+                Ok(StreamedRecord {
+                    offset,
+                    record,
+                    template_id,
+                    new_template: self.new_template.as_ref(),
+                    values: &self.values,
+                    headers: &self.headers,
+                })
+            }
+            Err(error) => Err(UnparsedRecord {
+                offset,
+                record,
+                error,
+            }),
+        })
     }
 }
 
+/// Step 1, splits the content of a record into tokens.
+/// This is also where drain3's masking happens, so that e.g. hex ids always end up as parameters.
+fn tokenize<'a>(log_content: &'a str, config: &DrainConfig, tokens: &mut Vec<TokenParse<'a>>) {
+    tokens.clear();
+    tokens.extend(
+        log_content
+            .split([' ', '\t', '\r', '\n'])
+            .filter(|s| !s.is_empty())
+            .map(|s| match config.is_masked(s) {
+                true => TokenParse::MaskedValue(s),
+                false => TokenParse::Token(s),
+            }),
+    );
+}
+
+/// Runs drain on the content of one record: tokenizes it, then finds or makes its cluster.
+/// `values` gets the parameters of the record. Returns the template id, plus the template if it's new.
+fn parse_content<'a>(
+    tree: &mut ParseTree,
+    log_content: &'a str,
+    tokens: &mut Vec<TokenParse<'a>>,
+    values: &mut Vec<&'a str>,
+) -> Result<(usize, Option<LogTemplate>), ParseError> {
+    tokenize(log_content, &tree.config, tokens);
+    if tokens.is_empty() {
+        return Err(ParseError::NoTokensInRecord);
+    }
+
+    // Step 2, we map #(num_tokens) => a parse tree with limited depth.
+    if let Some(match_cluster) = tree_search(&tree.root, &tree.config, tokens) {
+        zip_tokens_and_template(&match_cluster.template, tokens, values);
+        return Ok((match_cluster.cluster_id, None));
+    }
+    // Either the prefix tree did not exist, in which case we have to add it and a one-cluster leaf-node.
+    // Or, the prefix tree did exist, but no cluster matched above the threshold, so we need to add a cluster there.
+    let match_cluster = add_seq_to_prefix_tree(
+        &mut tree.root,
+        &tree.config,
+        tokens,
+        &mut tree.next_cluster_id,
+    );
+    zip_tokens_and_template(&match_cluster.template, tokens, values);
+    Ok((
+        match_cluster.cluster_id,
+        Some(match_cluster.template.clone()),
+    ))
+}
+
 fn has_numbers(s: &str) -> bool {
     s.chars().any(char::is_numeric)
 }
@@ -448,10 +739,11 @@ fn sequence_distance(seq1: &[OwnedLogTemplateItem], seq2: &[TokenParse]) -> (f64
     (ret_val, num_of_par)
 }
 
-// const SIMILARITY_THRESHOLD: f64 = 0.7;
-// All constants defaults pulled from drain3.py
-const SIMILARITY_THRESHOLD: f64 = 0.4;
-fn fast_match<'a>(logclusts: &'a Vec<LogCluster>, tokens: &[TokenParse]) -> Option<&'a LogCluster> {
+fn fast_match<'a>(
+    logclusts: &'a Vec<LogCluster>,
+    config: &DrainConfig,
+    tokens: &[TokenParse],
+) -> Option<&'a LogCluster> {
     // Sequence similarity search.
     let mut max_similarity = -1.0;
     let mut max_param_count = -1;
@@ -470,18 +762,16 @@ fn fast_match<'a>(logclusts: &'a Vec<LogCluster>, tokens: &[TokenParse]) -> Opti
         }
     }
 
-    if max_similarity >= SIMILARITY_THRESHOLD {
+    if max_similarity >= config.similarity_threshold {
         max_cluster
     } else {
         None
     }
 }
 
-
-const MAX_DEPTH: usize = 4;
-const MAX_CHILDREN: usize = 100;
 fn add_seq_to_prefix_tree<'a>(
     root: &'a mut TreeRoot,
+    config: &DrainConfig,
     tokens: &Vec<TokenParse>,
     num_clusters: &mut usize,
 ) -> &'a LogCluster {
@@ -511,10 +801,9 @@ fn add_seq_to_prefix_tree<'a>(
         })
     });
 
-    let mut current_depth = 1;
-    for token in tokens {
+    for (current_depth, token) in (1..).zip(tokens) {
         let inserter = || {
-            if current_depth == MAX_DEPTH - 1 || current_depth == token_count - 1 {
+            if current_depth == config.depth - 1 || current_depth == token_count - 1 {
                 GraphNodeContents::LeafNode(Vec::new())
             } else {
                 GraphNodeContents::MiddleNode(MiddleNode {
@@ -526,28 +815,27 @@ fn add_seq_to_prefix_tree<'a>(
         // trace!("token: {:?} node {:?}", token, cur_node);
         cur_node = match cur_node {
             GraphNodeContents::MiddleNode(middle) => {
-                assert!(!(current_depth >= MAX_DEPTH || current_depth >= token_count));
+                assert!(!(current_depth >= config.depth || current_depth >= token_count));
                 // if token not matched in this layer of existing tree.
                 let num_children = middle.child_d.len();
                 match token {
-                    TokenParse::MaskedValue(_v) => {
-                        middle
-                            .child_d
-                            .entry(OwnedLogTemplateItem::Value)
-                            .or_insert_with(inserter)
-                    }
+                    TokenParse::MaskedValue(_v) => middle
+                        .child_d
+                        .entry(OwnedLogTemplateItem::Value)
+                        .or_insert_with(inserter),
                     // https://stackoverflow.com/questions/36480845/how-to-avoid-temporary-allocations-when-using-a-complex-key-for-a-hashmap
                     // https://internals.rust-lang.org/t/pre-rfc-abandonning-morals-in-the-name-of-performance-the-raw-entry-api/70431
                     // It's possible that and_modify and _or_insert might be usd here but we really change at runtime to another entry
                     // syntactically this is ugly but whatever. we don't copy the strings anymore 8)
                     TokenParse::Token(token) => {
-                        let perfect_match_key = OwnedLogTemplateItem::StaticToken(token.to_string());
+                        let perfect_match_key =
+                            OwnedLogTemplateItem::StaticToken(token.to_string());
                         // At first glance, skipping over '*' entries here is unintuitive. However, if we've made it to
                         // adding, then there was not a satisfactory match in the tree already. So we'll copy the original
                         // algo and make a new node even if there is already a star here, as long as no numbers.
                         // if self.parametrize_numeric_tokens
                         // If it's a numerical token, take the * path.
-                        if has_numbers(token) || num_children >= MAX_CHILDREN {
+                        if has_numbers(token) || num_children >= config.max_children {
                             middle
                                 .child_d
                                 .entry(OwnedLogTemplateItem::Value)
@@ -564,7 +852,7 @@ fn add_seq_to_prefix_tree<'a>(
             }
             GraphNodeContents::LeafNode(leaf) => {
                 // if at max depth or this is last token in template - add current log cluster to the leaf node
-                assert!(current_depth >= MAX_DEPTH || current_depth >= token_count);
+                assert!(current_depth >= config.depth || current_depth >= token_count);
                 leaf.push(LogCluster {
                     template: tokens
                         .iter()
@@ -582,14 +870,17 @@ fn add_seq_to_prefix_tree<'a>(
                 return &leaf[leaf.len() - 1];
             }
         };
-        current_depth += 1
     }
     unreachable!();
 }
 
 // https://developer.ibm.com/blogs/how-mining-log-templates-can-help-ai-ops-in-cloud-scale-data-centers/
 
-fn tree_search<'a>(root: &'a TreeRoot, tokens: &[TokenParse]) -> Option<&'a LogCluster> {
+fn tree_search<'a>(
+    root: &'a TreeRoot,
+    config: &DrainConfig,
+    tokens: &[TokenParse],
+) -> Option<&'a LogCluster> {
     let token_count = tokens.len();
     assert!(token_count != 0);
     let e = root.get(&token_count);
@@ -601,9 +892,8 @@ fn tree_search<'a>(root: &'a TreeRoot, tokens: &[TokenParse]) -> Option<&'a LogC
         unreachable!("Shouldn't be possible.");
     }*/
     // let GraphNodeContents::MiddleNode(mut parentn) = parentn;
-    let mut current_depth = 1;
-    for token in tokens {
-        if current_depth >= MAX_DEPTH {
+    for (current_depth, token) in (1..).zip(tokens) {
+        if current_depth >= config.depth {
             break;
         }
 
@@ -619,12 +909,7 @@ fn tree_search<'a>(root: &'a TreeRoot, tokens: &[TokenParse]) -> Option<&'a LogC
         // If we know it's a Value, go ahead and take that branch.
         match token {
             TokenParse::MaskedValue(_v) => {
-                let maybe_next = middle.child_d.get(&OwnedLogTemplateItem::Value);
-                if let Some(next) = maybe_next {
-                    cur_node = next;
-                } else {
-                    return None;
-                }
+                cur_node = middle.child_d.get(&OwnedLogTemplateItem::Value)?;
             }
             TokenParse::Token(token) => {
                 // Actually walking to next child, look for the token, or a wildcard, or fail.
@@ -632,14 +917,12 @@ fn tree_search<'a>(root: &'a TreeRoot, tokens: &[TokenParse]) -> Option<&'a LogC
                 let maybe_next = middle.child_d.get(&key);
                 if let Some(next) = maybe_next {
                     cur_node = next;
-                } else if let Some(wildcard) = middle.child_d.get(&OwnedLogTemplateItem::Value) {
-                    cur_node = wildcard;
                 } else {
-                    return None; // Tried going down prefix tree that did not exist, need to make a new entry.
+                    // Tried going down prefix tree that did not exist, need to make a new entry.
+                    cur_node = middle.child_d.get(&OwnedLogTemplateItem::Value)?;
                 }
             }
         }
-        current_depth += 1;
     }
     // We have arrived at a list of LogClusters in a leaf-node.
     // Now, from these clusters, we need to pick the one that matches the closest.
@@ -647,7 +930,7 @@ fn tree_search<'a>(root: &'a TreeRoot, tokens: &[TokenParse]) -> Option<&'a LogC
         GraphNodeContents::MiddleNode(_) => unreachable!("Mistake."),
         GraphNodeContents::LeafNode(x) => x,
     };
-    let ret_log_clust = fast_match(log_clust, tokens);
+    let ret_log_clust = fast_match(log_clust, config, tokens);
     ret_log_clust
 }
 
@@ -667,71 +950,79 @@ pub type LogTemplate = Vec<OwnedLogTemplateItem>;
 
 use regex::Regex;
 
-use std::fs::read_to_string;
-
-/// Barebones example usage of the crate. Reads whole file into memory.
-pub fn print_log(filename: &str, actually_print: bool) {
-    // Abstraction review is due here. We don't need the whole file,
-    // we support streaming. It seems a line-based iterator is probably best,
-    // as an alternative "chunks" leaves us having to deal with partial-line reads.
-    // But right now creating a RecordsParsedIter involves some allocation.
-    // So for now we'll stick with read_to_string.
-    // Probably solution is to move most of the fields of iter out to a ParserState
-    // Then user doesn't have to fiddle with making each piece individually here, either.
-    let s: _ = read_to_string(filename).unwrap();
-    let mut tree = ParseTree::default();
-    let mut template_names = Vec::new();
-    let handle_parse = |template_names: &[String], rp: &RecordParsed| {
-        let typ = &template_names[rp.template_id];
-        let obj = json_object! {
-        template: typ,
-        values: ToJSONList(rp.values.to_vec())};
-        if actually_print {
-            println!("{}", obj.to_json_string());
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+/// Joins a template into the `<*>` form drain.py and drain3.py use.
+pub fn template_to_string(template: &[OwnedLogTemplateItem]) -> String {
+    template
+        .iter()
+        .map(|t| t.to_string())
+        .intersperse(" ".to_string())
+        .collect()
+}
+
+/// The values of a record as a JSON list, written straight from the record.
+struct ValuesJson<'r, 's>(&'r StreamedRecord<'s>);
+
+impl JSONValue for ValuesJson<'_, '_> {
+    fn write_json<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(b"[")?;
+        for (i, value) in self.0.values().enumerate() {
+            if i > 0 {
+                w.write_all(b",")?;
+            }
+            value.write_json(w)?;
         }
-        true
-    };
+        w.write_all(b"]")
+    }
+}
 
-    let mut rpi = RecordsParsedIter::from(&s, &mut tree);
-    loop {
-        let handle = |record| {
-            match record {
-                RecordsParsedResult::NewTemplate(template) => {
-                    template_names.push(
-                        template
-                            .template
-                            .iter()
-                            .map(|t| t.to_string())
-                            .intersperse(" ".to_string())
-                            .collect::<String>(),
-                    );
-                    // handle_parse(&template_names, &template.first_parse);
-                    true
-                }
-                RecordsParsedResult::RecordParsed(rp) => handle_parse(&template_names, &rp),
-                RecordsParsedResult::ParseError(e) => {
-                    error!("err: {}", e);
-                    false
-                }
-                RecordsParsedResult::UnparsedLine(line) => {
-                    error!("unparsed: {}", line);
-                    false
-                }
-                RecordsParsedResult::Done => {
-                    log::info!("Done!");
-                    false
+/// Barebones example usage of the crate. Writes a line of JSON with the template and values of every record
+/// in the file. The file is mmapped and streamed through drain, so it doesn't have to fit in memory.
+pub fn print_log(
+    filename: &str,
+    config: DrainConfig,
+    format: Option<&LogFormat>,
+    records: &Records,
+    out: impl Write,
+) -> io::Result<()> {
+    let file = File::open(filename)?;
+    if file.metadata()?.len() == 0 {
+        // Can't mmap nothing.
+        return Ok(());
+    }
+    let mmap = unsafe { memmap::MmapOptions::new().map(&file)? };
+    let mut out = BufWriter::new(out);
+    let mut tree = ParseTree::with_config(config);
+    let mut template_names = Vec::new();
+    let mut stream = RecordStream::new(&mmap, &mut tree)
+        .with_records(Some(records))
+        .with_format(format);
+    while let Some(record) = stream.next() {
+        match record {
+            Ok(record) => {
+                if let Some(template) = record.new_template {
+                    template_names.push(template_to_string(template));
                 }
+                let obj = json_object! {
+                template: template_names[record.template_id].as_str(),
+                values: ValuesJson(&record)};
+                obj.write_json(&mut out)?;
+                out.write_all(b"\n")?;
             }
-        };
-        if !rpi.next(handle) {
-            break;
+            Err(unparsed) => error!("unparsed at byte {}: {}", unparsed.offset, unparsed.error),
         }
     }
+    log::info!("Done!");
+    out.flush()
 }
 
 #[derive(Default)]
 pub struct RecordParser {
     state: DrainState,
+    boundary: RecordBoundary,
+    format: Option<LogFormat>,
     pub templates: Vec<String>,
 }
 
@@ -742,24 +1033,52 @@ pub enum RecordParsedResult<'a> {
 }
 
 impl RecordParser {
+    /// How a multi-line `record` passed to [`Self::parse_record`] is split up.
+    /// With the default [`RecordBoundary::Line`], only the first line is parsed.
+    pub fn with_boundary(mut self, boundary: RecordBoundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    /// Starts over with a tree using `config`, so call this before parsing anything.
+    pub fn with_config(mut self, config: DrainConfig) -> Self {
+        self.state.parse_tree = ParseTree::with_config(config);
+        self.templates.clear();
+        self
+    }
+
+    pub fn config(&self) -> &DrainConfig {
+        self.state.parse_tree.config()
+    }
+
+    /// Only cluster the `<Content>` field of each record; records that don't fit `format` are a
+    /// [`ParseError::FormatMismatch`].
+    pub fn with_format(mut self, format: Option<LogFormat>) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn format(&self) -> Option<&LogFormat> {
+        self.format.as_ref()
+    }
+
+    pub fn boundary(&self) -> RecordBoundary {
+        self.boundary
+    }
+
     /// Handles storing templates in a vector for you, passes through the rest.
     pub fn parse_record<'a>(&'a mut self, record: &'a str) -> RecordParsedResult<'a> {
         let mut result = None;
         let mut new_template = false;
 
-        let mut rpi = RecordsParsedIter::from(&record, &mut self.state.parse_tree);
+        let mut rpi = RecordsParsedIter::from(record, &mut self.state.parse_tree)
+            .with_boundary(self.boundary)
+            .with_format(self.format.as_ref());
         loop {
             let handle = |parsedrecord| {
                 match parsedrecord {
                     RecordsParsedResult::NewTemplate(template) => {
-                        self.templates.push(
-                            template
-                                .template
-                                .iter()
-                                .map(|t| t.to_string())
-                                .intersperse(" ".to_string())
-                                .collect::<String>(),
-                        );
+                        self.templates.push(template_to_string(&template.template));
                         new_template = true;
                         // handle_parse(&template_names, &template.first_parse);
                         true
@@ -781,16 +1100,212 @@ impl RecordParser {
                         panic!("Shouldnt happen in a one-line case.");
                     }
                     // Don't need to pass this through.
-                    RecordsParsedResult::Done => {
-                        assert!(result.is_some(), "The line {:?}'", record);
-                        false
-                    }
+                    RecordsParsedResult::Done => false,
                 }
             };
             if !rpi.next(handle) {
                 break;
             }
         }
-        return result.unwrap();
+        // An empty record never produces a result.
+        result.unwrap_or(RecordParsedResult::ParseError(ParseError::NoTokensInRecord))
+    }
+
+    /// The id of the template `record` belongs to, learning a new one if needed.
+    /// None if drain couldn't make sense of the record, e.g. it's blank.
+    pub fn template_id(&mut self, record: &str) -> Option<usize> {
+        match self.parse_record(record) {
+            RecordParsedResult::NewTemplate(rp) => Some(rp.template_id),
+            RecordParsedResult::RecordParsed(rp) => Some(rp.template_id),
+            RecordParsedResult::ParseError(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::records::RecordStart;
+
+    static TRACE: &str = "2022-03-22 08:51:06 ERROR request 1 failed: java.lang.IllegalStateException: connection 5 closed
+\tat com.example.Pool.take(Pool.java:42)
+\tat com.example.Server.handle(Server.java:17)
+Caused by: java.io.IOException: broken pipe
+\t... 2 more
+2022-03-22 08:51:07 ERROR request 2 failed: java.lang.IllegalStateException: connection 9 closed
+\tat com.example.Pool.take(Pool.java:42)
+\tat com.example.Server.handle(Server.java:17)
+Caused by: java.io.IOException: broken pipe
+\t... 2 more
+";
+
+    fn template_ids(input: &str, boundary: RecordBoundary) -> Vec<usize> {
+        let mut tree = ParseTree::default();
+        let mut rpi = RecordsParsedIter::from(input, &mut tree).with_boundary(boundary);
+        let mut ids = Vec::new();
+        while rpi.next(|result| match result {
+            RecordsParsedResult::RecordParsed(rp) => {
+                ids.push(rp.template_id);
+                true
+            }
+            RecordsParsedResult::NewTemplate(_) => true,
+            _ => false,
+        }) {}
+        ids
+    }
+
+    #[test]
+    fn split_record() {
+        let b = RecordBoundary::Indented;
+        assert_eq!(b.split_record("a\n b\nc"), ("a\n b", "c"));
+        assert_eq!(b.split_record("a\r\n\tb\r\n"), ("a\r\n\tb", ""));
+        assert_eq!(b.split_record("a\n b"), ("a\n b", ""));
+        assert_eq!(b.split_record("a\n\nb"), ("a\n", "b"));
+        assert_eq!(
+            RecordBoundary::Line.split_record("a\n b\nc"),
+            ("a", " b\nc")
+        );
+    }
+
+    #[test]
+    fn log_format() {
+        let format = LogFormat::new("<Date> <Time> <Level> [<Component>]: <Content>").unwrap();
+        assert_eq!(format.headers(), ["Date", "Time", "Level", "Component"]);
+        assert_eq!(
+            format.split("2022-03-22  08:51:06 INFO [main]: took 5 ms"),
+            Some((vec!["2022-03-22", "08:51:06", "INFO", "main"], "took 5 ms"))
+        );
+        assert_eq!(format.split("\tat com.example.Pool.take"), None);
+        assert_eq!(
+            format
+                .split("2022-03-22 08:51:06 ERROR [main]: oops\n\tat Pool.take")
+                .unwrap()
+                .1,
+            "oops\n\tat Pool.take"
+        );
+        assert!(matches!(
+            LogFormat::new("<Date> <Level>"),
+            Err(FormatError::NoContent)
+        ));
+        assert!(matches!(
+            LogFormat::new("<Date <Content>"),
+            Err(FormatError::BadField(_))
+        ));
+        assert!(matches!(
+            LogFormat::new("<1st> <Content>"),
+            Err(FormatError::BadField(_))
+        ));
+
+        // Headers never make it into the template, even though every timestamp differs.
+        let mut parser = RecordParser::default()
+            .with_boundary(RecordBoundary::Indented)
+            .with_format(Some(format));
+        for record in [
+            "2022-03-22 08:51:06 INFO [main]: took 5 ms",
+            "2022-03-23 11:00:00 WARN [pool]: took 7 ms",
+        ] {
+            match parser.parse_record(record) {
+                RecordParsedResult::NewTemplate(rp) | RecordParsedResult::RecordParsed(rp) => {
+                    assert_eq!(rp.template_id, 0);
+                    assert_eq!(rp.headers.len(), 4);
+                }
+                RecordParsedResult::ParseError(e) => panic!("{e}"),
+            }
+        }
+        assert_eq!(parser.templates, ["took <*> ms"]);
+        assert!(matches!(
+            parser.parse_record("no header here"),
+            RecordParsedResult::ParseError(ParseError::FormatMismatch)
+        ));
+    }
+
+    #[test]
+    fn record_stream() {
+        let mut tree = ParseTree::default();
+        let mut stream =
+            RecordStream::new(TRACE.as_bytes(), &mut tree).with_boundary(RecordBoundary::Indented);
+        let mut seen = Vec::new();
+        while let Some(record) = stream.next() {
+            let record = record.unwrap();
+            seen.push((
+                record.offset,
+                record.template_id,
+                record.new_template.map(|t| template_to_string(t)),
+                record.values().collect::<Vec<_>>().join(" "),
+            ));
+        }
+        let second = TRACE.find("2022-03-22 08:51:07").unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[0].0, 0);
+        assert_eq!(seen[1].0, second);
+        assert_eq!((seen[0].1, seen[1].1), (0, 0));
+        assert!(seen[0].2.is_some() && seen[1].2.is_none());
+        assert!(seen[1].3.starts_with("2022-03-22 08:51:07 2"));
+
+        // Split by wd's records instead, here one per timestamp.
+        let records = Records::new(RecordStart::Timestamp, Default::default());
+        let mut tree = ParseTree::default();
+        let mut stream =
+            RecordStream::new(TRACE.as_bytes(), &mut tree).with_records(Some(&records));
+        let mut offsets = Vec::new();
+        while let Some(record) = stream.next() {
+            let record = record.unwrap();
+            assert!(!record.record.ends_with('\n'));
+            offsets.push((record.offset, record.template_id));
+        }
+        assert_eq!(offsets, [(0, 0), (second, 0)]);
+
+        let format = LogFormat::new("<Date> <Time> <Level> <Content>").unwrap();
+        let mut tree = ParseTree::default();
+        let mut stream =
+            RecordStream::new(b"2022-03-22 08:51:06 INFO took 5 ms\n\xff\n", &mut tree)
+                .with_format(Some(&format));
+        let record = stream.next().unwrap().unwrap();
+        assert_eq!(
+            record.headers().collect::<Vec<_>>(),
+            ["2022-03-22", "08:51:06", "INFO"]
+        );
+        assert_eq!(record.values().collect::<Vec<_>>(), ["5"]);
+        let unparsed = stream.next().unwrap().unwrap_err();
+        assert_eq!(unparsed.record, "\u{FFFD}");
+        assert!(matches!(unparsed.error, ParseError::FormatMismatch));
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn drain_config() {
+        let records = "connect alpha ok\nconnect beta ok\n";
+        let ids = |config: DrainConfig| {
+            let mut tree = ParseTree::with_config(config);
+            records
+                .lines()
+                .map(|line| tree.template_id(line, None).unwrap())
+                .collect::<Vec<_>>()
+        };
+        // The second token picks a different leaf of the tree, so they're never even compared.
+        assert_eq!(ids(DrainConfig::default()), [0, 1]);
+        let masked = DrainConfig {
+            masks: vec![Regex::new("^(alpha|beta)$").unwrap()],
+            ..Default::default()
+        };
+        assert_eq!(ids(masked), [0, 0]);
+        let shallow = DrainConfig {
+            depth: 2,
+            ..Default::default()
+        };
+        assert_eq!(ids(shallow.clone()), [0, 0]);
+        let strict = DrainConfig {
+            similarity_threshold: 0.9,
+            ..shallow
+        };
+        assert_eq!(ids(strict), [0, 1]);
+    }
+
+    #[test]
+    fn stack_trace_is_one_template() {
+        assert_eq!(template_ids(TRACE, RecordBoundary::Indented), vec![0, 0]);
+        let per_line = template_ids(TRACE, RecordBoundary::Line);
+        assert_eq!(per_line.len(), 10);
+        assert!(per_line.iter().max().unwrap() > &0);
     }
 }
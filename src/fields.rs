@@ -0,0 +1,646 @@
+//! Named fields of structured records, such as JSON lines, logfmt, the groups of a user's regex or the header of a
+//! drain log format, so that they can be shown, filtered and
+//! timestamped by key rather than by where they happen to be in the line.
+
+use std::{borrow::Cow, cmp::Ordering, fmt};
+
+use bstr::ByteSlice;
+use clap::ValueEnum;
+use lazy_static::lazy_static;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use regex::Regex;
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use serde_json::Value;
+
+use crate::drainrs::{LogFormat, CONTENT_FIELD};
+
+/// Keys tried, in order, for the timestamp of a record when none was given.
+pub const TS_KEYS: [&str; 7] = [
+    "ts",
+    "time",
+    "timestamp",
+    "@timestamp",
+    "t",
+    "date",
+    "datetime",
+];
+/// Keys tried, in order, for the severity of a record.
+pub const LEVEL_KEYS: [&str; 5] = ["level", "lvl", "severity", "loglevel", "@level"];
+/// Keys tried, in order, for the message of a record.
+pub const MSG_KEYS: [&str; 6] = ["msg", "message", "@message", "event", "text", "content"];
+
+/// How many records [`FieldFormat::detect`] and the other field detection look at.
+pub(crate) const DETECT_RECORDS: usize = 10;
+
+/// How the fields of a record are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FieldFormat {
+    /// One JSON object per line.
+    Json,
+    /// `key=value` pairs separated by spaces, values quoted if they have spaces, like `level=info msg="hi there"`.
+    Logfmt,
+}
+
+impl FieldFormat {
+    /// The format of the first few records of `source`, if they all have one.
+    pub fn detect(source: &[u8]) -> Option<Self> {
+        let mut records = source
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .take(DETECT_RECORDS)
+            .peekable();
+        records.peek()?;
+        let mut formats = records.map(|record| {
+            [FieldFormat::Json, FieldFormat::Logfmt]
+                .into_iter()
+                .find(|format| format.parse(&record.to_str_lossy()).is_some())
+        });
+        let first = formats.next()??;
+        formats.all(|format| format == Some(first)).then_some(first)
+    }
+
+    /// The fields of `record`, or None if it isn't written this way, e.g. it's a stray line of text.
+    pub fn parse(self, record: &str) -> Option<Fields> {
+        match self {
+            FieldFormat::Json => {
+                let record = record.trim();
+                if !record.starts_with('{') {
+                    return None;
+                }
+                serde_json::from_str(record).ok()
+            }
+            FieldFormat::Logfmt => logfmt(record),
+        }
+    }
+
+    /// Finds the value of the top-level field `key` in a record, for [`crate::timestamps::TimestampParser::with_locator`].
+    pub fn key_locator(self, key: &str) -> Locator {
+        Locator::new(match self {
+            FieldFormat::Json => {
+                Regex::new(&format!(r#""{}"\s*:\s*"?([^",}}]+)"#, regex::escape(key))).unwrap()
+            }
+            FieldFormat::Logfmt => Regex::new(&format!(
+                r#"(?:^|\s){}=(?:"([^"]*)"|([^"\s]\S*))"#,
+                regex::escape(key)
+            ))
+            .unwrap(),
+        })
+    }
+}
+
+/// Finds one value in a line: the group named `group` of where `re` matches, or without a name,
+/// the first group that matched, or the whole match if it has no groups.
+#[derive(Debug, Clone)]
+pub struct Locator {
+    re: Regex,
+    group: Option<String>,
+}
+
+impl Locator {
+    pub fn new(re: Regex) -> Self {
+        Self { re, group: None }
+    }
+
+    /// The group of `re` named `group`, or None if there's no such group.
+    pub fn group(re: Regex, group: &str) -> Option<Self> {
+        re.capture_names()
+            .flatten()
+            .any(|name| name == group)
+            .then(|| Self {
+                re,
+                group: Some(group.to_string()),
+            })
+    }
+
+    pub fn find<'a>(&self, line: &'a str) -> Option<regex::Match<'a>> {
+        let caps = self.re.captures(line)?;
+        match &self.group {
+            Some(group) => caps.name(group),
+            None => caps.iter().skip(1).flatten().next().or(caps.get(0)),
+        }
+    }
+}
+
+/// Where the fields of records come from.
+#[derive(Debug, Clone)]
+pub enum FieldSource {
+    Format(FieldFormat),
+    /// The named groups of a regex that matches whole records, like `^(?P<ts>\S+ \S+) (?P<level>\w+) (?P<msg>.*)$`.
+    Regex(Regex),
+    /// The header fields of a drain [`LogFormat`], and its `Content`.
+    LogFormat(LogFormat),
+}
+
+impl FieldSource {
+    /// The fields of `record`, or None if it doesn't have them, e.g. it's a continuation line.
+    pub fn parse(&self, record: &str) -> Option<Fields> {
+        match self {
+            FieldSource::Format(format) => format.parse(record),
+            FieldSource::Regex(re) => {
+                let caps = re.captures(record)?;
+                let fields = re
+                    .capture_names()
+                    .flatten()
+                    .filter_map(|name| {
+                        let value = caps.name(name)?.as_str();
+                        Some((name.to_string(), Value::String(value.to_string())))
+                    })
+                    .collect();
+                Some(Fields(fields))
+            }
+            FieldSource::LogFormat(format) => {
+                let (headers, content) = format.split(record)?;
+                let names = format.headers().iter().map(String::as_str);
+                let fields = names
+                    .zip(headers)
+                    .chain([(CONTENT_FIELD, content)])
+                    .map(|(key, value)| (key.to_string(), Value::String(value.to_string())))
+                    .collect();
+                Some(Fields(fields))
+            }
+        }
+    }
+
+    /// See [`FieldFormat::key_locator`]. None when fields are found by position, since timestamps are then
+    /// wherever they'd be found without fields.
+    pub fn key_locator(&self, key: &str) -> Option<Locator> {
+        match self {
+            FieldSource::Format(format) => Some(format.key_locator(key)),
+            FieldSource::Regex(re) => Locator::group(re.clone(), key),
+            FieldSource::LogFormat(_) => None,
+        }
+    }
+}
+
+/// The fields of one record, in the order they were written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Fields(Vec<(String, Value)>);
+
+impl Fields {
+    /// The field named `key`, or if there isn't one, the nested field that `key` is the dotted path of.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        if let Some((_, value)) = self.0.iter().find(|(k, _)| k == key) {
+            return Some(value);
+        }
+        let mut path = key.split('.');
+        let first = path.next()?;
+        let mut value = &self.0.iter().find(|(k, _)| k == first)?.1;
+        for part in path {
+            value = value.get(part)?;
+        }
+        Some(value)
+    }
+
+    /// [`Self::get`] as text: strings without their quotes, anything else as compact JSON.
+    pub fn text(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.get(key).map(value_text)
+    }
+
+    /// The first of `keys` this record has, ignoring case, as the record writes it.
+    pub fn first_of(&self, keys: &[&str]) -> Option<&str> {
+        keys.iter().find_map(|key| {
+            self.iter()
+                .map(|(k, _)| k)
+                .find(|k| k.eq_ignore_ascii_case(key))
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Keeps only the fields in `shown`, if it isn't empty, and then only those not in `hidden`.
+    pub fn retain(&mut self, shown: &[String], hidden: &[String]) {
+        self.0
+            .retain(|(key, _)| (shown.is_empty() || shown.contains(key)) && !hidden.contains(key));
+    }
+
+    /// Replaces the value of the top-level field `key`, if there is one.
+    pub fn set(&mut self, key: &str, value: Value) {
+        if let Some((_, v)) = self.0.iter_mut().find(|(k, _)| k == key) {
+            *v = value;
+        }
+    }
+
+    /// Every field as `key=value` on one line, keys colored.
+    pub fn collapsed(&self) -> Line<'static> {
+        let mut spans = Vec::new();
+        for (i, (key, value)) in self.iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::raw(" "));
+            }
+            spans.push(Span::styled(format!("{key}="), KEY_STYLE));
+            let text = value_text(value);
+            let text = match text.contains(char::is_whitespace) {
+                true => format!("{:?}", text),
+                false => text.into_owned(),
+            };
+            spans.push(Span::styled(text, value_style(key, value)));
+        }
+        Line::from(spans)
+    }
+
+    /// The record as indented JSON, one top-level field per line.
+    pub fn pretty(&self) -> Vec<Line<'static>> {
+        let mut lines = vec![Line::raw("{")];
+        for (i, (key, value)) in self.iter().enumerate() {
+            let comma = if i + 1 < self.len() { "," } else { "" };
+            let pretty = serde_json::to_string_pretty(value).unwrap_or_default();
+            let mut rows = pretty.lines();
+            let first = rows.next().unwrap_or_default();
+            lines.push(Line::from(vec![
+                Span::raw("  "),
+                Span::styled(format!("{:?}", key), KEY_STYLE),
+                Span::raw(": "),
+                Span::styled(first.to_string(), value_style(key, value)),
+            ]));
+            lines.extend(rows.map(|row| Line::raw(format!("  {row}"))));
+            if let Some(last) = lines.last_mut() {
+                last.spans.push(Span::raw(comma));
+            }
+        }
+        lines.push(Line::raw("}"));
+        lines
+    }
+}
+
+impl<'de> Deserialize<'de> for Fields {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // serde_json's own map would sort the keys, and the order they were written in reads better.
+        struct FieldsVisitor;
+
+        impl<'de> Visitor<'de> for FieldsVisitor {
+            type Value = Fields;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Fields, A::Error> {
+                let mut fields = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some(field) = map.next_entry()? {
+                    fields.push(field);
+                }
+                Ok(Fields(fields))
+            }
+        }
+
+        deserializer.deserialize_map(FieldsVisitor)
+    }
+}
+
+/// The pairs of a logfmt record, None unless it's all pairs and there are at least two of them,
+/// so that a line of prose with an `=` in it isn't taken for one.
+fn logfmt(record: &str) -> Option<Fields> {
+    let mut fields = Vec::new();
+    let mut rest = record.trim();
+    while !rest.is_empty() {
+        let key_end = rest.find(|c: char| c == '=' || c == '"' || c.is_whitespace())?;
+        if key_end == 0 || !rest[key_end..].starts_with('=') {
+            return None;
+        }
+        let key = &rest[..key_end];
+        rest = &rest[key_end + 1..];
+        let value = match rest.strip_prefix('"') {
+            Some(quoted) => {
+                let mut escaped = false;
+                let end = quoted.char_indices().find_map(|(i, c)| match c {
+                    '"' if !escaped => Some(i),
+                    _ => {
+                        escaped = c == '\\' && !escaped;
+                        None
+                    }
+                })?;
+                rest = &quoted[end + 1..];
+                if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+                    return None;
+                }
+                // Go's %q escapes, which logfmt encoders use, read the same as JSON's for anything that matters.
+                let raw = &quoted[..end];
+                serde_json::from_str(&format!("\"{raw}\"")).unwrap_or_else(|_| raw.to_string())
+            }
+            None => {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                let value = rest[..end].to_string();
+                rest = &rest[end..];
+                value
+            }
+        };
+        fields.push((key.to_string(), Value::String(value)));
+        rest = rest.trim_start();
+    }
+    (fields.len() >= 2).then_some(Fields(fields))
+}
+
+const KEY_STYLE: Style = Style::new().fg(Color::Cyan);
+
+pub fn value_text(value: &Value) -> Cow<'_, str> {
+    match value {
+        Value::String(s) => Cow::Borrowed(s),
+        other => Cow::Owned(other.to_string()),
+    }
+}
+
+/// Severities stand out by color, everything else is plain.
+pub fn value_style(key: &str, value: &Value) -> Style {
+    if !LEVEL_KEYS.iter().any(|k| k.eq_ignore_ascii_case(key)) {
+        return Style::default();
+    }
+    let level = value_text(value).to_ascii_lowercase();
+    match level.as_str() {
+        "fatal" | "panic" | "critical" | "crit" | "error" | "err" => {
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+        }
+        "warn" | "warning" => Style::default().fg(Color::Yellow),
+        "info" => Style::default().fg(Color::Green),
+        "debug" | "trace" => Style::default().fg(Color::DarkGray),
+        _ => Style::default(),
+    }
+}
+
+/// How records with fields are shown. Records without them are always shown as written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FieldView {
+    #[default]
+    AsWritten,
+    Collapsed,
+    Pretty,
+    Columns,
+}
+
+impl FieldView {
+    pub fn next(self) -> Self {
+        match self {
+            FieldView::AsWritten => FieldView::Collapsed,
+            FieldView::Collapsed => FieldView::Pretty,
+            FieldView::Pretty => FieldView::Columns,
+            FieldView::Columns => FieldView::AsWritten,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Eq => ordering.is_eq(),
+            Comparison::Ne => ordering.is_ne(),
+            Comparison::Lt => ordering.is_lt(),
+            Comparison::Le => ordering.is_le(),
+            Comparison::Gt => ordering.is_gt(),
+            Comparison::Ge => ordering.is_ge(),
+        }
+    }
+}
+
+/// A condition on one field, like `level=error` or `latency_ms>500`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldFilter {
+    pub key: String,
+    pub op: Comparison,
+    pub value: String,
+}
+
+impl FieldFilter {
+    /// None if `s` isn't a key, one of `= != < <= > >=`, then a value.
+    pub fn parse(s: &str) -> Option<Self> {
+        lazy_static! {
+            static ref RE: Regex =
+                Regex::new(r"^\s*([\w.@-]+?)\s*(!=|<=|>=|=|<|>)\s*(.+?)\s*$").unwrap();
+        }
+        let caps = RE.captures(s)?;
+        let op = match &caps[2] {
+            "=" => Comparison::Eq,
+            "!=" => Comparison::Ne,
+            "<" => Comparison::Lt,
+            "<=" => Comparison::Le,
+            ">" => Comparison::Gt,
+            _ => Comparison::Ge,
+        };
+        let value = &caps[3];
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        Some(Self {
+            key: caps[1].to_string(),
+            op,
+            value: value.to_string(),
+        })
+    }
+
+    /// Numbers compare as numbers, anything else as text regardless of case.
+    /// A record without the field never matches.
+    pub fn matches(&self, fields: &Fields) -> bool {
+        let actual = match fields.text(&self.key) {
+            Some(actual) => actual,
+            None => return false,
+        };
+        let ordering = match (actual.parse::<f64>(), self.value.parse::<f64>()) {
+            (Ok(actual), Ok(wanted)) => actual.partial_cmp(&wanted),
+            _ => Some(actual.to_lowercase().cmp(&self.value.to_lowercase())),
+        };
+        ordering.is_some_and(|ordering| self.op.holds(ordering))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static JSONL: &str = r#"{"ts":"2024-05-01T10:00:00.123Z","level":"info","msg":"started","port":8080}
+{"ts":"2024-05-01T10:00:01.500Z","level":"error","msg":"request failed","latency_ms":734,"http":{"status":502}}
+"#;
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            FieldFormat::detect(JSONL.as_bytes()),
+            Some(FieldFormat::Json)
+        );
+        assert_eq!(FieldFormat::detect(b"plain text\n{\"a\":1}\n"), None);
+        assert_eq!(FieldFormat::detect(b""), None);
+
+        let second = JSONL.lines().nth(1).unwrap();
+        let fields = FieldFormat::Json.parse(second).unwrap();
+        let keys: Vec<_> = fields.iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, ["ts", "level", "msg", "latency_ms", "http"]);
+        assert_eq!(fields.text("msg").unwrap(), "request failed");
+        assert_eq!(fields.text("latency_ms").unwrap(), "734");
+        assert_eq!(fields.text("http.status").unwrap(), "502");
+        assert_eq!(fields.first_of(&TS_KEYS), Some("ts"));
+        assert!(FieldFormat::Json.parse("[1, 2]").is_none());
+
+        let ts = FieldFormat::Json.key_locator("ts");
+        assert_eq!(
+            ts.find(second).unwrap().as_str(),
+            "2024-05-01T10:00:01.500Z"
+        );
+        let latency = FieldFormat::Json.key_locator("latency_ms");
+        assert_eq!(latency.find(second).unwrap().as_str(), "734");
+    }
+
+    #[test]
+    fn logfmt() {
+        let logfmt = r#"ts=2024-05-01T10:00:00Z level=info msg="request done" path=/api dur=12ms
+time="2024-05-01 10:00:01" level=error msg="said \"no\"" err=
+"#;
+        assert_eq!(
+            FieldFormat::detect(logfmt.as_bytes()),
+            Some(FieldFormat::Logfmt)
+        );
+        assert_eq!(
+            FieldFormat::detect(b"GET /index.html?a=1 200\n"),
+            None,
+            "prose with an = in it"
+        );
+
+        let first = FieldFormat::Logfmt
+            .parse(logfmt.lines().next().unwrap())
+            .unwrap();
+        let keys: Vec<_> = first.iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, ["ts", "level", "msg", "path", "dur"]);
+        assert_eq!(first.text("msg").unwrap(), "request done");
+        let second = logfmt.lines().nth(1).unwrap();
+        let fields = FieldFormat::Logfmt.parse(second).unwrap();
+        assert_eq!(fields.text("msg").unwrap(), r#"said "no""#);
+        assert_eq!(fields.text("err").unwrap(), "");
+        assert!(FieldFormat::Logfmt.parse("a=1").is_none());
+        assert!(FieldFormat::Logfmt
+            .parse(r#"a=1 b="unterminated"#)
+            .is_none());
+        assert!(FieldFormat::Logfmt.parse("a=1 just words").is_none());
+
+        let time = FieldFormat::Logfmt.key_locator("time");
+        assert_eq!(time.find(second).unwrap().as_str(), "2024-05-01 10:00:01");
+        let ts = FieldFormat::Logfmt.key_locator("ts");
+        assert_eq!(ts.find(logfmt).unwrap().as_str(), "2024-05-01T10:00:00Z");
+        assert!(FieldFormat::Logfmt
+            .key_locator("s")
+            .find("ts=1 s2=3")
+            .is_none());
+    }
+
+    #[test]
+    fn record_regex() {
+        let re =
+            Regex::new(r"^(?P<ts>\S+ \S+) (?P<level>\w+)\s+:(?P<component>[^:]+): (?P<msg>.*)$")
+                .unwrap();
+        let source = FieldSource::Regex(re);
+        let record = "03/22/2022 08:51:01 INFO   :...locate_configFile: Specified configuration file: /u/user10/rsvpd1.conf";
+        let fields = source.parse(record).unwrap();
+        let keys: Vec<_> = fields.iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, ["ts", "level", "component", "msg"]);
+        assert_eq!(fields.text("component").unwrap(), "...locate_configFile");
+        assert_eq!(
+            fields.text("msg").unwrap(),
+            "Specified configuration file: /u/user10/rsvpd1.conf"
+        );
+        assert!(source.parse(" 02 ").is_none());
+
+        let level = source.key_locator("level").unwrap();
+        assert_eq!(level.find(record).unwrap().as_str(), "INFO");
+        let ts = source.key_locator("ts").unwrap();
+        assert_eq!(ts.find(record).unwrap().as_str(), "03/22/2022 08:51:01");
+        assert!(source.key_locator("missing").is_none());
+
+        // The regex is used as given, so escaped parens and unnamed groups don't throw it off.
+        let re = Regex::new(r"^(\w+) \(?P<level>\) (?P<level>\w+)$").unwrap();
+        let level = FieldSource::Regex(re).key_locator("level").unwrap();
+        assert_eq!(level.find("main (P<level>) WARN").unwrap().as_str(), "WARN");
+    }
+
+    #[test]
+    fn log_format_headers() {
+        let format = LogFormat::new("<Date> <Time> <Level> [<Thread>]: <Content>").unwrap();
+        let source = FieldSource::LogFormat(format);
+        let fields = source
+            .parse("2024-05-01 10:00:00 INFO [pool-1]: user 5 logged in")
+            .unwrap();
+        let keys: Vec<_> = fields.iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, ["Date", "Time", "Level", "Thread", "Content"]);
+        assert_eq!(fields.text("Content").unwrap(), "user 5 logged in");
+        assert_eq!(fields.first_of(&TS_KEYS), Some("Time"));
+        assert_eq!(fields.first_of(&LEVEL_KEYS), Some("Level"));
+        assert!(source.parse("    at continuation").is_none());
+        assert!(source.key_locator("Date").is_none());
+    }
+
+    #[test]
+    fn views() {
+        let fields = FieldFormat::Json
+            .parse(r#"{"level":"warn","msg":"disk almost full","pct":97}"#)
+            .unwrap();
+        let text =
+            |line: &Line| -> String { line.spans.iter().map(|s| s.content.clone()).collect() };
+        assert_eq!(
+            text(&fields.collapsed()),
+            r#"level=warn msg="disk almost full" pct=97"#
+        );
+        let pretty: Vec<_> = fields.pretty().iter().map(text).collect();
+        assert_eq!(
+            pretty,
+            [
+                "{",
+                r#"  "level": "warn","#,
+                r#"  "msg": "disk almost full","#,
+                r#"  "pct": 97"#,
+                "}"
+            ]
+        );
+
+        let mut fewer = fields.clone();
+        fewer.retain(&[], &["pct".to_string()]);
+        assert_eq!(
+            text(&fewer.collapsed()),
+            r#"level=warn msg="disk almost full""#
+        );
+        fewer.retain(&["msg".to_string()], &[]);
+        assert_eq!(text(&fewer.collapsed()), r#"msg="disk almost full""#);
+    }
+
+    #[test]
+    fn field_filters() {
+        let fields = FieldFormat::Json
+            .parse(r#"{"level":"ERROR","latency_ms":734,"http":{"status":502}}"#)
+            .unwrap();
+        let matches = |filter: &str| FieldFilter::parse(filter).unwrap().matches(&fields);
+        assert!(matches("level=error"));
+        assert!(!matches("level!=error"));
+        assert!(matches("latency_ms>500"));
+        assert!(!matches("latency_ms<=500"));
+        assert!(matches("latency_ms >= 734"));
+        assert!(matches("http.status=502"));
+        assert!(!matches("missing=1"));
+        assert!(!matches("missing!=1"));
+        assert_eq!(
+            FieldFilter::parse(r#"msg="a b""#).unwrap().value,
+            "a b".to_string()
+        );
+        assert!(FieldFilter::parse("just some words").is_none());
+        assert!(FieldFilter::parse("level=").is_none());
+    }
+}
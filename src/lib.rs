@@ -24,3 +24,15 @@ pub mod drainrs;
 pub mod utils;
 
 pub mod dateparser;
+
+pub mod config;
+
+pub mod template_index;
+
+pub mod template_diff;
+
+pub mod timestamps;
+
+pub mod fields;
+
+pub mod records;
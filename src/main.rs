@@ -1,28 +1,85 @@
 use std::fs::File;
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
 use colored::Colorize;
-use memmap::MmapOptions;
+use memmap::{Mmap, MmapOptions};
 use tracing::error;
-use wd::{app::App, logging::initialize_logging, tui::Tui, utils::initialize_panic_handler};
+use wd::{
+    app::App, config::Config, drainrs::print_log, logging::initialize_logging,
+    template_diff::diff_templates, template_index::print_templates, tui::Tui,
+    utils::initialize_panic_handler,
+};
 
 /// Ratatui Template TUI
 #[derive(Parser, Debug)]
-#[command(author, version, about)]
+#[command(author, version, about, subcommand_negates_reqs = true)]
 struct Args {
     /// The tick rate to use
     #[arg(short, long, default_value_t = 5000)]
     tick_rate: u64,
 
-    #[arg()]
-    filename: String,
+    #[arg(required = true)]
+    filename: Option<String>,
+
+    #[command(flatten)]
+    config: Config,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print a line of JSON with the drain template and values of every record.
+    Drain { filename: String },
+    /// Print a line of JSON with the count and first byte offset of every drain template, most common first.
+    Templates { filename: String },
+    /// Compare the drain templates of two logs, then view the bad one with the differences listed.
+    Diff {
+        good: String,
+        bad: String,
+        /// Report templates whose share of records changed by more than this factor.
+        #[arg(long, default_value_t = 2.0)]
+        factor: f64,
+    },
+}
+
+fn map_file(filename: &str) -> Result<Mmap> {
+    let file = File::open(filename).with_context(|| format!("Unable to open {filename}"))?;
+    Ok(unsafe { MmapOptions::new().map(&file)? })
 }
 
-async fn tui_main(tick_rate: u64, filename: String) -> Result<()> {
+async fn tui_main(tick_rate: u64, filename: String, config: Config) -> Result<()> {
     let file = File::open(filename.clone()).unwrap();
     let mmap = unsafe { MmapOptions::new().map(&file).unwrap() };
-    let mut app = App::new(tick_rate, filename, mmap);
+    let mut app = App::new(tick_rate, filename, mmap, config);
+    app.enter().await?;
+    app.init().await?;
+    app.run().await?;
+    app.exit().await?;
+    Ok(())
+}
+
+async fn diff_main(
+    tick_rate: u64,
+    good: String,
+    bad: String,
+    factor: f64,
+    config: Config,
+) -> Result<()> {
+    let good_mmap = map_file(&good)?;
+    let bad_mmap = map_file(&bad)?;
+    let mut parser = config.record_parser();
+    let diffs = diff_templates(
+        &good_mmap,
+        &bad_mmap,
+        &mut parser,
+        &config.records(),
+        factor,
+    );
+    let mut app = App::new(tick_rate, bad, bad_mmap, config);
+    app.home.lock().await.show_template_diff(diffs);
     app.enter().await?;
     app.init().await?;
     app.run().await?;
@@ -82,18 +139,57 @@ fn main() -> io::Result<()> {
 }
 */
 
+/// Whoever reads the output stopping early, like `wd drain big.log | head`, is a normal way to finish.
+fn ignore_broken_pipe(result: std::io::Result<()>) -> Result<()> {
+    match result {
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        result => Ok(result?),
+    }
+}
+
 fn main() -> Result<()> {
     initialize_logging()?;
 
     initialize_panic_handler();
 
     let args = Args::parse();
+    match &args.command {
+        Some(Command::Drain { filename }) => {
+            return ignore_broken_pipe(print_log(
+                filename,
+                args.config.drain_config(),
+                args.config.log_format.as_ref(),
+                &args.config.records(),
+                std::io::stdout().lock(),
+            ));
+        }
+        Some(Command::Templates { filename }) => {
+            let mmap = map_file(filename)?;
+            let mut parser = args.config.record_parser();
+            return ignore_broken_pipe(print_templates(
+                &mmap,
+                &mut parser,
+                &args.config.records(),
+                std::io::stdout().lock(),
+            ));
+        }
+        _ => {}
+    }
 
     match tokio::runtime::Builder::new_multi_thread()
         .enable_all()
         .build()?
-        .block_on(async { tui_main(args.tick_rate, args.filename).await })
-    {
+        .block_on(async {
+            match args.command {
+                Some(Command::Diff { good, bad, factor }) => {
+                    diff_main(args.tick_rate, good, bad, factor, args.config).await
+                }
+                Some(Command::Drain { .. } | Command::Templates { .. }) => {
+                    unreachable!("handled before starting the TUI")
+                }
+                None => tui_main(args.tick_rate, args.filename.unwrap(), args.config).await,
+            }
+        }) {
         Ok(_) => std::process::exit(0),
         Err(e) => {
             match Tui::new() {
@@ -0,0 +1,304 @@
+//! Splitting a log into records. Most records are a line, but some go on for more, like a stack trace,
+//! and those should be filtered, searched, clustered and scrolled past as one.
+
+use std::{collections::HashSet, ops::Range, str::FromStr};
+
+use bstr::ByteSlice;
+use chrono::NaiveDate;
+use regex::Regex;
+
+use crate::{
+    drainrs::RecordBoundary,
+    timestamps::{find_line_starting_before, TimestampParser},
+};
+
+/// Records longer than this are cut up, so that a start rule which rarely matches can't make the whole
+/// file one record.
+pub const MAX_RECORD_LINES: usize = 1000;
+
+/// Records of more lines than this can be folded to their first.
+pub const FOLD_AFTER_LINES: usize = 3;
+
+/// What the first line of a record looks like; any other line continues the record above it.
+#[derive(Debug, Clone, Default)]
+pub enum RecordStart {
+    /// Every line is a record.
+    Line,
+    /// Lines that aren't empty, indented, or a `Caused by:`, see [`RecordBoundary::Indented`].
+    #[default]
+    Indent,
+    /// Lines that start with a timestamp, or have one where `--ts-regex` looks.
+    Timestamp,
+    /// Lines that match the regex.
+    Regex(Regex),
+}
+
+impl FromStr for RecordStart {
+    type Err = regex::Error;
+
+    /// `line`, `indent`, `timestamp`, or else a regex.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "line" => Self::Line,
+            "indent" => Self::Indent,
+            "timestamp" => Self::Timestamp,
+            _ => Self::Regex(Regex::new(s)?),
+        })
+    }
+}
+
+/// Finds records by a [`RecordStart`] rule, in whichever source it's given.
+/// Ranges of records and lines never include their last newline, with one exception: if the source ends
+/// in a newline, the empty line after it continues the last record like any other empty line would,
+/// so that record takes the newline in. With [`RecordStart::Line`] it's an empty record of its own instead.
+#[derive(Debug, Clone, Default)]
+pub struct Records {
+    start: RecordStart,
+    /// For [`RecordStart::Timestamp`], kept apart from the one timestamps are shown with.
+    timestamps: TimestampParser,
+}
+
+impl Records {
+    pub fn new(start: RecordStart, timestamps: TimestampParser) -> Self {
+        Self { start, timestamps }
+    }
+
+    /// Whether the line starting at `line_start` starts a record. The first line of `source` always does.
+    pub fn is_start(&self, source: &[u8], line_start: usize) -> bool {
+        if line_start == 0 {
+            return true;
+        }
+        let line = &source[line_start..];
+        match &self.start {
+            RecordStart::Line => true,
+            RecordStart::Indent => !RecordBoundary::Indented.is_continuation(line),
+            RecordStart::Timestamp => self
+                .timestamps
+                .parse_at(source, line_start, NaiveDate::default())
+                .is_some(),
+            RecordStart::Regex(re) => {
+                re.is_match(&line[..line.find_byte(b'\n').unwrap_or(line.len())].to_str_lossy())
+            }
+        }
+    }
+
+    /// The record starting at `start`, whatever that line looks like.
+    pub fn record_from(&self, source: &[u8], start: usize) -> Range<usize> {
+        let mut end = line_end(source, start);
+        for _ in 1..MAX_RECORD_LINES {
+            if end == source.len() || self.is_start(source, end + 1) {
+                break;
+            }
+            end = line_end(source, end + 1);
+        }
+        start..end
+    }
+
+    /// The record that the line starting at `line_start` is part of, cut up the same way [`Self::forward`] would.
+    /// That means going back to where the record truly starts, however far that is.
+    pub fn record_at(&self, source: &[u8], line_start: usize) -> Range<usize> {
+        let mut start = line_start;
+        while !self.is_start(source, start) {
+            start = find_line_starting_before(source, start - 1);
+        }
+        loop {
+            let record = self.record_from(source, start);
+            if record.end >= line_start {
+                return record;
+            }
+            start = record.end + 1;
+        }
+    }
+
+    /// Records from the one starting at `start` to the end of `source`.
+    pub fn forward<'a>(
+        &'a self,
+        source: &'a [u8],
+        start: usize,
+    ) -> impl Iterator<Item = Range<usize>> + 'a {
+        let mut next = Some(start);
+        std::iter::from_fn(move || {
+            let record = self.record_from(source, next?);
+            next = (record.end < source.len()).then_some(record.end + 1);
+            Some(record)
+        })
+    }
+
+    /// Records before the one starting at `start`, nearest first, the same ones as [`Self::forward`] gives.
+    /// `start` can be the end of `source`, for every record.
+    pub fn backward<'a>(
+        &'a self,
+        source: &'a [u8],
+        start: usize,
+    ) -> impl Iterator<Item = Range<usize>> + 'a {
+        // The empty line after a last newline is where forward ends, whichever record it's part of.
+        let mut last = (start == source.len() && source.ends_with(b"\n"))
+            .then(|| self.record_at(source, start));
+        let mut next = last.as_ref().map_or(start, |record| record.start);
+        std::iter::from_fn(move || {
+            if let Some(last) = last.take() {
+                return Some(last);
+            }
+            if next == 0 {
+                return None;
+            }
+            let record = self.record_at(source, find_line_starting_before(source, next - 1));
+            next = record.start;
+            Some(record)
+        })
+    }
+}
+
+/// Which long records are shown folded to their first line. To begin with, all of them are.
+#[derive(Debug, Clone)]
+pub struct Folds {
+    all: bool,
+    /// Starts of records folded the other way from the rest.
+    toggled: HashSet<usize>,
+}
+
+impl Default for Folds {
+    fn default() -> Self {
+        Self {
+            all: true,
+            toggled: HashSet::new(),
+        }
+    }
+}
+
+impl Folds {
+    /// How many lines of `record` are hidden: all but the first if it's folded, else none.
+    pub fn hidden_lines(&self, source: &[u8], record: &Range<usize>) -> usize {
+        if self.all == self.toggled.contains(&record.start) {
+            return 0;
+        }
+        match line_count(source, record) {
+            lines if lines > FOLD_AFTER_LINES => lines - 1,
+            _ => 0,
+        }
+    }
+
+    /// Folds `record` if it's unfolded and the other way around. False if it's too short to fold.
+    pub fn toggle(&mut self, source: &[u8], record: &Range<usize>) -> bool {
+        if line_count(source, record) <= FOLD_AFTER_LINES {
+            return false;
+        }
+        if !self.toggled.remove(&record.start) {
+            self.toggled.insert(record.start);
+        }
+        true
+    }
+
+    /// Switches between every long record folded and every one unfolded.
+    pub fn toggle_all(&mut self) {
+        self.all = !self.all;
+        self.toggled.clear();
+    }
+
+    pub fn all_folded(&self) -> bool {
+        self.all
+    }
+}
+
+/// Lines of `record`, which can be any range of whole lines of `source`.
+pub fn lines(source: &[u8], record: Range<usize>) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut next = Some(record.start);
+    std::iter::from_fn(move || {
+        let start = next?;
+        let end = line_end(source, start).min(record.end);
+        next = (end < record.end).then_some(end + 1);
+        Some(start..end)
+    })
+}
+
+fn line_count(source: &[u8], record: &Range<usize>) -> usize {
+    source[record.clone()].find_iter("\n").count() + 1
+}
+
+/// Where the line starting at `line_start` ends: its newline, or the end of `source`.
+fn line_end(source: &[u8], line_start: usize) -> usize {
+    source[line_start..]
+        .find_byte(b'\n')
+        .map_or(source.len(), |idx| line_start + idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(source: &str, records: impl Iterator<Item = Range<usize>>) -> Vec<&str> {
+        records.map(|record| &source[record]).collect()
+    }
+
+    #[test]
+    fn start_rules() {
+        let source = "2024-01-02 10:00:00 boom\n  at a\nCaused by: b\n  at c\n2024-01-02 10:00:01 ok\n{\n}\n";
+        let bytes = source.as_bytes();
+        let records =
+            |start: &str| Records::new(start.parse().unwrap(), TimestampParser::default());
+
+        let indent = records("indent");
+        assert_eq!(
+            texts(source, indent.forward(bytes, 0)),
+            [
+                "2024-01-02 10:00:00 boom\n  at a\nCaused by: b\n  at c",
+                "2024-01-02 10:00:01 ok",
+                "{",
+                "}\n"
+            ]
+        );
+        let by_timestamp = records("timestamp");
+        let expected = [
+            "2024-01-02 10:00:00 boom\n  at a\nCaused by: b\n  at c",
+            "2024-01-02 10:00:01 ok\n{\n}\n",
+        ];
+        assert_eq!(texts(source, by_timestamp.forward(bytes, 0)), expected);
+        let mut backward = texts(source, by_timestamp.backward(bytes, source.len()));
+        backward.reverse();
+        assert_eq!(backward, expected);
+        assert_eq!(by_timestamp.record_at(bytes, 32), 0..51);
+        assert_eq!(
+            texts(source, lines(bytes, 0..51)),
+            [
+                "2024-01-02 10:00:00 boom",
+                "  at a",
+                "Caused by: b",
+                "  at c"
+            ]
+        );
+
+        let by_regex = records("^(2024|Caused)");
+        assert_eq!(
+            texts(source, by_regex.forward(bytes, 0)),
+            [
+                "2024-01-02 10:00:00 boom\n  at a",
+                "Caused by: b\n  at c",
+                "2024-01-02 10:00:01 ok\n{\n}\n"
+            ]
+        );
+        let by_line = records("line");
+        assert_eq!(by_line.forward(bytes, 0).count(), 8);
+        let mut backward: Vec<_> = by_line.backward(bytes, source.len()).collect();
+        backward.reverse();
+        assert_eq!(backward, by_line.forward(bytes, 0).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn long_records() {
+        let source = format!("boom\n{}next", "  at x\n".repeat(2500));
+        let bytes = source.as_bytes();
+        let records = Records::default();
+        let forward: Vec<_> = records.forward(bytes, 0).collect();
+        let lines: Vec<_> = forward
+            .iter()
+            .map(|record| line_count(bytes, record))
+            .collect();
+        assert_eq!(lines, [MAX_RECORD_LINES, MAX_RECORD_LINES, 501, 1]);
+        let mut backward: Vec<_> = records.backward(bytes, bytes.len()).collect();
+        backward.reverse();
+        assert_eq!(backward, forward);
+        // From the middle of a piece, it's still the piece forward cut.
+        let middle = forward[1].start + "  at x\n".len() * 300;
+        assert_eq!(records.record_at(bytes, middle), forward[1]);
+    }
+}
@@ -0,0 +1,125 @@
+//! Compares the drain templates of a known-good log against a failing one.
+
+use std::cmp::Ordering;
+
+use crate::{
+    drainrs::RecordParser,
+    records::Records,
+    template_index::{default_threads, TemplateIndex},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffKind {
+    OnlyGood,
+    OnlyBad,
+    /// How many times more (or less, if < 1) common the template is in the bad log, relative to its size.
+    Changed(f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateDiff {
+    pub kind: DiffKind,
+    pub template: String,
+    pub good_count: usize,
+    pub bad_count: usize,
+    /// Byte offset into the bad log of the first record with this template.
+    pub bad_first_seen: Option<usize>,
+}
+
+/// Runs one parser over both logs, so the same template gets the same id in each, and reports the
+/// templates that only one of them has, or whose share of records changed by more than `factor`.
+/// Sorted with templates new in the bad log first, as that's usually where the answer is.
+pub fn diff_templates(
+    good: &[u8],
+    bad: &[u8],
+    parser: &mut RecordParser,
+    records: &Records,
+    factor: f64,
+) -> Vec<TemplateDiff> {
+    let good_index = TemplateIndex::build_parallel(good, parser, records, default_threads());
+    let bad_index = TemplateIndex::build_parallel(bad, parser, records, default_threads());
+    let good_total = good_index.total().max(1) as f64;
+    let bad_total = bad_index.total().max(1) as f64;
+
+    let mut diffs: Vec<_> = parser
+        .templates
+        .iter()
+        .enumerate()
+        .filter_map(|(template_id, template)| {
+            let good_count = good_index.count(template_id);
+            let bad_count = bad_index.count(template_id);
+            let kind = match (good_count, bad_count) {
+                (0, 0) => return None,
+                (_, 0) => DiffKind::OnlyGood,
+                (0, _) => DiffKind::OnlyBad,
+                (good_count, bad_count) => {
+                    let ratio = (bad_count as f64 / bad_total) / (good_count as f64 / good_total);
+                    if ratio.max(1.0 / ratio) <= factor {
+                        return None;
+                    }
+                    DiffKind::Changed(ratio)
+                }
+            };
+            Some(TemplateDiff {
+                kind,
+                template: template.clone(),
+                good_count,
+                bad_count,
+                bad_first_seen: (bad_count > 0).then(|| bad_index.stats[template_id].first_seen),
+            })
+        })
+        .collect();
+    diffs.sort_by(|a, b| {
+        sort_key(a)
+            .partial_cmp(&sort_key(b))
+            .unwrap_or(Ordering::Equal)
+    });
+    diffs
+}
+
+fn sort_key(diff: &TemplateDiff) -> (u8, f64) {
+    match diff.kind {
+        DiffKind::OnlyBad => (0, -(diff.bad_count as f64)),
+        // Biggest change first, whichever direction it went.
+        DiffKind::Changed(ratio) => (1, -ratio.max(1.0 / ratio)),
+        DiffKind::OnlyGood => (2, -(diff.good_count as f64)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff() {
+        let good = b"connected to host 1\nconnected to host 2\nrequest took 5 ms\nrequest took 6 ms\nshutting down cleanly\n";
+        let bad = b"connected to host 1\nrequest took 500 ms\nrequest took 600 ms\nrequest took 700 ms\nrequest took 800 ms\nrequest took 900 ms\ndisk on fire\n";
+        let diffs = diff_templates(
+            good,
+            bad,
+            &mut RecordParser::default(),
+            &Records::default(),
+            2.0,
+        );
+        let summary: Vec<_> = diffs
+            .iter()
+            .map(|d| {
+                (
+                    d.template.as_str(),
+                    d.good_count,
+                    d.bad_count,
+                    d.bad_first_seen,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("disk on fire", 0, 1, Some(120)),
+                ("connected to host <*>", 2, 1, Some(0)),
+                ("shutting down cleanly", 1, 0, None),
+            ]
+        );
+        assert!(matches!(diffs[1].kind, DiffKind::Changed(r) if r < 0.5));
+    }
+}
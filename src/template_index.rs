@@ -0,0 +1,311 @@
+//! Whole-file drain template counts, for telling rare records apart from the usual spam.
+
+use std::{
+    io::{self, BufWriter, Write},
+    thread,
+};
+
+use bstr::ByteSlice;
+use json_in_type::*;
+
+use crate::{
+    drainrs::{ParseTree, RecordParser},
+    records::Records,
+};
+
+/// Below this, splitting the file up between threads isn't worth it.
+const MIN_CHUNK_SIZE: usize = 4 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TemplateStats {
+    /// Number of records in the file with this template.
+    pub count: usize,
+    /// Byte offset of the first record with this template, usize::MAX if count is 0.
+    pub first_seen: usize,
+}
+
+impl Default for TemplateStats {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            first_seen: usize::MAX,
+        }
+    }
+}
+
+/// Per-template stats, indexed by template id of the [`RecordParser`] that built it.
+/// A parser that already knows templates from elsewhere is fine, templates this file lacks get a count of 0.
+#[derive(Debug, Default)]
+pub struct TemplateIndex {
+    pub stats: Vec<TemplateStats>,
+    pub file_len: usize,
+}
+
+impl TemplateIndex {
+    /// Runs every record of `source`, as split up by `records`, through `parser`. Afterwards `parser` knows
+    /// every template in the file, so its ids can be looked up here.
+    pub fn build(source: &[u8], parser: &mut RecordParser, records: &Records) -> Self {
+        Self {
+            stats: count_records(source, 0, records, |record| parser.template_id(record)),
+            file_len: source.len(),
+        }
+    }
+
+    /// Same as [`Self::build`], but splits `source` into up to `threads` chunks which are clustered separately.
+    ///
+    /// The templates found in each chunk are then merged by running the first record of each through `parser`,
+    /// so ids are the same no matter how the threads were scheduled, and a chunk's records are counted under
+    /// whichever template `parser` gives their chunk-local template.
+    /// This can count a few records differently than [`Self::build`] would, as drain results depend on the order
+    /// records are seen in; it's close enough to tell rare from common.
+    pub fn build_parallel(
+        source: &[u8],
+        parser: &mut RecordParser,
+        records: &Records,
+        threads: usize,
+    ) -> Self {
+        let threads = threads.min(source.len() / MIN_CHUNK_SIZE);
+        let chunks = split_chunks(source, threads, records);
+        Self::build_chunked(source, parser, records, &chunks)
+    }
+
+    fn build_chunked(
+        source: &[u8],
+        parser: &mut RecordParser,
+        records: &Records,
+        chunks: &[(usize, usize)],
+    ) -> Self {
+        if chunks.len() < 2 {
+            return Self::build(source, parser, records);
+        }
+        let format = parser.format().cloned();
+        let config = parser.config().clone();
+        let per_chunk: Vec<Vec<TemplateStats>> = thread::scope(|scope| {
+            let workers: Vec<_> = chunks
+                .iter()
+                .map(|&(start, end)| {
+                    let format = format.as_ref();
+                    let config = config.clone();
+                    // Timestamp parsers remember what they've seen, so each thread needs its own.
+                    let records = records.clone();
+                    scope.spawn(move || {
+                        // A bare tree doesn't keep template strings around, which we have no use for here.
+                        let mut tree = ParseTree::with_config(config);
+                        count_records(&source[start..end], start, &records, |record| {
+                            tree.template_id(record, format)
+                        })
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("template counting thread panicked"))
+                .collect()
+        });
+
+        let mut stats: Vec<TemplateStats> = Vec::new();
+        for chunk_stats in per_chunk {
+            for local in chunk_stats.into_iter().filter(|local| local.count > 0) {
+                let record = &source[records.record_from(source, local.first_seen)];
+                let template_id = match parser.template_id(&record.to_str_lossy()) {
+                    Some(template_id) => template_id,
+                    None => continue,
+                };
+                if template_id >= stats.len() {
+                    stats.resize(template_id + 1, TemplateStats::default());
+                }
+                let global = &mut stats[template_id];
+                global.count += local.count;
+                global.first_seen = global.first_seen.min(local.first_seen);
+            }
+        }
+        Self {
+            stats,
+            file_len: source.len(),
+        }
+    }
+
+    pub fn count(&self, template_id: usize) -> usize {
+        self.stats.get(template_id).map_or(0, |stats| stats.count)
+    }
+
+    /// Total number of records that drain could make sense of.
+    pub fn total(&self) -> usize {
+        self.stats.iter().map(|stats| stats.count).sum()
+    }
+
+    /// A template is rare if it occurs fewer than `below` times, or if it first shows up after
+    /// `after_pct` percent of the file. Templates the index never saw are rare too.
+    pub fn is_rare(&self, template_id: usize, below: usize, after_pct: Option<f64>) -> bool {
+        let stats = match self.stats.get(template_id) {
+            Some(stats) => stats,
+            None => return true,
+        };
+        let late = after_pct.is_some_and(|pct| {
+            stats.first_seen as f64 >= self.file_len as f64 * pct.clamp(0.0, 100.0) / 100.0
+        });
+        stats.count < below || late
+    }
+}
+
+/// Writes a line of JSON with the count and first byte offset of every template in `source`, most common first.
+pub fn print_templates(
+    source: &[u8],
+    parser: &mut RecordParser,
+    records: &Records,
+    out: impl Write,
+) -> io::Result<()> {
+    let index = TemplateIndex::build_parallel(source, parser, records, default_threads());
+    let mut by_count: Vec<_> = index
+        .stats
+        .iter()
+        .enumerate()
+        .filter(|(_, stats)| stats.count > 0)
+        .collect();
+    by_count.sort_by_key(|&(template_id, stats)| (std::cmp::Reverse(stats.count), template_id));
+    let mut out = BufWriter::new(out);
+    for (template_id, stats) in by_count {
+        let obj = json_object! {
+        template: parser.templates[template_id].as_str(),
+        count: stats.count,
+        first_seen: stats.first_seen};
+        obj.write_json(&mut out)?;
+        out.write_all(b"\n")?;
+    }
+    out.flush()
+}
+
+/// As many threads as the machine has, for [`TemplateIndex::build_parallel`].
+pub fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Stats of each record of `source`, which starts at `base_offset` in the file.
+fn count_records(
+    source: &[u8],
+    base_offset: usize,
+    records: &Records,
+    mut template_id: impl FnMut(&str) -> Option<usize>,
+) -> Vec<TemplateStats> {
+    let mut stats: Vec<TemplateStats> = Vec::new();
+    for record in records.forward(source, 0) {
+        // The empty line after the last newline isn't a record.
+        if record.start == source.len() {
+            break;
+        }
+        let text = source[record.clone()].trim_end_with(|c| c == '\n' || c == '\r');
+        if let Some(template_id) = template_id(&text.to_str_lossy()) {
+            if template_id >= stats.len() {
+                stats.resize(template_id + 1, TemplateStats::default());
+            }
+            let stats = &mut stats[template_id];
+            if stats.count == 0 {
+                stats.first_seen = base_offset + record.start;
+            }
+            stats.count += 1;
+        }
+    }
+    stats
+}
+
+/// Splits `source` into about `n` (start, end) ranges, each beginning at the start of a record.
+fn split_chunks(source: &[u8], n: usize, records: &Records) -> Vec<(usize, usize)> {
+    let n = n.max(1);
+    let mut chunks = Vec::with_capacity(n);
+    let mut start = 0;
+    for i in 1..n {
+        let end = match source[source.len() * i / n..].find_byte(b'\n') {
+            Some(idx) => source.len() * i / n + idx + 1,
+            None => break,
+        };
+        let end = match end < source.len() && !records.is_start(source, end) {
+            true => (records.record_from(source, end).end + 1).min(source.len()),
+            false => end,
+        };
+        if end <= start || end == source.len() {
+            continue;
+        }
+        chunks.push((start, end));
+        start = end;
+    }
+    chunks.push((start, source.len()));
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn print() {
+        let source = b"disk on fire\nconnected to host 1\nconnected to host 2\n";
+        let mut out = Vec::new();
+        print_templates(
+            source,
+            &mut RecordParser::default(),
+            &Records::default(),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"template\":\"connected to host <*>\",\"count\":2,\"first_seen\":13}\n\
+             {\"template\":\"disk on fire\",\"count\":1,\"first_seen\":0}\n"
+        );
+    }
+
+    #[test]
+    fn rare_templates() {
+        let source = b"connected to host 1\nconnected to host 2\nconnected to host 3\nconnected to host 4\ndisk on fire\nconnected to host 5\n";
+        let mut parser = RecordParser::default();
+        let index = TemplateIndex::build(source, &mut parser, &Records::default());
+        assert_eq!(parser.templates, ["connected to host <*>", "disk on fire"]);
+        assert_eq!(
+            index.stats[0],
+            TemplateStats {
+                count: 5,
+                first_seen: 0
+            }
+        );
+        assert_eq!(
+            index.stats[1],
+            TemplateStats {
+                count: 1,
+                first_seen: 80
+            }
+        );
+        assert!(!index.is_rare(0, 3, None));
+        assert!(index.is_rare(1, 3, None));
+        assert!(!index.is_rare(1, 1, None));
+        assert!(index.is_rare(1, 1, Some(50.0)));
+        assert!(!index.is_rare(0, 1, Some(50.0)));
+        assert!(index.is_rare(2, 1, None));
+    }
+
+    #[test]
+    fn parallel_matches_sequential() {
+        let mut source = Vec::new();
+        for i in 0..30_000 {
+            source.extend_from_slice(format!("connected to host {i}\n").as_bytes());
+            if i % 10_000 == 7 {
+                source.extend_from_slice(b"disk on fire\n\tat the disk\n");
+            }
+        }
+        let records = Records::default();
+        let mut sequential_parser = RecordParser::default();
+        let sequential = TemplateIndex::build(&source, &mut sequential_parser, &records);
+
+        let chunks = split_chunks(&source, 3, &records);
+        assert_eq!(chunks.len(), 3);
+        for &(start, _) in &chunks {
+            assert!(start == 0 || source[start - 1] == b'\n');
+            assert!(records.is_start(&source, start));
+        }
+
+        let mut parallel_parser = RecordParser::default();
+        let parallel =
+            TemplateIndex::build_chunked(&source, &mut parallel_parser, &records, &chunks);
+        assert_eq!(parallel_parser.templates, sequential_parser.templates);
+        assert_eq!(parallel.stats, sequential.stats);
+    }
+}
@@ -0,0 +1,1004 @@
+//! Finding the timestamps of records in a log file, and records by timestamp.
+
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::ops::Range;
+
+use bstr::ByteSlice;
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Days, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use regex::Regex;
+use thiserror::Error;
+use tracing::{debug, info};
+
+use crate::dateparser::datetime::{parse_partial, Family, Parse};
+use crate::fields::Locator;
+
+pub type FileOffset = usize;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TsBinSearchError {
+    FailedParseTs,
+    /// Time goes back by more than [`MAX_DISORDER`] somewhere up to the record at this offset, so there's
+    /// no telling where in the file a time is.
+    NotTimeOrdered(FileOffset),
+}
+
+/// How far out of order records can be written, as by the threads of a busy service, before a file
+/// counts as not being in time order.
+const MAX_DISORDER: TimeDelta = TimeDelta::minutes(1);
+
+/// Most lines looked at on either side of where the binary search lands.
+const SCAN_LINES: usize = 10_000;
+
+/// How far into a line a timestamp is looked for.
+const LINE_WINDOW: usize = 1024;
+
+/// A strftime-style pattern like `%Y-%m-%d|%H:%M:%S%.3f`, for timestamps the built-in formats don't know.
+#[derive(Debug, Clone)]
+pub struct TsFormat(String);
+
+#[derive(Error, Debug)]
+#[error("invalid timestamp format {0:?}")]
+pub struct TsFormatError(String);
+
+impl TsFormat {
+    pub fn new(format: &str) -> Result<Self, TsFormatError> {
+        match StrftimeItems::new(format).any(|item| item == Item::Error) {
+            true => Err(TsFormatError(format.to_string())),
+            false => Ok(Self(format.to_string())),
+        }
+    }
+
+    /// Parses the start of `s`, ignoring whatever follows the timestamp, and says how many bytes it took up.
+    /// Timestamps without an offset are in `tz`, and ones without a date are on `default_date`.
+    pub fn parse<Tz2: TimeZone>(
+        &self,
+        s: &str,
+        tz: &Tz2,
+        default_date: NaiveDate,
+    ) -> Option<(DateTime<Utc>, usize)> {
+        if let Ok((dt, rest)) = DateTime::parse_and_remainder(s, &self.0) {
+            return Some((dt.with_timezone(&Utc), s.len() - rest.len()));
+        }
+        let (naive, rest) = NaiveDateTime::parse_and_remainder(s, &self.0)
+            .or_else(|_| {
+                NaiveTime::parse_and_remainder(s, &self.0)
+                    .map(|(time, rest)| (default_date.and_time(time), rest))
+            })
+            .ok()?;
+        Some((
+            tz.from_local_datetime(&naive)
+                .earliest()?
+                .with_timezone(&Utc),
+            s.len() - rest.len(),
+        ))
+    }
+}
+
+impl std::str::FromStr for TsFormat {
+    type Err = TsFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+/// Where in their lines the timestamps of a file are: `words` words starting at the `field`th place in the line
+/// a timestamp could start. Counted that way rather than in bytes so that e.g. levels of different lengths
+/// before the timestamp don't throw it off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Column {
+    pub field: usize,
+    pub words: usize,
+}
+
+/// How far into a line a timestamp can start, when we have to look for it.
+const SCAN_WINDOW: usize = 128;
+
+/// Most words a timestamp can be, as in `May 8, 2009 5:57:51 PM`.
+const MAX_WORDS: usize = 5;
+
+/// Timestamps never contain these, but they often surround one, like `[...]` or `"ts":"..."`.
+const TERMINATORS: &[char] = &['"', '\'', '[', ']', '<', '>', '(', ')', '{', '}', '|'];
+
+/// Byte offsets in `line` where a timestamp could start: each letter or digit that follows something
+/// which can't be inside a timestamp.
+fn field_starts(line: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut prev: Option<char> = None;
+    line.char_indices()
+        .filter(move |&(_, c)| {
+            let starts = c.is_alphanumeric()
+                && prev.is_none_or(|p| !(p.is_alphanumeric() || ":.-/+,".contains(p)));
+            prev = Some(c);
+            starts
+        })
+        .map(|(i, _)| i)
+        .take_while(|&i| i < SCAN_WINDOW)
+}
+
+/// The first `words` space separated words of `s`, up to the first terminator.
+/// None if there aren't that many.
+fn first_words(s: &str, words: usize) -> Option<&str> {
+    let s = &s[..s.find(TERMINATORS).unwrap_or(s.len())];
+    let mut end = 0;
+    for _ in 0..words {
+        let word_start = s.len() - s[end..].trim_start_matches(' ').len();
+        if word_start == s.len() {
+            return None;
+        }
+        end = s[word_start..]
+            .find(' ')
+            .map_or(s.len(), |i| word_start + i);
+    }
+    Some(&s[..end])
+}
+
+/// Up to `LINE_WINDOW` bytes of the line starting at `start_offset`, cut at the newline or at invalid utf8.
+fn line_window(s: &[u8], start_offset: FileOffset) -> &str {
+    let window = &s[start_offset..(start_offset + LINE_WINDOW).min(s.len())];
+    let window = &window[..window.find_byte(b'\n').unwrap_or(window.len())];
+    match std::str::from_utf8(window) {
+        Ok(line) => line,
+        Err(e) => std::str::from_utf8(&window[..e.valid_up_to()]).unwrap(),
+    }
+}
+
+/// Find first character of line starting at or before byte_offset.
+pub fn find_line_starting_before(s: &[u8], byte_offset: usize) -> usize {
+    // mmap[0..byte_offset].iter().rev().find('\n').unwrap_or(0)
+    let bytes_before_offset_of_newline = s[..byte_offset] // or s[0..byte_offset]
+        .iter()
+        .rev()
+        .enumerate()
+        .find_map(|(index, val)| match val {
+            val if *val == b'\n' => Some(index),
+            _ => None,
+        })
+        .unwrap_or(byte_offset);
+    // 4096 -> 3486 should find byte 10. TODO Unit test.
+    let idx = byte_offset - bytes_before_offset_of_newline;
+    assert!(idx == 0 || s[idx - 1] == b'\n');
+    idx
+}
+
+/// A time going back by more than this means the log has gone past midnight, rather than a few records
+/// having been written out of order.
+const ROLLOVER_JUMP: TimeDelta = TimeDelta::hours(1);
+
+/// Where the days start in a log whose timestamps are only times of day. Without it a log that goes past
+/// midnight looks to go back in time, so 00:01 sorts before the 23:59 above it and [`TimestampParser::bin_search`]
+/// gets lost.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DayIndex {
+    /// Offset of the first record of each day after the first.
+    day_starts: Vec<FileOffset>,
+}
+
+impl DayIndex {
+    /// Reads the timestamp of every line of `source`, which must be time-only (see
+    /// [`TimestampParser::is_time_only`]), and starts a new day wherever time jumps back by more than an hour.
+    /// `parser` should be a fresh one, as its own day index would be applied.
+    pub fn build(source: &[u8], parser: &TimestampParser) -> Self {
+        // Any date will do, only the times are compared.
+        let date = NaiveDate::default();
+        let mut day_starts = Vec::new();
+        let mut prev: Option<DateTime<Utc>> = None;
+        let mut line_start = 0;
+        while line_start < source.len() {
+            if let Some(ts) = parser.parse_at(source, line_start, date) {
+                if prev.is_some_and(|prev| prev - ts > ROLLOVER_JUMP) {
+                    day_starts.push(line_start);
+                }
+                prev = Some(ts);
+            }
+            line_start = match source[line_start..].find_byte(b'\n') {
+                Some(newline) => line_start + newline + 1,
+                None => source.len(),
+            };
+        }
+        Self { day_starts }
+    }
+
+    /// How many midnights the log has gone past by `offset`.
+    pub fn days_before(&self, offset: FileOffset) -> usize {
+        self.day_starts.partition_point(|&start| start <= offset)
+    }
+
+    /// How many days after the first the log goes on for.
+    pub fn len(&self) -> usize {
+        self.day_starts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.day_starts.is_empty()
+    }
+}
+
+/// How the timestamp at the start of each record is shown. The file itself is never changed, only what's on screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeDisplay {
+    #[default]
+    AsWritten,
+    Local,
+    Utc,
+    /// Time since the record with this timestamp, like `+00:01:32.450`.
+    Relative(DateTime<Utc>),
+}
+
+impl TimeDisplay {
+    /// What to show instead of `ts`, None to leave it as written.
+    pub fn format(&self, ts: DateTime<Utc>) -> Option<String> {
+        match self {
+            TimeDisplay::AsWritten => None,
+            TimeDisplay::Local => Some(
+                ts.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S%.3f%:z")
+                    .to_string(),
+            ),
+            TimeDisplay::Utc => Some(ts.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()),
+            TimeDisplay::Relative(anchor) => {
+                let delta = ts - *anchor;
+                let sign = if delta < chrono::Duration::zero() {
+                    '-'
+                } else {
+                    '+'
+                };
+                let millis = delta.num_milliseconds().unsigned_abs();
+                Some(format!(
+                    "{sign}{:02}:{:02}:{:02}.{:03}",
+                    millis / 3_600_000,
+                    millis / 60_000 % 60,
+                    millis / 1000 % 60,
+                    millis % 1000
+                ))
+            }
+        }
+    }
+}
+
+/// Parses the timestamps of one file. Remembers which format family the last timestamp was in and tries
+/// that first, since a file nearly always sticks to one; that makes a parse several times cheaper.
+#[derive(Debug, Default, Clone)]
+pub struct TimestampParser {
+    hint: Cell<Option<Family>>,
+    /// Where timestamps of this file are, once one has been found.
+    column: Cell<Option<Column>>,
+    format: Option<TsFormat>,
+    locator: Option<Locator>,
+    /// Zone of timestamps that don't say, local time if None.
+    tz: Option<Tz>,
+    days: DayIndex,
+}
+
+impl TimestampParser {
+    /// Parse timestamps with `format` instead of guessing.
+    pub fn with_format(mut self, format: Option<TsFormat>) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Look for timestamps wherever `locator` matches in the line instead of at its start.
+    /// The timestamp is whatever the locator finds, see [`Locator`].
+    pub fn with_locator(mut self, locator: Option<Locator>) -> Self {
+        self.locator = locator;
+        self
+    }
+
+    /// Take timestamps without an offset to be in `tz` rather than local time.
+    pub fn with_tz(mut self, tz: Option<Tz>) -> Self {
+        self.tz = tz;
+        self
+    }
+
+    /// The format family timestamps of this file have been in so far.
+    pub fn family(&self) -> Option<Family> {
+        self.hint.get()
+    }
+
+    pub fn column(&self) -> Option<Column> {
+        self.column.get()
+    }
+
+    /// Date the timestamps without one after each midnight, once the log has been indexed.
+    pub fn set_days(&mut self, days: DayIndex) {
+        self.days = days;
+    }
+
+    pub fn days(&self) -> &DayIndex {
+        &self.days
+    }
+
+    /// Whether the timestamp of the line at `start_offset` is only a time of day, so takes its date from
+    /// whatever it's given.
+    pub fn is_time_only(&self, s: &[u8], start_offset: FileOffset) -> bool {
+        let date = NaiveDate::default();
+        match (
+            self.parse_at(s, start_offset, date),
+            self.parse_at(s, start_offset, date + Days::new(1)),
+        ) {
+            (Some(a), Some(b)) => a != b,
+            _ => false,
+        }
+    }
+
+    /// Timestamp of the line at `start_offset`. If it has no date, it's taken to be `default_date` plus
+    /// however many midnights the [`DayIndex`] says have gone by, so `default_date` is the date of the first record.
+    pub fn parse_at(
+        &self,
+        s: &[u8],
+        start_offset: FileOffset,
+        default_date: NaiveDate,
+    ) -> Option<DateTime<Utc>> {
+        self.locate_at(s, start_offset, default_date)
+            .map(|(_, ts)| ts)
+    }
+
+    /// Like [`Self::parse_at`], but also says which bytes of the line the timestamp was, relative to `start_offset`.
+    pub fn locate_at(
+        &self,
+        s: &[u8],
+        start_offset: FileOffset,
+        default_date: NaiveDate,
+    ) -> Option<(Range<usize>, DateTime<Utc>)> {
+        let default_date = default_date
+            .checked_add_days(Days::new(self.days.days_before(start_offset) as u64))
+            .unwrap_or(default_date);
+        let line = line_window(s, start_offset);
+        let (offset, line) = match &self.locator {
+            Some(locator) => {
+                let m = locator.find(line)?;
+                (m.start(), m.as_str())
+            }
+            None => (0, line),
+        };
+        if let Some(format) = &self.format {
+            let (ts, len) = match &self.tz {
+                Some(tz) => format.parse(line, tz, default_date),
+                None => format.parse(line, &Local, default_date),
+            }?;
+            return Some((offset..offset + len, ts));
+        }
+        if self.locator.is_some() {
+            return Some((
+                offset..offset + line.len(),
+                self.parse_guessing(line, default_date)?,
+            ));
+        }
+        if let Some(column) = self.column.get() {
+            if let Some(found) = self.parse_column(line, column, default_date) {
+                return Some(found);
+            }
+        }
+        // Not every line has it in the same place, e.g. after a prefix some lines don't have, so look again.
+        // Lines without any, like the rest of a stack trace, leave the one found before alone.
+        let column = self.locate(line, default_date)?;
+        debug!("Timestamps are at {:?}", column);
+        self.column.set(Some(column));
+        self.parse_column(line, column, default_date)
+    }
+
+    fn parse_column(
+        &self,
+        line: &str,
+        column: Column,
+        default_date: NaiveDate,
+    ) -> Option<(Range<usize>, DateTime<Utc>)> {
+        let start = field_starts(line).nth(column.field)?;
+        let candidate = first_words(&line[start..], column.words)?;
+        Some((
+            start..start + candidate.len(),
+            self.parse_guessing(candidate, default_date)?,
+        ))
+    }
+
+    /// Where the leftmost timestamp in `line` is.
+    fn locate(&self, line: &str, default_date: NaiveDate) -> Option<Column> {
+        for (field, start) in field_starts(line).enumerate() {
+            // Longest first, else a date could be parsed without its time, or a time without its zone.
+            for words in (1..=MAX_WORDS).rev() {
+                let Some(candidate) = first_words(&line[start..], words) else {
+                    continue;
+                };
+                if self.parse_guessing(candidate, default_date).is_some() {
+                    return Some(Column { field, words });
+                }
+            }
+        }
+        None
+    }
+
+    fn parse_guessing(&self, s: &str, default_date: NaiveDate) -> Option<DateTime<Utc>> {
+        debug!("Parsing: {}", s);
+        let (partial, family) = parse_partial(s, self.hint.get()).ok()?;
+        // What the timestamp leaves out comes from the file: the day it's on, and midnight for a date alone.
+        let ts = match &self.tz {
+            Some(tz) => Parse::new(tz, Some(NaiveTime::MIN), Some(default_date)).resolve(partial),
+            None => Parse::new(&Local, Some(NaiveTime::MIN), Some(default_date)).resolve(partial),
+        }
+        .ok()?;
+        self.hint.set(Some(family));
+        Some(ts)
+    }
+
+    /// The closest line at or before `byte_offset` that has a timestamp, giving up after 1000 lines.
+    pub fn find_before(
+        &self,
+        s: &[u8],
+        mut byte_offset: FileOffset,
+        default_date: NaiveDate,
+    ) -> Option<(FileOffset, DateTime<Utc>)> {
+        let mut lines_try = 1000;
+        while lines_try > 0 {
+            let line_start = find_line_starting_before(s, byte_offset);
+            match self.parse_at(s, line_start, default_date) {
+                Some(ts) => {
+                    return Some((line_start, ts));
+                }
+                None if line_start == 0 => return None,
+                None => {
+                    byte_offset = line_start - 1;
+                    lines_try -= 1;
+                }
+            }
+        }
+        None
+    }
+
+    /// The closest line at or after `byte_offset` that has a timestamp, giving up after 1000 lines.
+    pub fn find_after(
+        &self,
+        s: &[u8],
+        byte_offset: FileOffset,
+        default_date: NaiveDate,
+    ) -> Option<(FileOffset, DateTime<Utc>)> {
+        let mut line_start = match byte_offset == 0 || s.get(byte_offset - 1) == Some(&b'\n') {
+            true => byte_offset,
+            false => next_line_start(s, byte_offset),
+        };
+        for _ in 0..1000 {
+            if line_start >= s.len() {
+                return None;
+            }
+            if let Some(ts) = self.parse_at(s, line_start, default_date) {
+                return Some((line_start, ts));
+            }
+            line_start = next_line_start(s, line_start);
+        }
+        None
+    }
+
+    /// Offset of the first line with a timestamp at or after `dt`, or the end of `s` if there is none.
+    /// Records may be written up to [`MAX_DISORDER`] out of order; past that the file is reported as not
+    /// being in time order rather than landing somewhere wrong.
+    /// `default_date` is as for [`Self::parse_at`].
+    pub fn bin_search(
+        &self,
+        s: &[u8],
+        dt: &DateTime<Utc>,
+        default_date: NaiveDate,
+    ) -> Result<FileOffset, TsBinSearchError> {
+        let first = self
+            .find_after(s, 0, default_date)
+            .ok_or(TsBinSearchError::FailedParseTs)?;
+        // Every timestamp the search looks at, to check they're in order at least with each other.
+        let mut probes = vec![first];
+        probes.extend(self.find_before(s, s.len().saturating_sub(1), default_date));
+        // Lines before `low` are before `dt`, the line at `high` is at or after it.
+        let mut low: usize = 0;
+        let mut high: usize = s.len();
+        while low < high {
+            let middle = low + (high - low) / 2;
+            info!("Bin search: low: {}, mid: {}, high: {}", low, middle, high);
+            // A long stretch without timestamps, like a stack trace, is looked past in whichever direction works.
+            let probe = self
+                .find_before(s, middle, default_date)
+                .filter(|&(line_start, _)| line_start >= low)
+                .or_else(|| {
+                    self.find_after(s, middle, default_date)
+                        .filter(|&(line_start, _)| line_start < high)
+                });
+            let Some((line_start, ts)) = probe else {
+                break;
+            };
+            probes.push((line_start, ts));
+            info!("Comparing tses {} and {}", ts, dt);
+            match ts < *dt {
+                true => low = next_line_start(s, line_start),
+                false => high = line_start,
+            }
+        }
+        probes.sort_by_key(|&(line_start, _)| line_start);
+        let mut latest = first.1;
+        for (line_start, ts) in probes {
+            if latest - ts > MAX_DISORDER {
+                return Err(TsBinSearchError::NotTimeOrdered(line_start));
+            }
+            latest = latest.max(ts);
+        }
+        self.refine(s, dt, default_date, high)
+    }
+
+    /// Looks around `landed` for records written out of order: the first line at or after `dt` may be a bit
+    /// before it. Stops once timestamps are more than [`MAX_DISORDER`] away from `dt`, or after [`SCAN_LINES`].
+    fn refine(
+        &self,
+        s: &[u8],
+        dt: &DateTime<Utc>,
+        default_date: NaiveDate,
+        landed: FileOffset,
+    ) -> Result<FileOffset, TsBinSearchError> {
+        let mut first = landed;
+        // Going back, time should only go down; anything much later than a record after it means the file
+        // went back in time at that record.
+        let mut after = self
+            .parse_at(s, landed, default_date)
+            .map(|ts| (landed, ts));
+        let mut line_start = landed;
+        for _ in 0..SCAN_LINES {
+            if line_start == 0 {
+                break;
+            }
+            line_start = find_line_starting_before(s, line_start - 1);
+            let Some(ts) = self.parse_at(s, line_start, default_date) else {
+                continue;
+            };
+            if let Some((after_start, after_ts)) = after {
+                if ts - after_ts > MAX_DISORDER {
+                    return Err(TsBinSearchError::NotTimeOrdered(after_start));
+                }
+            }
+            if ts >= *dt {
+                first = line_start;
+            } else if *dt - ts > MAX_DISORDER {
+                break;
+            }
+            if after.is_none_or(|(_, after_ts)| ts < after_ts) {
+                after = Some((line_start, ts));
+            }
+        }
+
+        let mut before: Option<DateTime<Utc>> = None;
+        let mut line_start = landed;
+        for _ in 0..SCAN_LINES {
+            if line_start >= s.len() {
+                break;
+            }
+            if let Some(ts) = self.parse_at(s, line_start, default_date) {
+                if before.is_some_and(|before| before - ts > MAX_DISORDER) {
+                    return Err(TsBinSearchError::NotTimeOrdered(line_start));
+                }
+                if ts - *dt > MAX_DISORDER {
+                    break;
+                }
+                before = Some(before.map_or(ts, |before| before.max(ts)));
+            }
+            line_start = next_line_start(s, line_start);
+        }
+        Ok(first)
+    }
+}
+
+/// Start of the line after the one `offset` is in, or the end of `s`.
+fn next_line_start(s: &[u8], offset: FileOffset) -> FileOffset {
+    match s[offset..].find_byte(b'\n') {
+        Some(newline) => offset + newline + 1,
+        None => s.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::FixedOffset;
+
+    use super::*;
+
+    static LINES: &str = "03/22/2022 08:51:06 INFO   :...mylogline
+03/22/2022 08:51:08 INFO   :...mylogline";
+
+    fn bin_search(s: &[u8], dt: &DateTime<Utc>) -> Result<FileOffset, TsBinSearchError> {
+        TimestampParser::default().bin_search(s, dt, dt.date_naive())
+    }
+
+    fn local(s: &str) -> DateTime<Utc> {
+        chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S")
+            .unwrap()
+            .and_local_timezone(Local)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_first() {
+        assert_eq!(
+            bin_search(
+                LINES.as_bytes(),
+                &DateTime::<FixedOffset>::parse_from_rfc3339("2022-03-22T08:51:06Z")
+                    .unwrap()
+                    .with_timezone(&Utc)
+            )
+            .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_dt() {
+        // let dt = crate::dateparser::parse_with_timezone("09:42:21.0090980809 WORD", &Local);
+        // TODO switch to qsv-dateparser to get support for fractions on the timestamps?
+        // Or just hardcode a filter out of them, because we don't need the tenths?
+        assert!(crate::dateparser::parse_with_timezone("09:42:21", &Local, None).is_ok());
+        assert!(crate::dateparser::parse_with_timezone("09:42:21.401", &Local, None).is_ok());
+        assert!(crate::dateparser::parse_with_timezone("09:42:21.99923", &Local, None).is_ok());
+    }
+
+    #[test]
+    fn test_second() {
+        assert_eq!(
+            bin_search(LINES.as_bytes(), &local("2022-03-22T08:51:08")).unwrap(),
+            41
+        );
+    }
+
+    #[test]
+    fn test_between() {
+        assert_eq!(
+            bin_search(LINES.as_bytes(), &local("2022-03-22T08:51:07")).unwrap(),
+            41
+        );
+    }
+
+    #[test]
+    fn test_after() {
+        assert_eq!(
+            bin_search(LINES.as_bytes(), &local("2022-03-22T08:51:09")).unwrap(),
+            LINES.len()
+        );
+    }
+
+    #[test]
+    fn test_before() {
+        assert_eq!(
+            bin_search(LINES.as_bytes(), &local("2022-03-22T08:51:00")).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn custom_format() {
+        let lines = b"[2022-03-22|08:51:06.123] started\n[2022-03-22|08:51:07.000] stopped\n";
+        let today = Local::now().date_naive();
+        let parser = TimestampParser::default()
+            .with_format(Some(TsFormat::new("[%Y-%m-%d|%H:%M:%S%.3f]").unwrap()));
+        assert_eq!(
+            parser.parse_at(lines, 0, today),
+            Some(local("2022-03-22T08:51:06") + chrono::Duration::milliseconds(123))
+        );
+        assert_eq!(
+            parser
+                .bin_search(lines, &local("2022-03-22T08:51:06"), today)
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            parser
+                .bin_search(lines, &local("2022-03-22T08:51:07"), today)
+                .unwrap(),
+            34
+        );
+        assert!(TsFormat::new("%Y-%Q").is_err());
+
+        let time_only = TimestampParser::default()
+            .with_format(Some(TsFormat::new("%H:%M:%S").unwrap()))
+            .with_locator(Some(Locator::new(Regex::new(r"at (\S+)").unwrap())));
+        let date = NaiveDate::from_ymd_opt(2022, 3, 22).unwrap();
+        assert_eq!(
+            time_only.parse_at(b"job 7 finished at 08:51:06\n", 0, date),
+            Some(local("2022-03-22T08:51:06"))
+        );
+    }
+
+    #[test]
+    fn located_timestamp() {
+        // Unix timestamps are one of the built-in formats, so they don't need a --ts-format.
+        let parser = TimestampParser::default()
+            .with_locator(Some(Locator::new(Regex::new(r"ts=(\d+)").unwrap())));
+        let today = Local::now().date_naive();
+        assert_eq!(
+            parser.parse_at(b"GET / ts=1687208330123\nts=0", 0, today),
+            DateTime::from_timestamp_millis(1687208330123)
+        );
+        assert_eq!(
+            parser.parse_at(b"GET / took 5ms\nts=1687208330123", 0, today),
+            None
+        );
+    }
+
+    #[test]
+    fn timestamp_anywhere() {
+        let date = NaiveDate::from_ymd_opt(2022, 3, 22).unwrap();
+        let utc = |s| {
+            DateTime::<FixedOffset>::parse_from_rfc3339(s)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let cases: [(&[u8], _, _); 4] = [
+            (
+                b"INFO 2022-03-22T08:51:06Z started\nDEBUG 2022-03-22T08:51:07Z running",
+                Column { field: 1, words: 1 },
+                "2022-03-22T08:51:07Z",
+            ),
+            (
+                b"<34>1 2022-03-22T08:51:06.003Z mymachine su - ID47 - 'su root' failed\n<34>1 2022-03-22T08:51:07Z mymachine",
+                Column { field: 2, words: 1 },
+                "2022-03-22T08:51:07Z",
+            ),
+            (
+                b"{\"ts\":\"2022-03-22T08:51:06Z\",\"msg\":\"started\"}\n{\"ts\":\"2022-03-22T08:51:07Z\"}",
+                Column { field: 1, words: 1 },
+                "2022-03-22T08:51:07Z",
+            ),
+            (
+                b"[main] 03/22/2022 08:51:06 started\n[main] 03/22/2022 08:51:07 running",
+                Column { field: 1, words: 2 },
+                "2022-03-22T08:51:07",
+            ),
+        ];
+        for (lines, column, second) in cases {
+            let parser = TimestampParser::default();
+            assert!(parser.parse_at(lines, 0, date).is_some());
+            assert_eq!(parser.column(), Some(column));
+            let second = match second.ends_with('Z') {
+                true => utc(second),
+                false => local(second),
+            };
+            let next_line = lines.find_byte(b'\n').unwrap() + 1;
+            assert_eq!(parser.parse_at(lines, next_line, date), Some(second));
+        }
+    }
+
+    #[test]
+    fn column_moves() {
+        let date = NaiveDate::from_ymd_opt(2022, 3, 22).unwrap();
+        let lines = b"[Tue Mar 22 08:51:06 2022] started\n[error] [Tue Mar 22 08:51:07 2022] failed\nno time here\n[Tue Mar 22 08:51:08 2022] done";
+        let parser = TimestampParser::default();
+        assert_eq!(
+            parser.parse_at(lines, 0, date),
+            Some(local("2022-03-22T08:51:06"))
+        );
+        let column = parser.column();
+        assert!(column.is_some());
+
+        let at = |line: &str| lines.find(line).unwrap();
+        assert_eq!(
+            parser.parse_at(lines, at("[error]"), date),
+            Some(local("2022-03-22T08:51:07"))
+        );
+        assert_ne!(parser.column(), column);
+        let moved = parser.column();
+        assert_eq!(parser.parse_at(lines, at("no time"), date), None);
+        assert_eq!(parser.column(), moved);
+        assert_eq!(
+            parser.parse_at(lines, at("[Tue Mar 22 08:51:08"), date),
+            Some(local("2022-03-22T08:51:08"))
+        );
+        assert_eq!(parser.column(), column);
+    }
+
+    #[test]
+    fn timezones() {
+        let date = NaiveDate::from_ymd_opt(2022, 3, 22).unwrap();
+        let berlin = TimestampParser::default().with_tz(Some(chrono_tz::Europe::Berlin));
+        // CET is +1 until the end of March.
+        assert_eq!(
+            berlin.parse_at(b"03/22/2022 08:51:06 started", 0, date),
+            DateTime::from_timestamp(1647935466, 0)
+        );
+        let formatted = berlin.with_format(Some(TsFormat::new("%H:%M:%S").unwrap()));
+        assert_eq!(
+            formatted.parse_at(b"08:51:06 started", 0, date),
+            DateTime::from_timestamp(1647935466, 0)
+        );
+        // An explicit offset wins.
+        assert_eq!(
+            TimestampParser::default().parse_at(b"2022-03-22 08:51:06 JST started", 0, date),
+            DateTime::from_timestamp(1647906666, 0)
+        );
+    }
+
+    #[test]
+    fn remembers_family() {
+        let parser = TimestampParser::default();
+        let today = Local::now().date_naive();
+        assert_eq!(parser.family(), None);
+        assert!(parser.parse_at(LINES.as_bytes(), 0, today).is_some());
+        assert_eq!(parser.family(), Some(Family::SlashMdy));
+        // A line in another format is still parsed, and that's what gets tried first from then on.
+        let line = b"08:51:09 UTC   :...mylogline";
+        assert!(parser.parse_at(line, 0, today).is_some());
+        assert_eq!(parser.family(), Some(Family::Hms));
+        assert!(parser.parse_at(b"no timestamp here", 0, today).is_none());
+        assert_eq!(parser.family(), Some(Family::Hms));
+    }
+
+    #[test]
+    fn time_display() {
+        let date = NaiveDate::from_ymd_opt(2022, 3, 22).unwrap();
+        let line = b"[main] 2022-03-22T08:51:06.003Z started";
+        let (range, ts) = TimestampParser::default().locate_at(line, 0, date).unwrap();
+        assert_eq!(&line[range], b"2022-03-22T08:51:06.003Z");
+        let formatted = TimestampParser::default()
+            .with_format(Some(TsFormat::new("%H:%M:%S").unwrap()))
+            .with_locator(Some(Locator::new(Regex::new(r"at (\S+)").unwrap())));
+        assert_eq!(
+            formatted
+                .locate_at(b"job 7 finished at 08:51:06 ok", 0, date)
+                .map(|(r, _)| r),
+            Some(18..26)
+        );
+
+        assert_eq!(TimeDisplay::AsWritten.format(ts), None);
+        assert_eq!(
+            TimeDisplay::Utc.format(ts).unwrap(),
+            "2022-03-22T08:51:06.003Z"
+        );
+        let anchor = ts - chrono::Duration::milliseconds(92_450);
+        assert_eq!(
+            TimeDisplay::Relative(anchor).format(ts).unwrap(),
+            "+00:01:32.450"
+        );
+        assert_eq!(
+            TimeDisplay::Relative(ts).format(anchor).unwrap(),
+            "-00:01:32.450"
+        );
+    }
+
+    #[test]
+    fn past_midnight() {
+        let lines = b"23:58:00 a\n23:59:30 b\n23:59:10 written late\n00:01:00 c\n00:02:00 d\n";
+        let date = NaiveDate::from_ymd_opt(2022, 3, 22).unwrap();
+        let next_day = |time| local(&format!("2022-03-23T{time}"));
+        let mut parser = TimestampParser::default();
+        assert!(parser.is_time_only(lines, 0));
+        assert!(!TimestampParser::default().is_time_only(LINES.as_bytes(), 0));
+
+        let days = DayIndex::build(lines, &TimestampParser::default());
+        let c = lines.find("00:01").unwrap();
+        assert_eq!(
+            days,
+            DayIndex {
+                day_starts: vec![c]
+            }
+        );
+        parser.set_days(days);
+        assert_eq!(parser.parse_at(lines, c, date), Some(next_day("00:01:00")));
+        assert_eq!(
+            parser.parse_at(lines, 0, date),
+            Some(local("2022-03-22T23:58:00"))
+        );
+        assert_eq!(
+            parser
+                .bin_search(lines, &next_day("00:01:00"), date)
+                .unwrap(),
+            c
+        );
+    }
+
+    #[test]
+    fn date_alone() {
+        // Neither the time of day nor the date it's parsed on matter.
+        let lines = b"2022-03-21 a\n2022-03-22 b\n";
+        let date = NaiveDate::from_ymd_opt(2030, 1, 1).unwrap();
+        let parser = TimestampParser::default();
+        assert!(!parser.is_time_only(lines, 0));
+        assert_eq!(
+            parser.parse_at(lines, 0, date),
+            Some(local("2022-03-21T00:00:00"))
+        );
+    }
+
+    #[test]
+    fn out_of_order() {
+        let date = NaiveDate::from_ymd_opt(2022, 3, 22).unwrap();
+        let at = |time| local(&format!("2022-03-22T{time}"));
+        let parser = TimestampParser::default();
+        // Threads finishing out of order, by a few seconds at most.
+        let times = [
+            "08:00:00", "08:00:03", "08:00:01", "08:00:02", "08:00:06", "08:00:04", "08:00:05",
+            "08:00:09", "08:00:07", "08:00:08",
+        ];
+        let lines: String = times.iter().map(|t| format!("{t} worker done\n")).collect();
+        let line = |i: usize| i * "08:00:00 worker done\n".len();
+        for (target, expected) in [("08:00:03", 1), ("08:00:04", 4), ("08:00:07", 7)] {
+            assert_eq!(
+                parser.bin_search(lines.as_bytes(), &at(target), date),
+                Ok(line(expected)),
+                "{target}"
+            );
+        }
+
+        // Two logs glued together, the second from an hour earlier.
+        let glued = format!("{lines}{}", lines.replace("08:", "07:"));
+        assert_eq!(
+            parser.bin_search(glued.as_bytes(), &at("08:00:05"), date),
+            Err(TsBinSearchError::NotTimeOrdered(lines.len()))
+        );
+
+        // A stack trace too long for find_before to look past.
+        let trace = "    at Foo.bar(Foo.java:1)\n".repeat(3000);
+        let lines = format!("08:00:00 failed\n{trace}08:00:01 retrying\n08:00:02 ok\n");
+        assert_eq!(
+            parser.bin_search(lines.as_bytes(), &at("08:00:01"), date),
+            Ok(lines.find("08:00:01").unwrap())
+        );
+        assert_eq!(
+            parser.bin_search(b"no timestamps\nhere\n", &at("08:00:01"), date),
+            Err(TsBinSearchError::FailedParseTs)
+        );
+    }
+
+    #[test]
+    fn fleet_formats() {
+        let date = NaiveDate::from_ymd_opt(2022, 3, 22).unwrap();
+        let utc = |s| {
+            DateTime::<FixedOffset>::parse_from_rfc3339(s)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let cases: [(&str, Family, &str); 8] = [
+            (
+                "<165>1 2003-10-11T22:14:15.003Z mymachine.example.com evntslog - ID47 - hi",
+                Family::Ymd,
+                "2003-10-11T22:14:15.003Z",
+            ),
+            (
+                "Mar 22 08:51:06 mymachine sshd[4242]: Accepted publickey",
+                Family::Syslog,
+                "2022-03-22T08:51:06Z",
+            ),
+            // Would be in the future, so it's from last year.
+            (
+                "Oct 11 22:14:15 mymachine su: 'su root' failed",
+                Family::Syslog,
+                "2021-10-11T22:14:15Z",
+            ),
+            (
+                "Mar  2 08:51:06.123456 mymachine systemd[1]: Started",
+                Family::Syslog,
+                "2022-03-02T08:51:06.123456Z",
+            ),
+            (
+                "127.0.0.1 - frank [10/Oct/2000:13:55:36 -0700] \"GET /apache_pb.gif HTTP/1.0\" 200 2326",
+                Family::Clf,
+                "2000-10-10T20:55:36Z",
+            ),
+            (
+                "I0322 08:51:06.123456   12345 controller.go:123] Reconciling",
+                Family::Klog,
+                "2022-03-22T08:51:06.123456Z",
+            ),
+            (
+                "2022/03/22 08:51:06 listening on :8080",
+                Family::SlashYmd,
+                "2022-03-22T08:51:06Z",
+            ),
+            (
+                "__REALTIME_TIMESTAMP=1647939066123456",
+                Family::UnixTimestamp,
+                "2022-03-22T08:51:06.123456Z",
+            ),
+        ];
+        for (line, family, ts) in cases {
+            let parser = TimestampParser::default().with_tz(Some(chrono_tz::UTC));
+            assert_eq!(
+                parser.parse_at(line.as_bytes(), 0, date),
+                Some(utc(ts)),
+                "{line}"
+            );
+            assert_eq!(parser.family(), Some(family), "{line}");
+        }
+    }
+}